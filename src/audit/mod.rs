@@ -0,0 +1,285 @@
+//! Persistent audit trail of logins and privileged actions
+//!
+//! Entries are written to the `audit_log` table and pruned according to
+//! `AuthConfig::audit_retention_days`.
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, QueryBuilder, Sqlite};
+use time::OffsetDateTime;
+
+use crate::error::Result;
+
+/// Whether the audited action succeeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+        }
+    }
+}
+
+/// A new audit entry; the timestamp is assigned when it is recorded
+#[derive(Debug, Clone)]
+pub struct AuditRecord {
+    /// Username, `token:<name>` for API tokens, or the attempted username for logins
+    pub actor: Option<String>,
+    pub ip: Option<String>,
+    /// Dotted action name, e.g. `atx.power`, `config.update`
+    pub action: String,
+    pub details: Option<serde_json::Value>,
+    pub outcome: AuditOutcome,
+    /// Error message for failed actions
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    /// Unix timestamp (seconds)
+    pub timestamp: i64,
+    pub actor: Option<String>,
+    pub ip: Option<String>,
+    pub action: String,
+    pub details: Option<serde_json::Value>,
+    pub outcome: AuditOutcome,
+    pub message: Option<String>,
+}
+
+type AuditRow = (
+    i64,
+    i64,
+    Option<String>,
+    Option<String>,
+    String,
+    Option<String>,
+    String,
+    Option<String>,
+);
+
+impl AuditEntry {
+    fn from_row(row: AuditRow) -> Self {
+        let (id, timestamp, actor, ip, action, details, outcome, message) = row;
+        Self {
+            id,
+            timestamp,
+            actor,
+            ip,
+            action,
+            details: details.and_then(|d| serde_json::from_str(&d).ok()),
+            outcome: if outcome == "success" {
+                AuditOutcome::Success
+            } else {
+                AuditOutcome::Failure
+            },
+            message,
+        }
+    }
+}
+
+/// Filters for [`AuditLog::query`]; all are optional and combined with AND
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    /// Exact action, or a prefix such as `msd` matching `msd.*`
+    pub action: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    /// Unix timestamp lower bound (inclusive)
+    pub since: Option<i64>,
+    /// Unix timestamp upper bound (inclusive)
+    pub until: Option<i64>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditPage {
+    pub total: i64,
+    pub entries: Vec<AuditEntry>,
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Clone)]
+pub struct AuditLog {
+    pool: Pool<Sqlite>,
+}
+
+impl AuditLog {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record(&self, record: AuditRecord) -> Result<()> {
+        let details = record
+            .details
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (timestamp, actor, ip, action, details, outcome, message)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )
+        .bind(OffsetDateTime::now_utc().unix_timestamp())
+        .bind(&record.actor)
+        .bind(&record.ip)
+        .bind(&record.action)
+        .bind(details)
+        .bind(record.outcome.as_str())
+        .bind(&record.message)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    fn push_filters<'a>(builder: &mut QueryBuilder<'a, Sqlite>, query: &'a AuditQuery) {
+        builder.push(" WHERE 1 = 1");
+        if let Some(ref actor) = query.actor {
+            builder.push(" AND actor = ").push_bind(actor);
+        }
+        if let Some(ref action) = query.action {
+            builder
+                .push(" AND (action = ")
+                .push_bind(action)
+                .push(" OR action LIKE ")
+                .push_bind(format!("{}.%", action))
+                .push(")");
+        }
+        if let Some(outcome) = query.outcome {
+            builder.push(" AND outcome = ").push_bind(outcome.as_str());
+        }
+        if let Some(since) = query.since {
+            builder.push(" AND timestamp >= ").push_bind(since);
+        }
+        if let Some(until) = query.until {
+            builder.push(" AND timestamp <= ").push_bind(until);
+        }
+    }
+
+    /// Newest entries first
+    pub async fn query(&self, query: &AuditQuery) -> Result<AuditPage> {
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM audit_log");
+        Self::push_filters(&mut count, query);
+        let (total,): (i64,) = count.build_query_as().fetch_one(&self.pool).await?;
+
+        let mut select = QueryBuilder::new(
+            "SELECT id, timestamp, actor, ip, action, details, outcome, message FROM audit_log",
+        );
+        Self::push_filters(&mut select, query);
+        select
+            .push(" ORDER BY id DESC LIMIT ")
+            .push_bind(query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE) as i64)
+            .push(" OFFSET ")
+            .push_bind(query.offset.unwrap_or(0) as i64);
+        let rows: Vec<AuditRow> = select.build_query_as().fetch_all(&self.pool).await?;
+
+        Ok(AuditPage {
+            total,
+            entries: rows.into_iter().map(AuditEntry::from_row).collect(),
+        })
+    }
+
+    /// Delete entries older than `retention_days`; 0 keeps everything.
+    pub async fn prune(&self, retention_days: u32) -> Result<u64> {
+        if retention_days == 0 {
+            return Ok(0);
+        }
+        let cutoff = OffsetDateTime::now_utc().unix_timestamp() - retention_days as i64 * 86400;
+        let result = sqlx::query("DELETE FROM audit_log WHERE timestamp < ?1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use tempfile::tempdir;
+
+    fn record(actor: &str, action: &str, outcome: AuditOutcome) -> AuditRecord {
+        AuditRecord {
+            actor: Some(actor.to_string()),
+            ip: Some("10.0.0.5".to_string()),
+            action: action.to_string(),
+            details: Some(serde_json::json!({"k": "v"})),
+            outcome,
+            message: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_and_filter() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let log = AuditLog::new(db.clone_pool());
+
+        log.record(record("admin", "auth.login", AuditOutcome::Success))
+            .await
+            .unwrap();
+        log.record(record("admin", "atx.power", AuditOutcome::Success))
+            .await
+            .unwrap();
+        log.record(record("ops", "msd.connect", AuditOutcome::Failure))
+            .await
+            .unwrap();
+        log.record(record("ops", "msd.disconnect", AuditOutcome::Success))
+            .await
+            .unwrap();
+
+        let all = log.query(&AuditQuery::default()).await.unwrap();
+        assert_eq!(all.total, 4);
+        assert_eq!(all.entries[0].action, "msd.disconnect");
+        assert_eq!(all.entries[0].details, Some(serde_json::json!({"k": "v"})));
+
+        let msd = log
+            .query(&AuditQuery {
+                action: Some("msd".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(msd.total, 2);
+
+        let failed = log
+            .query(&AuditQuery {
+                actor: Some("ops".to_string()),
+                outcome: Some(AuditOutcome::Failure),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(failed.total, 1);
+        assert_eq!(failed.entries[0].action, "msd.connect");
+
+        let page = log
+            .query(&AuditQuery {
+                limit: Some(1),
+                offset: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(page.total, 4);
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].action, "msd.connect");
+
+        assert_eq!(log.prune(30).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,231 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use crate::config::{AccessConfig, AccessRules, ConfigStore};
+use crate::error::{AppError, Result};
+use crate::events::{EventBus, SystemEvent};
+use crate::state::AppState;
+use crate::web::ErrorResponse;
+
+/// Parse a CIDR block, accepting a bare address as a single-host network.
+pub fn parse_cidr(s: &str) -> Result<IpNet> {
+    let s = s.trim();
+    s.parse::<IpNet>()
+        .or_else(|_| s.parse::<IpAddr>().map(IpNet::from))
+        .map_err(|_| AppError::BadRequest(format!("Invalid CIDR: {}", s)))
+}
+
+/// IPv4-mapped IPv6 peers (dual-stack listeners) are matched as IPv4.
+pub fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        v4 => v4,
+    }
+}
+
+/// Listener an [`AccessPolicy`] applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessService {
+    Web,
+    Rtsp,
+    RustDesk,
+}
+
+impl AccessService {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Web => "web",
+            Self::Rtsp => "rtsp",
+            Self::RustDesk => "rustdesk",
+        }
+    }
+
+    fn rules<'a>(&self, config: &'a AccessConfig) -> &'a AccessRules {
+        match self {
+            Self::Web => &config.web,
+            Self::Rtsp => &config.rtsp,
+            Self::RustDesk => &config.rustdesk,
+        }
+    }
+}
+
+/// Validate every entry of a rule list.
+pub fn validate_rules(rules: &AccessRules) -> Result<()> {
+    for cidr in rules.allow.iter().chain(&rules.deny) {
+        parse_cidr(cidr)?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+struct CompiledRules {
+    allow: Vec<IpNet>,
+    deny: Vec<IpNet>,
+}
+
+impl CompiledRules {
+    /// Invalid entries are skipped; they are rejected when the config is updated.
+    fn new(rules: &AccessRules) -> Self {
+        let parse = |list: &[String]| list.iter().filter_map(|c| parse_cidr(c).ok()).collect();
+        Self {
+            allow: parse(&rules.allow),
+            deny: parse(&rules.deny),
+        }
+    }
+
+    fn allows(&self, ip: IpAddr) -> bool {
+        if self.deny.iter().any(|net| net.contains(&ip)) {
+            return false;
+        }
+        self.allow.is_empty() || self.allow.iter().any(|net| net.contains(&ip))
+    }
+}
+
+/// Effective rules for one service: the global rules plus the service's own
+#[derive(Debug, Clone)]
+pub struct AccessPolicy {
+    global: CompiledRules,
+    service: CompiledRules,
+}
+
+impl AccessPolicy {
+    pub fn new(config: &AccessConfig, service: AccessService) -> Self {
+        Self {
+            global: CompiledRules::new(&config.global),
+            service: CompiledRules::new(service.rules(config)),
+        }
+    }
+
+    pub fn allows(&self, ip: IpAddr) -> bool {
+        let ip = canonical_ip(ip);
+        self.global.allows(ip) && self.service.allows(ip)
+    }
+}
+
+/// Shared access check for all listeners. Reads the current `access` config
+/// on every call, so rule changes apply to new connections immediately.
+pub struct AccessControl {
+    config: ConfigStore,
+    events: Arc<EventBus>,
+}
+
+impl AccessControl {
+    pub fn new(config: ConfigStore, events: Arc<EventBus>) -> Self {
+        Self { config, events }
+    }
+
+    pub fn policy(&self, service: AccessService) -> AccessPolicy {
+        AccessPolicy::new(&self.config.get().access, service)
+    }
+
+    /// Whether `peer` may connect to `service`. Rejections are logged and
+    /// published as `access.denied` events.
+    pub fn admit(&self, service: AccessService, peer: IpAddr) -> bool {
+        if self.policy(service).allows(peer) {
+            return true;
+        }
+
+        let ip = canonical_ip(peer).to_string();
+        tracing::warn!(
+            "Rejected {} connection from {} by access policy",
+            service.as_str(),
+            ip
+        );
+        self.events.publish(SystemEvent::AccessDenied {
+            service: service.as_str().to_string(),
+            ip,
+        });
+        false
+    }
+}
+
+/// Outermost web layer: rejects peers not admitted by the web access policy,
+/// before authentication or static file serving.
+pub async fn access_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+
+    if let Some(peer) = peer {
+        if !state.access.admit(AccessService::Web, peer) {
+            let body = ErrorResponse {
+                success: false,
+                message: "Access denied".to_string(),
+            };
+            return (StatusCode::FORBIDDEN, Json(body)).into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(allow: &[&str], deny: &[&str]) -> AccessRules {
+        AccessRules {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_global_and_service_rules() {
+        let config = AccessConfig {
+            global: rules(&["192.168.0.0/16", "10.0.0.0/8"], &["192.168.1.66"]),
+            rtsp: rules(&["10.1.0.0/16"], &[]),
+            rustdesk: rules(&[], &["10.9.0.0/16"]),
+            ..Default::default()
+        };
+
+        let web = AccessPolicy::new(&config, AccessService::Web);
+        assert!(web.allows(ip("192.168.1.10")));
+        assert!(web.allows(ip("::ffff:10.2.3.4")));
+        assert!(!web.allows(ip("192.168.1.66")));
+        assert!(!web.allows(ip("172.16.0.1")));
+
+        let rtsp = AccessPolicy::new(&config, AccessService::Rtsp);
+        assert!(rtsp.allows(ip("10.1.2.3")));
+        assert!(!rtsp.allows(ip("192.168.1.10")));
+
+        let rustdesk = AccessPolicy::new(&config, AccessService::RustDesk);
+        assert!(rustdesk.allows(ip("10.2.0.1")));
+        assert!(!rustdesk.allows(ip("10.9.0.1")));
+    }
+
+    #[test]
+    fn test_empty_config_allows_everything() {
+        let policy = AccessPolicy::new(&AccessConfig::default(), AccessService::Web);
+        assert!(policy.allows(ip("203.0.113.1")));
+        assert!(policy.allows(ip("::1")));
+    }
+
+    #[test]
+    fn test_parse_cidr() {
+        assert!(parse_cidr("10.0.0.0/33").is_err());
+        assert!(parse_cidr("proxy.local").is_err());
+        assert_eq!(
+            parse_cidr(" 192.168.1.1 ").unwrap(),
+            "192.168.1.1/32".parse::<IpNet>().unwrap()
+        );
+        assert!(validate_rules(&rules(&["fd00::/8"], &["bad"])).is_err());
+    }
+}
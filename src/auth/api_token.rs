@@ -0,0 +1,367 @@
+use axum::http::Method;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::password::hash_token;
+use super::user::UserRole;
+use crate::error::{AppError, Result};
+
+/// Prefix that distinguishes API tokens from session IDs in `Authorization: Bearer`
+pub const API_TOKEN_PREFIX: &str = "okvm_";
+
+/// Scopes a token may be granted. `<resource>:*` and `*` are accepted as wildcards.
+pub const API_SCOPES: &[&str] = &[
+    "system:read",
+    "stream:read",
+    "stream:write",
+    "hid:read",
+    "hid:write",
+    "atx:read",
+    "atx:power",
+    "msd:read",
+    "msd:write",
+    "audio:read",
+    "audio:write",
+    "config:read",
+    "config:write",
+];
+
+type ApiTokenRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    String,
+    Option<String>,
+);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    pub created_at: String,
+    pub last_used: Option<String>,
+}
+
+impl ApiToken {
+    fn from_row(row: ApiTokenRow) -> Result<Self> {
+        let (id, name, permissions, expires_at, created_at, last_used) = row;
+        let scopes = serde_json::from_str(&permissions)
+            .map_err(|e| AppError::Internal(format!("Invalid token permissions: {}", e)))?;
+        let expires_at = expires_at
+            .map(|s| OffsetDateTime::parse(&s, &Rfc3339))
+            .transpose()
+            .map_err(|e| AppError::Internal(format!("Invalid token expiry: {}", e)))?;
+
+        Ok(Self {
+            id,
+            name,
+            scopes,
+            expires_at,
+            created_at,
+            last_used,
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| OffsetDateTime::now_utc() > expires_at)
+    }
+
+    /// Whether any granted scope covers `required`.
+    pub fn grants(&self, required: &str) -> bool {
+        let resource = required.split(':').next().unwrap_or(required);
+        self.scopes.iter().any(|scope| {
+            scope == "*"
+                || scope == required
+                || scope
+                    .strip_suffix(":*")
+                    .is_some_and(|prefix| prefix == resource)
+        })
+    }
+
+    /// Role used for route role guards and the token's synthetic session, so
+    /// features such as WebRTC HID input see the same privilege level as the scopes.
+    pub fn role(&self) -> UserRole {
+        if self.grants("config:write") {
+            UserRole::Admin
+        } else if [
+            "stream:write",
+            "hid:write",
+            "atx:power",
+            "msd:write",
+            "audio:write",
+        ]
+        .iter()
+        .any(|scope| self.grants(scope))
+        {
+            UserRole::Operator
+        } else {
+            UserRole::Viewer
+        }
+    }
+}
+
+/// Validate a requested scope against [`API_SCOPES`].
+pub fn validate_scope(scope: &str) -> Result<()> {
+    let valid = scope == "*"
+        || API_SCOPES.contains(&scope)
+        || scope.strip_suffix(":*").is_some_and(|resource| {
+            API_SCOPES
+                .iter()
+                .any(|known| known.split(':').next() == Some(resource))
+        });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!("Unknown scope: {}", scope)))
+    }
+}
+
+/// Scope a bearer token needs for an API request, or `None` if the endpoint is
+/// session-only (user management, tokens, updates, extensions, terminal, ...).
+pub fn required_scope(method: &Method, path: &str) -> Option<&'static str> {
+    let read = matches!(*method, Method::GET | Method::HEAD);
+    let path = path.strip_prefix("/api").unwrap_or(path);
+
+    // WebSocket upgrades are GETs; classify by what the socket does.
+    match path {
+        "/ws" => return Some("system:read"),
+        "/ws/hid" => return Some("hid:write"),
+        "/ws/audio" => return Some("audio:read"),
+        _ => {}
+    }
+
+    let resource = path.trim_start_matches('/').split('/').next()?;
+    let scope = match resource {
+        // WebRTC offers/ICE are POSTs but only consume the stream
+        "webrtc" | "snapshot" => "stream:read",
+        "stream" if read => "stream:read",
+        "stream" => "stream:write",
+        "hid" if read => "hid:read",
+        "hid" => "hid:write",
+        "atx" if read => "atx:read",
+        "atx" => "atx:power",
+        "msd" if read => "msd:read",
+        "msd" => "msd:write",
+        "audio" if read => "audio:read",
+        "audio" => "audio:write",
+        "config" if read => "config:read",
+        "config" => "config:write",
+        "info" | "devices" if read => "system:read",
+        _ => return None,
+    };
+    Some(scope)
+}
+
+fn generate_token() -> String {
+    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    let mut rng = rand::rng();
+    let secret: String = (0..40)
+        .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
+        .collect();
+    format!("{}{}", API_TOKEN_PREFIX, secret)
+}
+
+#[derive(Clone)]
+pub struct ApiTokenStore {
+    pool: Pool<Sqlite>,
+}
+
+impl ApiTokenStore {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<ApiToken>> {
+        let rows: Vec<ApiTokenRow> = sqlx::query_as(
+            "SELECT id, name, permissions, expires_at, created_at, last_used FROM api_tokens ORDER BY created_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ApiToken::from_row).collect()
+    }
+
+    /// Create a token. The plain-text secret is returned once and never stored.
+    pub async fn create(
+        &self,
+        name: &str,
+        scopes: Vec<String>,
+        expires_at: Option<OffsetDateTime>,
+    ) -> Result<(ApiToken, String)> {
+        for scope in &scopes {
+            validate_scope(scope)?;
+        }
+
+        let secret = generate_token();
+        let token = ApiToken {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            scopes,
+            expires_at,
+            created_at: OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .expect("RFC3339 format"),
+            last_used: None,
+        };
+
+        let permissions = serde_json::to_string(&token.scopes)
+            .map_err(|e| AppError::Internal(format!("Failed to encode scopes: {}", e)))?;
+        let expires_at = token
+            .expires_at
+            .map(|t| t.format(&Rfc3339).expect("RFC3339 format"));
+
+        sqlx::query(
+            r#"
+            INSERT INTO api_tokens (id, name, token_hash, permissions, expires_at, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(&token.id)
+        .bind(&token.name)
+        .bind(hash_token(&secret))
+        .bind(permissions)
+        .bind(expires_at)
+        .bind(&token.created_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok((token, secret))
+    }
+
+    /// Resolve a presented secret to a live token and record its use.
+    pub async fn authenticate(&self, secret: &str) -> Result<Option<ApiToken>> {
+        let row: Option<ApiTokenRow> = sqlx::query_as(
+            "SELECT id, name, permissions, expires_at, created_at, last_used FROM api_tokens WHERE token_hash = ?1",
+        )
+        .bind(hash_token(secret))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(token) = row.map(ApiToken::from_row).transpose()? else {
+            return Ok(None);
+        };
+        if token.is_expired() {
+            return Ok(None);
+        }
+
+        let now = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .expect("RFC3339 format");
+        sqlx::query("UPDATE api_tokens SET last_used = ?1 WHERE id = ?2")
+            .bind(&now)
+            .bind(&token.id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(Some(ApiToken {
+            last_used: Some(now),
+            ..token
+        }))
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM api_tokens WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("API token not found".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_token_lifecycle() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let store = ApiTokenStore::new(db.clone_pool());
+
+        assert!(store
+            .create("bad", vec!["root:*".to_string()], None)
+            .await
+            .is_err());
+
+        let (token, secret) = store
+            .create(
+                "ci",
+                vec!["hid:write".to_string(), "msd:*".to_string()],
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(secret.starts_with(API_TOKEN_PREFIX));
+
+        let authed = store.authenticate(&secret).await.unwrap().unwrap();
+        assert_eq!(authed.id, token.id);
+        assert!(authed.last_used.is_some());
+        assert!(store.authenticate("okvm_wrong").await.unwrap().is_none());
+
+        let (_, expired) = store
+            .create(
+                "old",
+                vec!["*".to_string()],
+                Some(OffsetDateTime::now_utc() - time::Duration::hours(1)),
+            )
+            .await
+            .unwrap();
+        assert!(store.authenticate(&expired).await.unwrap().is_none());
+
+        store.delete(&token.id).await.unwrap();
+        assert!(store.authenticate(&secret).await.unwrap().is_none());
+        assert_eq!(store.list().await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_scope_matching() {
+        let token = ApiToken {
+            id: String::new(),
+            name: String::new(),
+            scopes: vec!["hid:write".to_string(), "msd:*".to_string()],
+            expires_at: None,
+            created_at: String::new(),
+            last_used: None,
+        };
+        assert!(token.grants("hid:write"));
+        assert!(!token.grants("hid:read"));
+        assert!(token.grants("msd:read"));
+        assert!(token.grants("msd:write"));
+        assert!(!token.grants("atx:power"));
+        assert_eq!(token.role(), UserRole::Operator);
+
+        assert_eq!(
+            required_scope(&Method::POST, "/api/atx/power"),
+            Some("atx:power")
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/msd/status"),
+            Some("msd:read")
+        );
+        assert_eq!(required_scope(&Method::GET, "/ws/hid"), Some("hid:write"));
+        assert_eq!(
+            required_scope(&Method::POST, "/webrtc/offer"),
+            Some("stream:read")
+        );
+        assert_eq!(required_scope(&Method::GET, "/users"), None);
+    }
+}
@@ -0,0 +1,181 @@
+use axum_server::accept::Accept;
+use axum_server::tls_rustls::RustlsAcceptor;
+use futures::future::BoxFuture;
+use rustls::pki_types::{pem::PemObject, CertificateDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tower_http::add_extension::AddExtension;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::config::{ClientCertMode, WebConfig};
+use crate::error::{AppError, Result};
+
+/// Identity of a verified TLS client certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// Full subject distinguished name
+    pub subject: String,
+    /// Subject common name, matched against local usernames
+    pub common_name: Option<String>,
+    /// Lowercase hex SHA-256 of the DER certificate
+    pub fingerprint: String,
+}
+
+impl ClientCertificate {
+    pub fn from_der(der: &[u8]) -> Option<Self> {
+        let (_, cert) = X509Certificate::from_der(der).ok()?;
+        let common_name = cert
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(|cn| cn.trim().to_string())
+            .filter(|cn| !cn.is_empty());
+
+        Some(Self {
+            subject: cert.subject().to_string(),
+            common_name,
+            fingerprint: Sha256::digest(der)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+        })
+    }
+}
+
+/// Parse a PEM bundle of client CA certificates.
+pub fn parse_client_ca(pem: &[u8]) -> Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_slice_iter(pem) {
+        let cert =
+            cert.map_err(|e| AppError::BadRequest(format!("Invalid client CA PEM: {}", e)))?;
+        roots
+            .add(cert)
+            .map_err(|e| AppError::BadRequest(format!("Invalid client CA certificate: {}", e)))?;
+    }
+    if roots.is_empty() {
+        return Err(AppError::BadRequest(
+            "Client CA PEM contains no certificates".to_string(),
+        ));
+    }
+    Ok(roots)
+}
+
+/// Rebuild `server` with client certificate verification according to
+/// `web.client_cert_mode`. Returned unchanged when the mode is off.
+pub fn with_client_auth(server: Arc<ServerConfig>, web: &WebConfig) -> Result<Arc<ServerConfig>> {
+    if web.client_cert_mode == ClientCertMode::Off {
+        return Ok(server);
+    }
+    let ca_path = web
+        .client_ca_path
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| AppError::Config("Client certificate mode requires a client CA".into()))?;
+    let pem = std::fs::read(ca_path)?;
+    let roots = parse_client_ca(&pem).map_err(|e| AppError::Config(e.to_string()))?;
+
+    let mut verifier = WebPkiClientVerifier::builder(Arc::new(roots));
+    if web.client_cert_mode == ClientCertMode::Optional {
+        verifier = verifier.allow_unauthenticated();
+    }
+    let verifier = verifier
+        .build()
+        .map_err(|e| AppError::Config(format!("Invalid client CA: {}", e)))?;
+
+    let mut config = ServerConfig::builder()
+        .with_client_cert_verifier(verifier)
+        .with_cert_resolver(server.cert_resolver.clone());
+    config.alpn_protocols = server.alpn_protocols.clone();
+    Ok(Arc::new(config))
+}
+
+/// TLS acceptor that exposes the verified client certificate to handlers as
+/// an `Option<ClientCertificate>` request extension.
+#[derive(Clone)]
+pub struct ClientCertAcceptor {
+    inner: RustlsAcceptor,
+}
+
+impl ClientCertAcceptor {
+    pub fn new(inner: RustlsAcceptor) -> Self {
+        Self { inner }
+    }
+}
+
+impl<I, S> Accept<I, S> for ClientCertAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = <RustlsAcceptor as Accept<I, S>>::Stream;
+    type Service = AddExtension<S, Option<ClientCertificate>>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let handshake = self.inner.accept(stream, service);
+        Box::pin(async move {
+            let (stream, service) = handshake.await?;
+            let cert = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|chain| chain.first())
+                .and_then(|der| ClientCertificate::from_der(der));
+            Ok((stream, AddExtension::new(service, cert)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_cert(cn: &str) -> rcgen::CertifiedKey<rcgen::KeyPair> {
+        let mut params = rcgen::CertificateParams::new(vec![]).unwrap();
+        params
+            .distinguished_name
+            .push(rcgen::DnType::CommonName, cn);
+        params
+            .distinguished_name
+            .push(rcgen::DnType::OrganizationName, "Rack 4");
+        let key = rcgen::KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+        rcgen::CertifiedKey {
+            cert,
+            signing_key: key,
+        }
+    }
+
+    #[test]
+    fn test_identity_from_certificate() {
+        let certified = client_cert("alice");
+        let der = certified.cert.der();
+        let identity = ClientCertificate::from_der(der).unwrap();
+
+        assert_eq!(identity.common_name.as_deref(), Some("alice"));
+        assert!(identity.subject.contains("CN=alice"));
+        assert!(identity.subject.contains("O=Rack 4"));
+        assert_eq!(identity.fingerprint.len(), 64);
+        assert_eq!(
+            identity.fingerprint,
+            ClientCertificate::from_der(der).unwrap().fingerprint
+        );
+
+        assert!(ClientCertificate::from_der(b"not a certificate").is_none());
+    }
+
+    #[test]
+    fn test_parse_client_ca() {
+        let ca = client_cert("One-KVM client CA");
+        let bundle = format!("{}{}", ca.cert.pem(), client_cert("second").cert.pem());
+        assert_eq!(parse_client_ca(bundle.as_bytes()).unwrap().len(), 2);
+
+        assert!(parse_client_ca(b"").is_err());
+        assert!(parse_client_ca(ca.signing_key.serialize_pem().as_bytes()).is_err());
+    }
+}
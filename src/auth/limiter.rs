@@ -0,0 +1,182 @@
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use crate::config::AuthConfig;
+
+/// Thresholds taken from [`AuthConfig`]
+#[derive(Debug, Clone, Copy)]
+pub struct LoginLimits {
+    pub max_failures: u32,
+    pub lockout: Duration,
+    pub backoff_base: Duration,
+}
+
+impl From<&AuthConfig> for LoginLimits {
+    fn from(config: &AuthConfig) -> Self {
+        Self {
+            max_failures: config.login_max_failures,
+            lockout: Duration::from_secs(config.login_lockout_secs as u64),
+            backoff_base: Duration::from_secs(config.login_backoff_secs as u64),
+        }
+    }
+}
+
+struct FailureRecord {
+    failures: u32,
+    last_failure: Instant,
+    blocked_until: Instant,
+}
+
+/// Failed-login counters keyed by client IP and by username.
+///
+/// Each failure blocks the key for an exponentially growing delay; reaching
+/// `max_failures` blocks it for the full lockout. Counters are forgotten once
+/// no failure has been seen for a lockout period.
+#[derive(Default)]
+pub struct LoginLimiter {
+    records: Mutex<HashMap<String, FailureRecord>>,
+}
+
+fn keys(ip: Option<IpAddr>, username: &str) -> Vec<String> {
+    let mut keys = vec![format!("user:{}", username)];
+    if let Some(ip) = ip {
+        keys.push(format!("ip:{}", ip));
+    }
+    keys
+}
+
+impl LoginLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time left before another attempt is allowed, if the IP or username is blocked.
+    pub fn check(&self, ip: Option<IpAddr>, username: &str) -> Option<Duration> {
+        self.check_at(Instant::now(), ip, username)
+    }
+
+    /// Record a failed attempt. Returns the lockout duration if this failure triggered one.
+    pub fn record_failure(
+        &self,
+        limits: LoginLimits,
+        ip: Option<IpAddr>,
+        username: &str,
+    ) -> Option<Duration> {
+        self.record_failure_at(Instant::now(), limits, ip, username)
+    }
+
+    pub fn record_success(&self, ip: Option<IpAddr>, username: &str) {
+        let mut records = self.records.lock();
+        for key in keys(ip, username) {
+            records.remove(&key);
+        }
+    }
+
+    fn check_at(&self, now: Instant, ip: Option<IpAddr>, username: &str) -> Option<Duration> {
+        let records = self.records.lock();
+        keys(ip, username)
+            .iter()
+            .filter_map(|key| records.get(key))
+            .filter(|record| record.blocked_until > now)
+            .map(|record| record.blocked_until - now)
+            .max()
+    }
+
+    fn record_failure_at(
+        &self,
+        now: Instant,
+        limits: LoginLimits,
+        ip: Option<IpAddr>,
+        username: &str,
+    ) -> Option<Duration> {
+        let mut records = self.records.lock();
+        records.retain(|_, r| r.blocked_until > now || now - r.last_failure < limits.lockout);
+
+        let mut locked = None;
+        for key in keys(ip, username) {
+            let record = records.entry(key).or_insert(FailureRecord {
+                failures: 0,
+                last_failure: now,
+                blocked_until: now,
+            });
+            record.failures += 1;
+            record.last_failure = now;
+
+            if limits.max_failures > 0 && record.failures >= limits.max_failures {
+                record.blocked_until = now + limits.lockout;
+                locked = Some(limits.lockout);
+            } else if !limits.backoff_base.is_zero() {
+                let factor = 1u32 << (record.failures - 1).min(16);
+                record.blocked_until = now + (limits.backoff_base * factor).min(limits.lockout);
+            }
+        }
+        locked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: LoginLimits = LoginLimits {
+        max_failures: 3,
+        lockout: Duration::from_secs(60),
+        backoff_base: Duration::from_secs(1),
+    };
+
+    #[test]
+    fn test_backoff_then_lockout() {
+        let limiter = LoginLimiter::new();
+        let ip: IpAddr = "192.168.1.10".parse().unwrap();
+        let t0 = Instant::now();
+
+        assert!(limiter.check_at(t0, Some(ip), "admin").is_none());
+
+        assert!(limiter
+            .record_failure_at(t0, LIMITS, Some(ip), "admin")
+            .is_none());
+        assert_eq!(
+            limiter.check_at(t0, Some(ip), "admin"),
+            Some(Duration::from_secs(1))
+        );
+
+        let t1 = t0 + Duration::from_secs(1);
+        assert!(limiter.check_at(t1, Some(ip), "admin").is_none());
+        assert!(limiter
+            .record_failure_at(t1, LIMITS, Some(ip), "admin")
+            .is_none());
+        assert_eq!(
+            limiter.check_at(t1, Some(ip), "admin"),
+            Some(Duration::from_secs(2))
+        );
+
+        let t2 = t1 + Duration::from_secs(2);
+        assert_eq!(
+            limiter.record_failure_at(t2, LIMITS, Some(ip), "admin"),
+            Some(LIMITS.lockout)
+        );
+        // Same IP with another username, and the username from another IP, are both blocked
+        assert!(limiter.check_at(t2, Some(ip), "other").is_some());
+        assert!(limiter.check_at(t2, None, "admin").is_some());
+
+        let after = t2 + LIMITS.lockout;
+        assert!(limiter.check_at(after, Some(ip), "admin").is_none());
+    }
+
+    #[test]
+    fn test_success_resets_counters() {
+        let limiter = LoginLimiter::new();
+        let t0 = Instant::now();
+
+        limiter.record_failure_at(t0, LIMITS, None, "admin");
+        limiter.record_failure_at(t0, LIMITS, None, "admin");
+        limiter.record_success(None, "admin");
+
+        assert!(limiter.check_at(t0, None, "admin").is_none());
+        assert!(limiter
+            .record_failure_at(t0, LIMITS, None, "admin")
+            .is_none());
+    }
+}
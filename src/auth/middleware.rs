@@ -1,18 +1,33 @@
 use axum::{
-    extract::{Request, State},
+    extract::{ConnectInfo, Request, State},
     http::StatusCode,
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
+use axum_extra::extract::cookie::{Cookie, SameSite};
 use axum_extra::extract::CookieJar;
+use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
 
+use super::api_token::{required_scope, ApiToken, API_TOKEN_PREFIX};
+use super::client_cert::ClientCertificate;
+use super::proxy::ProxyAuth;
+use super::share::{is_stream_path, share_link_id, ShareLink, SHARE_TOKEN_PREFIX};
+use super::{session_key, LoginLimits, Session, SessionStore, User, UserRole};
+use crate::audit::{AuditOutcome, AuditRecord};
+use crate::config::ClientCertMode;
+use crate::error::AppError;
 use crate::state::AppState;
 use crate::web::ErrorResponse;
 
 pub const SESSION_COOKIE: &str = "one_kvm_session";
 
+/// Session ID from the cookie, or the `Authorization: Bearer` credential
+/// (a session ID or an API token starting with [`API_TOKEN_PREFIX`]).
 pub fn extract_session_id(cookies: &CookieJar, headers: &axum::http::HeaderMap) -> Option<String> {
     if let Some(cookie) = cookies.get(SESSION_COOKIE) {
         return Some(cookie.value().to_string());
@@ -51,8 +66,68 @@ pub async fn auth_middleware(
 
     let session_id = extract_session_id(&cookies, request.headers());
 
+    if let Some(ref session_id) = session_id {
+        if session_id.starts_with(API_TOKEN_PREFIX) {
+            return Ok(authenticate_api_token(&state, session_id, request, next).await);
+        }
+    }
+
+    if let Some(secret) = query_param(request.uri().query(), "share") {
+        if secret.starts_with(SHARE_TOKEN_PREFIX) {
+            return Ok(authenticate_share_link(&state, cookies, &secret, request, next).await);
+        }
+    }
+
+    if let Some(proxy) = ProxyAuth::from_config(&state.config.get().auth) {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0.ip());
+        if let Some(username) = proxy.username(peer, request.headers()) {
+            return Ok(
+                authenticate_proxy_user(&state, &proxy, cookies, &username, request, next).await,
+            );
+        }
+    }
+
+    if let Some(cert) = client_certificate(&state, &request) {
+        match resolve_client_cert_user(&state, &cert).await {
+            Ok(Some(user)) => {
+                let client_ip = request
+                    .extensions()
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|info| info.0.ip().to_string());
+                return Ok(login_external_user(
+                    &state,
+                    cookies,
+                    user,
+                    "client_cert",
+                    client_ip,
+                    request,
+                    next,
+                )
+                .await);
+            }
+            // Unmapped certificates only gate the connection; log in normally
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "Client certificate lookup failed for '{}': {}",
+                    cert.subject,
+                    e
+                );
+                return Ok(unauthorized_response(
+                    "Client certificate authentication failed",
+                ));
+            }
+        }
+    }
+
     if let Some(session_id) = session_id {
         if let Ok(Some(session)) = state.sessions.get(&session_id).await {
+            if share_link_id(&session).is_some() {
+                return Ok(run_share_session(&state, session, request, next).await);
+            }
             request.extensions_mut().insert(session);
             return Ok(next.run(request).await);
         }
@@ -68,12 +143,451 @@ pub async fn auth_middleware(
     Ok(unauthorized_response("Not authenticated"))
 }
 
+/// Bearer API tokens: check the endpoint's scope, then run the request under a
+/// synthetic session whose role mirrors the token's scopes.
+async fn authenticate_api_token(
+    state: &AppState,
+    secret: &str,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let token = match state.api_tokens.authenticate(secret).await {
+        Ok(Some(token)) => token,
+        Ok(None) => return unauthorized_response("Invalid or expired API token"),
+        Err(e) => {
+            tracing::warn!("API token lookup failed: {}", e);
+            return unauthorized_response("Invalid or expired API token");
+        }
+    };
+
+    match required_scope(request.method(), request.uri().path()) {
+        Some(scope) if token.grants(scope) => {}
+        Some(scope) => {
+            return error_response(
+                StatusCode::FORBIDDEN,
+                &format!("Permission denied: {} scope required", scope),
+            )
+        }
+        None => {
+            return error_response(
+                StatusCode::FORBIDDEN,
+                "Endpoint is not available to API tokens",
+            )
+        }
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let session = Session {
+        id: format!("token:{}", token.id),
+        user_id: format!("token:{}", token.id),
+        role: token.role(),
+        created_at: now,
+        expires_at: token.expires_at.unwrap_or(now + time::Duration::days(1)),
+        ip: None,
+        user_agent: None,
+        data: None,
+    };
+    request.extensions_mut().insert(session);
+    request.extensions_mut().insert(token);
+    next.run(request).await
+}
+
+/// Local account for an externally authenticated user, created with
+/// `default_role` if it does not exist yet.
+async fn resolve_external_user(
+    state: &AppState,
+    username: &str,
+    default_role: Option<UserRole>,
+    source: &str,
+) -> crate::error::Result<Option<User>> {
+    if let Some(user) = state.users.get_by_username(username).await? {
+        return Ok(Some(user));
+    }
+    let Some(role) = default_role else {
+        return Ok(None);
+    };
+
+    // The password is never revealed; these accounts log in through `source` only.
+    let password = Uuid::new_v4().simple().to_string();
+    let user = state.users.create(username, &password, role).await?;
+    tracing::info!(
+        "Created {} account '{}' for {} user",
+        role,
+        username,
+        source
+    );
+    Ok(Some(user))
+}
+
+/// Requests carrying a username header from a trusted proxy.
+async fn authenticate_proxy_user(
+    state: &AppState,
+    proxy: &ProxyAuth,
+    cookies: CookieJar,
+    username: &str,
+    request: Request,
+    next: Next,
+) -> Response {
+    let user =
+        match resolve_external_user(state, username, proxy.default_role, "reverse-proxy").await {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                return error_response(
+                    StatusCode::FORBIDDEN,
+                    &format!("No local account for proxy user '{}'", username),
+                )
+            }
+            Err(e) => {
+                tracing::warn!("Proxy user lookup failed for '{}': {}", username, e);
+                return unauthorized_response("Proxy authentication failed");
+            }
+        };
+
+    let client_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| proxy.client_ip(info.0.ip(), request.headers()).to_string());
+    login_external_user(state, cookies, user, "proxy", client_ip, request, next).await
+}
+
+/// Verified client certificate of the TLS connection, if client certificate
+/// authentication is enabled.
+fn client_certificate(state: &AppState, request: &Request) -> Option<ClientCertificate> {
+    if state.config.get().web.client_cert_mode == ClientCertMode::Off {
+        return None;
+    }
+    request
+        .extensions()
+        .get::<Option<ClientCertificate>>()
+        .cloned()
+        .flatten()
+}
+
+/// Local account named by the certificate's common name.
+async fn resolve_client_cert_user(
+    state: &AppState,
+    cert: &ClientCertificate,
+) -> crate::error::Result<Option<User>> {
+    let Some(username) = cert.common_name.as_deref() else {
+        return Ok(None);
+    };
+    let default_role = state
+        .config
+        .get()
+        .web
+        .client_cert_default_role
+        .as_deref()
+        .and_then(|role| role.parse().ok());
+    resolve_external_user(state, username, default_role, "client certificate").await
+}
+
+/// Reuse the session cookie if it belongs to `user`. Clients that don't keep
+/// cookies run under the live session opened earlier from the same address and
+/// user agent; otherwise create a session, audit the login and set its cookie.
+async fn login_external_user(
+    state: &AppState,
+    cookies: CookieJar,
+    user: User,
+    method: &str,
+    client_ip: Option<String>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(cookie) = cookies.get(SESSION_COOKIE) {
+        if let Ok(Some(session)) = state.sessions.get(cookie.value()).await {
+            if session.user_id == user.id {
+                request.extensions_mut().insert(session);
+                return next.run(request).await;
+            }
+        }
+    }
+
+    let user_agent: Option<String> = request
+        .headers()
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(256).collect());
+
+    let client_key =
+        SessionStore::client_key(&user.id, client_ip.as_deref(), user_agent.as_deref());
+    match state.sessions.get_for_client(&client_key).await {
+        Ok(Some(mut session)) if session.user_id == user.id => {
+            session.role = user.role;
+            request.extensions_mut().insert(session);
+            return next.run(request).await;
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to look up {} session: {}", method, e),
+    }
+
+    let session = match state
+        .sessions
+        .create(&user.id, user.role, client_ip.clone(), user_agent)
+        .await
+    {
+        Ok(session) => session,
+        Err(e) => {
+            tracing::warn!("Failed to create {} session: {}", method, e);
+            return unauthorized_response("Authentication failed");
+        }
+    };
+    state.sessions.set_for_client(client_key, &session.id);
+
+    let record = AuditRecord {
+        actor: Some(user.username.clone()),
+        ip: client_ip,
+        action: "auth.login".to_string(),
+        details: Some(serde_json::json!({ "method": method })),
+        outcome: AuditOutcome::Success,
+        message: None,
+    };
+    if let Err(e) = state.audit.record(record).await {
+        tracing::warn!("Failed to write audit entry for {} login: {}", method, e);
+    }
+
+    let cookie = session_cookie(
+        &session.id,
+        time::Duration::seconds(state.config.get().auth.session_timeout_secs as i64),
+    );
+
+    request.extensions_mut().insert(session);
+    let response = next.run(request).await;
+    (cookies.add(cookie), response).into_response()
+}
+
+/// Decoded value of a query string parameter
+fn query_param(query: Option<&str>, name: &str) -> Option<String> {
+    query?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        if key != name {
+            return None;
+        }
+        urlencoding::decode(value).ok().map(|v| v.into_owned())
+    })
+}
+
+fn session_cookie(session_id: &str, max_age: time::Duration) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE, session_id.to_string()))
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .max_age(max_age)
+        .build()
+}
+
+/// Requests through a share session: the link must still exist and only
+/// video (and optionally audio) endpoints are reachable.
+async fn run_share_session(
+    state: &AppState,
+    session: Session,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let link_id = share_link_id(&session).unwrap_or_default();
+    let link = match state.share_links.get(link_id).await {
+        Ok(Some(link)) => link,
+        Ok(None) => return unauthorized_response("Share link expired or revoked"),
+        Err(e) => {
+            tracing::warn!("Share link lookup failed: {}", e);
+            return unauthorized_response("Share link expired or revoked");
+        }
+    };
+
+    if !link.allows_path(request.uri().path()) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "Endpoint is not available to share links",
+        );
+    }
+
+    // Viewers who come back after others took their place wait for a free slot
+    if is_stream_path(request.uri().path()) {
+        match live_share_viewers(state, &link).await {
+            Ok(live) => {
+                if !live.contains(&session_key(&session.id))
+                    && live.len() >= link.max_viewers as usize
+                {
+                    return error_response(StatusCode::FORBIDDEN, "Viewer limit reached");
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to count share viewers: {}", e);
+                return unauthorized_response("Share link expired or revoked");
+            }
+        }
+    }
+
+    request.extensions_mut().insert(session);
+    request.extensions_mut().insert(link);
+    next.run(request).await
+}
+
+/// Keys of the share sessions of `link` that are streaming right now. Sessions
+/// live until the link expires, so viewers who left are not counted.
+async fn live_share_viewers(
+    state: &AppState,
+    link: &ShareLink,
+) -> crate::error::Result<HashSet<String>> {
+    let viewer_id = link.session_user_id();
+    let mut streaming = state.webrtc.active_owners().await;
+    streaming.extend(state.stream_manager.mjpeg_handler().client_owners());
+    Ok(state
+        .sessions
+        .list()
+        .await?
+        .into_iter()
+        .filter(|s| s.user_id == viewer_id && streaming.contains(&s.key))
+        .map(|s| s.key)
+        .collect())
+}
+
+/// First request with `?share=<secret>`: check the PIN and viewer limit, then
+/// open a view-only session lasting until the link expires.
+async fn authenticate_share_link(
+    state: &AppState,
+    cookies: CookieJar,
+    secret: &str,
+    request: Request,
+    next: Next,
+) -> Response {
+    let link: ShareLink = match state.share_links.authenticate(secret).await {
+        Ok(Some(link)) => link,
+        Ok(None) => return unauthorized_response("Invalid or expired share link"),
+        Err(e) => {
+            tracing::warn!("Share link lookup failed: {}", e);
+            return unauthorized_response("Invalid or expired share link");
+        }
+    };
+    let viewer_id = link.session_user_id();
+
+    // A viewer reopening the link keeps their session
+    if let Some(cookie) = cookies.get(SESSION_COOKIE) {
+        if let Ok(Some(session)) = state.sessions.get(cookie.value()).await {
+            if session.user_id == viewer_id {
+                return run_share_session(state, session, request, next).await;
+            }
+        }
+    }
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip());
+
+    if link.has_pin {
+        if let Some(wait) = state.login_limiter.check(peer, &viewer_id) {
+            return AppError::RateLimited {
+                retry_after_secs: wait.as_secs() + u64::from(wait.subsec_nanos() > 0),
+            }
+            .into_response();
+        }
+        let pin = query_param(request.uri().query(), "pin").or_else(|| {
+            request
+                .headers()
+                .get("x-share-pin")
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        });
+        if !link.verify_pin(pin.as_deref()) {
+            let limits = LoginLimits::from(&state.config.get().auth);
+            state.login_limiter.record_failure(limits, peer, &viewer_id);
+            let message = if pin.is_some() {
+                "Invalid PIN"
+            } else {
+                "PIN required"
+            };
+            return unauthorized_response(message);
+        }
+        state.login_limiter.record_success(peer, &viewer_id);
+    }
+
+    let viewers = match live_share_viewers(state, &link).await {
+        Ok(viewers) => viewers.len(),
+        Err(e) => {
+            tracing::warn!("Failed to count share viewers: {}", e);
+            return unauthorized_response("Invalid or expired share link");
+        }
+    };
+    if viewers >= link.max_viewers as usize {
+        return error_response(StatusCode::FORBIDDEN, "Viewer limit reached");
+    }
+
+    let ip = peer.map(|ip| ip.to_string());
+    let user_agent = request
+        .headers()
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(256).collect());
+    let session = match state
+        .sessions
+        .create_until(
+            &viewer_id,
+            UserRole::Viewer,
+            ip.clone(),
+            user_agent,
+            link.expires_at,
+        )
+        .await
+    {
+        Ok(session) => session,
+        Err(e) => {
+            tracing::warn!("Failed to create share session: {}", e);
+            return unauthorized_response("Invalid or expired share link");
+        }
+    };
+
+    let record = AuditRecord {
+        actor: Some(viewer_id),
+        ip,
+        action: "share.open".to_string(),
+        details: Some(serde_json::json!({ "id": link.id, "name": link.name })),
+        outcome: AuditOutcome::Success,
+        message: None,
+    };
+    if let Err(e) = state.audit.record(record).await {
+        tracing::warn!("Failed to write audit entry for share link: {}", e);
+    }
+
+    let cookie = session_cookie(&session.id, link.expires_at - OffsetDateTime::now_utc());
+    let response = run_share_session(state, session, request, next).await;
+    (cookies.add(cookie), response).into_response()
+}
+
+/// Route-group guard layered inside [`auth_middleware`]: rejects sessions and API tokens
+/// whose role is below `required`. A token's role follows from its scopes.
+pub async fn require_role(
+    State(required): State<UserRole>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let extensions = request.extensions();
+    let allowed = match extensions.get::<ApiToken>() {
+        Some(token) => token.role().allows(required),
+        None => extensions
+            .get::<Session>()
+            .is_some_and(|session| session.role.allows(required)),
+    };
+
+    if !allowed {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            &format!("Permission denied: {} role required", required),
+        );
+    }
+
+    next.run(request).await
+}
+
 fn unauthorized_response(message: &str) -> Response {
+    error_response(StatusCode::UNAUTHORIZED, message)
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
     let body = ErrorResponse {
         success: false,
         message: message.to_string(),
     };
-    (StatusCode::UNAUTHORIZED, Json(body)).into_response()
+    (status, Json(body)).into_response()
 }
 
 fn is_public_endpoint(path: &str) -> bool {
@@ -95,3 +609,90 @@ fn is_setup_public_endpoint(path: &str) -> bool {
         "/setup" | "/setup/init" | "/devices" | "/stream/codecs"
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{header, Method, Request, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::state::AppState;
+
+    fn bearer(method: Method, uri: &str, token: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_token_role_guards_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path()).await;
+        let (_, reader) = state
+            .api_tokens
+            .create("reader", vec!["hid:read".to_string()], None)
+            .await
+            .unwrap();
+        let (_, writer) = state
+            .api_tokens
+            .create("writer", vec!["hid:*".to_string()], None)
+            .await
+            .unwrap();
+        let (_, hid_writer) = state
+            .api_tokens
+            .create("hid", vec!["hid:write".to_string()], None)
+            .await
+            .unwrap();
+        let (_, config_reader) = state
+            .api_tokens
+            .create("config", vec!["config:read".to_string()], None)
+            .await
+            .unwrap();
+        let router = crate::web::create_router(state);
+        let status = |request: Request<Body>| {
+            let router = router.clone();
+            async move { router.oneshot(request).await.unwrap().status() }
+        };
+
+        // Read scopes act as a viewer
+        assert_eq!(
+            status(bearer(Method::GET, "/api/hid/status", &reader)).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(bearer(Method::GET, "/api/hid/presets", &reader)).await,
+            StatusCode::FORBIDDEN
+        );
+
+        // hid:* reaches operator routes but not admin-only ones
+        assert_eq!(
+            status(bearer(Method::GET, "/api/hid/presets", &writer)).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(bearer(Method::POST, "/api/hid/control/force", &writer)).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(bearer(Method::POST, "/api/hid/control/force", &hid_writer)).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(bearer(Method::GET, "/api/hid/jiggler", &writer)).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(bearer(Method::PUT, "/api/hid/jiggler", &writer)).await,
+            StatusCode::FORBIDDEN
+        );
+
+        // Admin-only config stays closed to read-scoped tokens
+        assert_eq!(
+            status(bearer(Method::GET, "/api/config", &config_reader)).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+}
@@ -1,9 +1,22 @@
+pub mod access;
+pub mod api_token;
+pub mod client_cert;
+mod limiter;
 pub mod middleware;
 mod password;
+pub mod proxy;
+mod recovery;
 mod session;
+pub mod share;
+pub mod totp;
 mod user;
 
-pub use middleware::{auth_middleware, SESSION_COOKIE};
+pub use access::{access_middleware, AccessControl, AccessService};
+pub use api_token::{ApiToken, ApiTokenStore};
+pub use limiter::{LoginLimiter, LoginLimits};
+pub use middleware::{auth_middleware, require_role, SESSION_COOKIE};
 pub use password::{hash_password, verify_password};
-pub use session::{Session, SessionStore};
-pub use user::{User, UserStore};
+pub use recovery::RecoveryCodeStore;
+pub use session::{session_key, Session, SessionInfo, SessionStore};
+pub use share::{ShareLink, ShareLinkStore};
+pub use user::{User, UserRole, UserStore, UserTotp};
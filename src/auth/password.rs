@@ -3,6 +3,8 @@ use argon2::{
     Argon2,
 };
 
+use sha2::{Digest, Sha256};
+
 use crate::error::{AppError, Result};
 
 pub fn hash_password(password: &str) -> Result<String> {
@@ -24,6 +26,15 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
         .is_ok())
 }
 
+/// Hex SHA-256 digest for high-entropy secrets (tokens, recovery codes) that
+/// must be looked up by value and therefore cannot use a salted hash.
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,278 @@
+use axum::http::{HeaderMap, HeaderName};
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+use super::access::{canonical_ip, parse_cidr};
+use super::UserRole;
+use crate::config::AuthConfig;
+
+/// Trusted reverse-proxy header authentication settings, see
+/// `AuthConfig::proxy_auth_*`.
+#[derive(Debug, Clone)]
+pub struct ProxyAuth {
+    header: HeaderName,
+    trusted: Vec<IpNet>,
+    pub default_role: Option<UserRole>,
+}
+
+impl ProxyAuth {
+    /// `None` when proxy auth is disabled or has no usable trusted proxies.
+    /// Invalid entries are skipped; they are rejected when the config is updated.
+    pub fn from_config(config: &AuthConfig) -> Option<Self> {
+        if !config.proxy_auth_enabled {
+            return None;
+        }
+        let header = HeaderName::from_bytes(config.proxy_auth_header.trim().as_bytes()).ok()?;
+        let trusted: Vec<IpNet> = config
+            .proxy_auth_trusted_cidrs
+            .iter()
+            .filter_map(|cidr| parse_cidr(cidr).ok())
+            .collect();
+        if trusted.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            header,
+            trusted,
+            default_role: config
+                .proxy_auth_default_role
+                .as_deref()
+                .and_then(|role| role.parse().ok()),
+        })
+    }
+
+    pub fn is_trusted(&self, peer: IpAddr) -> bool {
+        let peer = canonical_ip(peer);
+        self.trusted.iter().any(|net| net.contains(&peer))
+    }
+
+    /// Username asserted by the proxy. The header is ignored unless the
+    /// connection itself comes from a trusted proxy address.
+    pub fn username(&self, peer: Option<IpAddr>, headers: &HeaderMap) -> Option<String> {
+        if !peer.is_some_and(|peer| self.is_trusted(peer)) {
+            return None;
+        }
+        let username = headers.get(&self.header)?.to_str().ok()?.trim();
+        (!username.is_empty()).then(|| username.to_string())
+    }
+
+    /// Original client address from `X-Forwarded-For`. Hops are walked from
+    /// the right, since only entries appended by trusted proxies can be
+    /// believed; the first untrusted hop is the client. Anything left of it was
+    /// supplied by the client and is ignored.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let hops: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .collect();
+
+        let mut client = peer;
+        for hop in hops.iter().rev() {
+            if !self.is_trusted(client) {
+                break;
+            }
+            match hop.trim().parse() {
+                Ok(ip) => client = ip,
+                Err(_) => break,
+            }
+        }
+        client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn config() -> AuthConfig {
+        AuthConfig {
+            proxy_auth_enabled: true,
+            proxy_auth_trusted_cidrs: vec!["10.0.0.0/24".to_string(), "::1".to_string()],
+            proxy_auth_default_role: Some("viewer".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_header_trusted_only_from_proxy() {
+        let proxy = ProxyAuth::from_config(&config()).unwrap();
+        assert_eq!(proxy.default_role, Some(UserRole::Viewer));
+
+        // What a proxy stand-in would forward after authenticating "alice"
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-User", HeaderValue::from_static("alice"));
+        headers.insert(
+            "X-Forwarded-For",
+            HeaderValue::from_static("203.0.113.7, 10.0.0.2"),
+        );
+
+        let from_proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        assert_eq!(
+            proxy.username(Some(from_proxy), &headers).as_deref(),
+            Some("alice")
+        );
+        assert_eq!(
+            proxy.client_ip(from_proxy, &headers),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+
+        // A client-supplied entry left of the real client is ignored, and so
+        // are the addresses of further trusted proxies
+        headers.insert(
+            "X-Forwarded-For",
+            HeaderValue::from_static("198.51.100.1, 203.0.113.7, 10.0.0.3"),
+        );
+        assert_eq!(
+            proxy.client_ip(from_proxy, &headers),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+        let direct: IpAddr = "192.168.1.50".parse().unwrap();
+        assert_eq!(proxy.client_ip(direct, &headers), direct);
+
+        let mapped: IpAddr = "::ffff:10.0.0.9".parse().unwrap();
+        assert!(proxy.username(Some(mapped), &headers).is_some());
+        let loopback: IpAddr = "::1".parse().unwrap();
+        assert!(proxy.username(Some(loopback), &headers).is_some());
+
+        // A client talking to One-KVM directly cannot spoof the header
+        assert!(proxy.username(Some(direct), &headers).is_none());
+        assert!(proxy.username(None, &headers).is_none());
+
+        headers.insert("X-Forwarded-User", HeaderValue::from_static("  "));
+        assert!(proxy.username(Some(from_proxy), &headers).is_none());
+    }
+
+    #[test]
+    fn test_disabled_or_untrusted_config() {
+        let mut cfg = config();
+        cfg.proxy_auth_enabled = false;
+        assert!(ProxyAuth::from_config(&cfg).is_none());
+
+        let mut cfg = config();
+        cfg.proxy_auth_trusted_cidrs.clear();
+        assert!(ProxyAuth::from_config(&cfg).is_none());
+    }
+
+    /// Requests from a proxy stand-in through the real router and auth middleware
+    #[tokio::test]
+    async fn test_proxy_login_through_router() {
+        use crate::audit::AuditQuery;
+        use crate::state::AppState;
+        use axum::body::Body;
+        use axum::extract::ConnectInfo;
+        use axum::http::{header, Method, Request, StatusCode};
+        use std::net::SocketAddr;
+        use tower::ServiceExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path()).await;
+        state
+            .config
+            .update(|cfg| cfg.auth = config())
+            .await
+            .unwrap();
+        let router = crate::web::create_router(state.clone());
+
+        let request_to = |method: Method, uri: &str, peer: &str, user: &str| {
+            let mut request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("X-Forwarded-User", user)
+                .header(header::USER_AGENT, "proxy-test")
+                .body(Body::empty())
+                .unwrap();
+            let peer: SocketAddr = peer.parse().unwrap();
+            request.extensions_mut().insert(ConnectInfo(peer));
+            request
+        };
+        let request =
+            |peer: &str, user: &str| request_to(Method::GET, "/api/auth/check", peer, user);
+
+        // Trusted proxy: the account is created and a session cookie set
+        let response = router
+            .clone()
+            .oneshot(request("10.0.0.2:40000", "alice"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let cookie = response
+            .headers()
+            .get(header::SET_COOKIE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap();
+        assert!(cookie.starts_with("one_kvm_session="));
+        assert!(state
+            .users
+            .get_by_username("alice")
+            .await
+            .unwrap()
+            .is_some());
+
+        // A client that drops the cookie reuses that session
+        let response = router
+            .clone()
+            .oneshot(request("10.0.0.2:40001", "alice"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+        assert_eq!(state.sessions.list().await.unwrap().len(), 1);
+        let logins = state
+            .audit
+            .query(&AuditQuery {
+                action: Some("auth.login".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(logins.total, 1);
+
+        // ...as its real session, so it is listed as current and survives
+        // revoking the other sessions
+        let response = router
+            .clone()
+            .oneshot(request_to(
+                Method::GET,
+                "/api/auth/sessions",
+                "10.0.0.2:40002",
+                "alice",
+            ))
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let sessions: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(sessions[0]["current"], true);
+        let response = router
+            .clone()
+            .oneshot(request_to(
+                Method::DELETE,
+                "/api/auth/sessions",
+                "10.0.0.2:40003",
+                "alice",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(state.sessions.list().await.unwrap().len(), 1);
+
+        // A direct client cannot spoof the header
+        let response = router
+            .oneshot(request("192.168.1.50:40000", "mallory"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+        assert!(state
+            .users
+            .get_by_username("mallory")
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use rand::Rng;
+use sqlx::{Pool, Sqlite};
+
+use super::password::hash_token;
+use crate::error::Result;
+
+const CODE_COUNT: usize = 10;
+const CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+fn generate_code() -> String {
+    let mut rng = rand::rng();
+    let raw: String = (0..10)
+        .map(|_| CODE_ALPHABET[rng.random_range(0..CODE_ALPHABET.len())] as char)
+        .collect();
+    format!("{}-{}", &raw[..5], &raw[5..])
+}
+
+/// Codes are compared case-insensitively and without separators.
+fn hash_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    hash_token(&normalized)
+}
+
+/// One-time TOTP recovery codes per user, stored as SHA-256 hashes
+#[derive(Clone)]
+pub struct RecoveryCodeStore {
+    pool: Pool<Sqlite>,
+}
+
+impl RecoveryCodeStore {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Replace the user's codes with a fresh set and return them in plain text.
+    pub async fn regenerate(&self, user_id: &str) -> Result<Vec<String>> {
+        let codes: Vec<String> = (0..CODE_COUNT).map(|_| generate_code()).collect();
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = ?1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        for code in &codes {
+            sqlx::query("INSERT INTO totp_recovery_codes (code_hash, user_id) VALUES (?1, ?2)")
+                .bind(hash_code(code))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(codes)
+    }
+
+    /// Consume one of the user's codes. Returns false if it is unknown or was already used.
+    pub async fn consume(&self, user_id: &str, code: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM totp_recovery_codes WHERE code_hash = ?1 AND user_id = ?2")
+                .bind(hash_code(code))
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn remaining(&self, user_id: &str) -> Result<i64> {
+        let (count,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM totp_recovery_codes WHERE user_id = ?1")
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(count)
+    }
+
+    pub async fn clear(&self, user_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = ?1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Hand codes left over from the former global TOTP setup to `user_id`.
+    pub async fn adopt_unowned(&self, user_id: &str) -> Result<()> {
+        sqlx::query("UPDATE totp_recovery_codes SET user_id = ?1 WHERE user_id IS NULL")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_recovery_codes_are_single_use() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let store = RecoveryCodeStore::new(db.clone_pool());
+
+        let codes = store.regenerate("alice").await.unwrap();
+        assert_eq!(codes.len(), CODE_COUNT);
+        assert_eq!(store.remaining("alice").await.unwrap(), CODE_COUNT as i64);
+
+        assert!(store
+            .consume("alice", &codes[0].to_uppercase())
+            .await
+            .unwrap());
+        assert!(!store.consume("alice", &codes[0]).await.unwrap());
+        assert!(!store.consume("alice", "not-a-code").await.unwrap());
+        assert_eq!(
+            store.remaining("alice").await.unwrap(),
+            CODE_COUNT as i64 - 1
+        );
+
+        let fresh = store.regenerate("alice").await.unwrap();
+        assert!(!store.consume("alice", &codes[1]).await.unwrap());
+        assert!(store.consume("alice", &fresh[1]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_recovery_codes_are_per_user() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let store = RecoveryCodeStore::new(db.clone_pool());
+
+        let alice = store.regenerate("alice").await.unwrap();
+        let bob = store.regenerate("bob").await.unwrap();
+
+        assert!(!store.consume("bob", &alice[0]).await.unwrap());
+        assert_eq!(store.remaining("alice").await.unwrap(), CODE_COUNT as i64);
+
+        store.clear("alice").await.unwrap();
+        assert_eq!(store.remaining("alice").await.unwrap(), 0);
+        assert!(store.consume("bob", &bob[0]).await.unwrap());
+    }
+}
@@ -1,20 +1,28 @@
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
 use std::collections::HashMap;
 use std::sync::Arc;
 use time::{Duration, OffsetDateTime};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-use crate::error::Result;
+use super::password::hash_token;
+use super::user::UserRole;
+use crate::error::{AppError, Result};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
     pub user_id: String,
+    pub role: UserRole,
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
     #[serde(with = "time::serde::rfc3339")]
     pub expires_at: OffsetDateTime,
+    /// Client address that created the session
+    pub ip: Option<String>,
+    /// User-Agent of the client that created the session
+    pub user_agent: Option<String>,
     pub data: Option<serde_json::Value>,
 }
 
@@ -24,81 +32,479 @@ impl Session {
     }
 }
 
+/// Storage key for a session ID. Only this hash is kept, never the ID itself,
+/// and it is what [`SessionStore::delete_for_user`] reports for revoked sessions.
+pub fn session_key(session_id: &str) -> String {
+    hash_token(session_id)
+}
+
+/// A stored session as returned by [`SessionStore::list`]. The session ID itself
+/// is never kept, so sessions are identified by their [`session_key`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub key: String,
+    pub user_id: String,
+    pub role: UserRole,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+type SessionRow = (
+    String,
+    String,
+    i64,
+    i64,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+type SessionListRow = (
+    String,
+    String,
+    String,
+    i64,
+    i64,
+    Option<String>,
+    Option<String>,
+);
+
+fn from_unix(ts: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(ts).unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
+#[derive(Clone)]
+enum Backend {
+    /// Lost on restart; keyed by [`session_key`]
+    Memory(Arc<RwLock<HashMap<String, Session>>>),
+    /// `sessions` table in the main database
+    Sqlite(Pool<Sqlite>),
+}
+
 #[derive(Clone)]
 pub struct SessionStore {
-    inner: Arc<RwLock<HashMap<String, Session>>>,
+    backend: Backend,
     default_ttl: Duration,
+    /// Session IDs of clients that authenticate on every request instead of
+    /// keeping the cookie, keyed by [`Self::client_key`]; lost on restart
+    clients: Arc<parking_lot::Mutex<HashMap<String, String>>>,
 }
 
 impl SessionStore {
+    /// In-memory store; every session is dropped when the process exits.
     pub fn new(ttl_secs: i64) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(HashMap::new())),
+            backend: Backend::Memory(Arc::new(RwLock::new(HashMap::new()))),
+            default_ttl: Duration::seconds(ttl_secs),
+            clients: Arc::default(),
+        }
+    }
+
+    /// SQLite-backed store that survives restarts.
+    pub fn persistent(pool: Pool<Sqlite>, ttl_secs: i64) -> Self {
+        Self {
+            backend: Backend::Sqlite(pool),
             default_ttl: Duration::seconds(ttl_secs),
+            clients: Arc::default(),
         }
     }
 
-    pub async fn create(&self, user_id: &str) -> Result<Session> {
+    pub async fn create(
+        &self,
+        user_id: &str,
+        role: UserRole,
+        ip: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<Session> {
+        let expires_at = OffsetDateTime::now_utc() + self.default_ttl;
+        self.create_until(user_id, role, ip, user_agent, expires_at)
+            .await
+    }
+
+    /// Like [`Self::create`], with an explicit expiry instead of the default TTL.
+    pub async fn create_until(
+        &self,
+        user_id: &str,
+        role: UserRole,
+        ip: Option<String>,
+        user_agent: Option<String>,
+        expires_at: OffsetDateTime,
+    ) -> Result<Session> {
         let now = OffsetDateTime::now_utc();
         let session = Session {
             id: Uuid::new_v4().to_string(),
             user_id: user_id.to_string(),
+            role,
             created_at: now,
-            expires_at: now + self.default_ttl,
+            expires_at,
+            ip,
+            user_agent,
             data: None,
         };
+        let key = session_key(&session.id);
+
+        match &self.backend {
+            Backend::Memory(inner) => {
+                let mut guard = inner.write().await;
+                guard.insert(key, session.clone());
+            }
+            Backend::Sqlite(pool) => {
+                let data = session
+                    .data
+                    .as_ref()
+                    .map(serde_json::to_string)
+                    .transpose()?;
+                sqlx::query(
+                    r#"
+                    INSERT INTO sessions (id_hash, user_id, role, created_at, expires_at, ip, user_agent, data)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                    "#,
+                )
+                .bind(&key)
+                .bind(&session.user_id)
+                .bind(session.role.as_str())
+                .bind(session.created_at.unix_timestamp())
+                .bind(session.expires_at.unix_timestamp())
+                .bind(&session.ip)
+                .bind(&session.user_agent)
+                .bind(data)
+                .execute(pool)
+                .await?;
+            }
+        }
 
-        let mut guard = self.inner.write().await;
-        guard.insert(session.id.clone(), session.clone());
         Ok(session)
     }
 
     pub async fn get(&self, session_id: &str) -> Result<Option<Session>> {
-        let mut guard = self.inner.write().await;
-        let Some(session) = guard.get(session_id).cloned() else {
-            return Ok(None);
+        let key = session_key(session_id);
+        let session = match &self.backend {
+            Backend::Memory(inner) => inner.read().await.get(&key).cloned(),
+            Backend::Sqlite(pool) => {
+                let row: Option<SessionRow> = sqlx::query_as(
+                    "SELECT user_id, role, created_at, expires_at, ip, user_agent, data FROM sessions WHERE id_hash = ?1",
+                )
+                .bind(&key)
+                .fetch_optional(pool)
+                .await?;
+
+                match row {
+                    Some((user_id, role, created_at, expires_at, ip, user_agent, data)) => {
+                        Some(Session {
+                            id: session_id.to_string(),
+                            user_id,
+                            role: role.parse()?,
+                            created_at: from_unix(created_at),
+                            expires_at: from_unix(expires_at),
+                            ip,
+                            user_agent,
+                            data: data.map(|d| serde_json::from_str(&d)).transpose().map_err(
+                                |e| AppError::Internal(format!("Invalid session data: {}", e)),
+                            )?,
+                        })
+                    }
+                    None => None,
+                }
+            }
         };
-        if session.is_expired() {
-            guard.remove(session_id);
+
+        match session {
+            Some(session) if session.is_expired() => {
+                self.delete(session_id).await?;
+                Ok(None)
+            }
+            other => Ok(other),
+        }
+    }
+
+    /// Identity of a cookie-less client: the user and the address and user
+    /// agent the session was opened from.
+    pub fn client_key(user_id: &str, ip: Option<&str>, user_agent: Option<&str>) -> String {
+        format!(
+            "{}\n{}\n{}",
+            user_id,
+            ip.unwrap_or_default(),
+            user_agent.unwrap_or_default()
+        )
+    }
+
+    /// Live session last opened by the client `client_key`.
+    pub async fn get_for_client(&self, client_key: &str) -> Result<Option<Session>> {
+        let Some(session_id) = self.clients.lock().get(client_key).cloned() else {
             return Ok(None);
+        };
+        let session = self.get(&session_id).await?;
+        if session.is_none() {
+            self.clients.lock().remove(client_key);
         }
-        Ok(Some(session))
+        Ok(session)
+    }
+
+    /// Remember `session_id` for later requests from the client `client_key`.
+    pub fn set_for_client(&self, client_key: String, session_id: &str) {
+        self.clients
+            .lock()
+            .insert(client_key, session_id.to_string());
     }
 
     pub async fn delete(&self, session_id: &str) -> Result<()> {
-        let mut guard = self.inner.write().await;
-        guard.remove(session_id);
+        let key = session_key(session_id);
+        match &self.backend {
+            Backend::Memory(inner) => {
+                inner.write().await.remove(&key);
+            }
+            Backend::Sqlite(pool) => {
+                sqlx::query("DELETE FROM sessions WHERE id_hash = ?1")
+                    .bind(&key)
+                    .execute(pool)
+                    .await?;
+            }
+        }
         Ok(())
     }
 
     pub async fn cleanup_expired(&self) -> Result<u64> {
-        let mut guard = self.inner.write().await;
-        let before = guard.len();
-        guard.retain(|_, s| !s.is_expired());
-        Ok((before - guard.len()) as u64)
+        match &self.backend {
+            Backend::Memory(inner) => {
+                let mut guard = inner.write().await;
+                let before = guard.len();
+                guard.retain(|_, s| !s.is_expired());
+                Ok((before - guard.len()) as u64)
+            }
+            Backend::Sqlite(pool) => {
+                let result = sqlx::query("DELETE FROM sessions WHERE expires_at < ?1")
+                    .bind(OffsetDateTime::now_utc().unix_timestamp())
+                    .execute(pool)
+                    .await?;
+                Ok(result.rows_affected())
+            }
+        }
     }
 
     pub async fn delete_all(&self) -> Result<u64> {
-        let mut guard = self.inner.write().await;
-        let n = guard.len() as u64;
-        guard.clear();
-        Ok(n)
+        match &self.backend {
+            Backend::Memory(inner) => {
+                let mut guard = inner.write().await;
+                let n = guard.len() as u64;
+                guard.clear();
+                Ok(n)
+            }
+            Backend::Sqlite(pool) => {
+                let result = sqlx::query("DELETE FROM sessions").execute(pool).await?;
+                Ok(result.rows_affected())
+            }
+        }
     }
 
+    /// Remove every session belonging to `user_id`, returning the removed session keys.
+    pub async fn delete_for_user(&self, user_id: &str) -> Result<Vec<String>> {
+        match &self.backend {
+            Backend::Memory(inner) => {
+                let mut guard = inner.write().await;
+                let keys: Vec<String> = guard
+                    .iter()
+                    .filter(|(_, s)| s.user_id == user_id)
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in &keys {
+                    guard.remove(key);
+                }
+                Ok(keys)
+            }
+            Backend::Sqlite(pool) => {
+                let rows: Vec<(String,)> =
+                    sqlx::query_as("DELETE FROM sessions WHERE user_id = ?1 RETURNING id_hash")
+                        .bind(user_id)
+                        .fetch_all(pool)
+                        .await?;
+                Ok(rows.into_iter().map(|(key,)| key).collect())
+            }
+        }
+    }
+
+    /// Keys (see [`session_key`]) of all stored sessions.
     pub async fn list_ids(&self) -> Result<Vec<String>> {
-        let guard = self.inner.read().await;
-        Ok(guard.keys().cloned().collect())
+        match &self.backend {
+            Backend::Memory(inner) => Ok(inner.read().await.keys().cloned().collect()),
+            Backend::Sqlite(pool) => {
+                let rows: Vec<(String,)> = sqlx::query_as("SELECT id_hash FROM sessions")
+                    .fetch_all(pool)
+                    .await?;
+                Ok(rows.into_iter().map(|(key,)| key).collect())
+            }
+        }
+    }
+
+    /// All unexpired sessions, oldest first.
+    pub async fn list(&self) -> Result<Vec<SessionInfo>> {
+        let mut sessions = match &self.backend {
+            Backend::Memory(inner) => inner
+                .read()
+                .await
+                .iter()
+                .filter(|(_, s)| !s.is_expired())
+                .map(|(key, s)| SessionInfo {
+                    key: key.clone(),
+                    user_id: s.user_id.clone(),
+                    role: s.role,
+                    created_at: s.created_at,
+                    expires_at: s.expires_at,
+                    ip: s.ip.clone(),
+                    user_agent: s.user_agent.clone(),
+                })
+                .collect(),
+            Backend::Sqlite(pool) => {
+                let rows: Vec<SessionListRow> = sqlx::query_as(
+                    "SELECT id_hash, user_id, role, created_at, expires_at, ip, user_agent FROM sessions WHERE expires_at >= ?1",
+                )
+                .bind(OffsetDateTime::now_utc().unix_timestamp())
+                .fetch_all(pool)
+                .await?;
+
+                rows.into_iter()
+                    .map(
+                        |(key, user_id, role, created_at, expires_at, ip, user_agent)| {
+                            Ok(SessionInfo {
+                                key,
+                                user_id,
+                                role: role.parse()?,
+                                created_at: from_unix(created_at),
+                                expires_at: from_unix(expires_at),
+                                ip,
+                                user_agent,
+                            })
+                        },
+                    )
+                    .collect::<Result<Vec<_>>>()?
+            }
+        };
+        sessions.sort_by_key(|s| s.created_at);
+        Ok(sessions)
+    }
+
+    /// Remove a session by its [`session_key`]. Returns false if it did not exist.
+    pub async fn delete_by_key(&self, key: &str) -> Result<bool> {
+        match &self.backend {
+            Backend::Memory(inner) => Ok(inner.write().await.remove(key).is_some()),
+            Backend::Sqlite(pool) => {
+                let result = sqlx::query("DELETE FROM sessions WHERE id_hash = ?1")
+                    .bind(key)
+                    .execute(pool)
+                    .await?;
+                Ok(result.rows_affected() > 0)
+            }
+        }
     }
 
     pub async fn extend(&self, session_id: &str) -> Result<()> {
-        let mut guard = self.inner.write().await;
-        if let Some(session) = guard.get_mut(session_id) {
-            if session.is_expired() {
-                guard.remove(session_id);
-            } else {
-                session.expires_at = OffsetDateTime::now_utc() + self.default_ttl;
+        let Some(_) = self.get(session_id).await? else {
+            return Ok(());
+        };
+        let key = session_key(session_id);
+        let expires_at = OffsetDateTime::now_utc() + self.default_ttl;
+
+        match &self.backend {
+            Backend::Memory(inner) => {
+                if let Some(session) = inner.write().await.get_mut(&key) {
+                    session.expires_at = expires_at;
+                }
+            }
+            Backend::Sqlite(pool) => {
+                sqlx::query("UPDATE sessions SET expires_at = ?1 WHERE id_hash = ?2")
+                    .bind(expires_at.unix_timestamp())
+                    .bind(&key)
+                    .execute(pool)
+                    .await?;
             }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use tempfile::tempdir;
+
+    async fn exercise(store: &SessionStore) {
+        let a = store
+            .create(
+                "u1",
+                UserRole::Operator,
+                Some("10.0.0.2".to_string()),
+                Some("curl/8".to_string()),
+            )
+            .await
+            .unwrap();
+        let b = store
+            .create("u1", UserRole::Operator, None, None)
+            .await
+            .unwrap();
+        let c = store
+            .create("u2", UserRole::Viewer, None, None)
+            .await
+            .unwrap();
+
+        let fetched = store.get(&a.id).await.unwrap().unwrap();
+        assert_eq!(fetched.id, a.id);
+        assert_eq!(fetched.role, UserRole::Operator);
+        assert_eq!(fetched.ip.as_deref(), Some("10.0.0.2"));
+        assert_eq!(fetched.user_agent.as_deref(), Some("curl/8"));
+        assert!(!store.list_ids().await.unwrap().contains(&a.id));
+
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed.len(), 3);
+        let listed_a = listed.iter().find(|s| s.key == session_key(&a.id)).unwrap();
+        assert_eq!(listed_a.user_id, "u1");
+        assert_eq!(listed_a.ip.as_deref(), Some("10.0.0.2"));
+
+        let mut revoked = store.delete_for_user("u1").await.unwrap();
+        revoked.sort();
+        let mut expected = vec![session_key(&a.id), session_key(&b.id)];
+        expected.sort();
+        assert_eq!(revoked, expected);
+        assert!(store.get(&a.id).await.unwrap().is_none());
+        assert!(store.get(&c.id).await.unwrap().is_some());
+
+        assert!(store.delete_by_key(&session_key(&c.id)).await.unwrap());
+        assert!(!store.delete_by_key(&session_key(&c.id)).await.unwrap());
+        assert!(store.get(&c.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        exercise(&SessionStore::new(3600)).await;
+    }
+
+    #[tokio::test]
+    async fn test_persistent_store() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        exercise(&SessionStore::persistent(db.clone_pool(), 3600)).await;
+
+        // Sessions survive a new store instance (i.e. a restart)
+        let session = SessionStore::persistent(db.clone_pool(), 3600)
+            .create("u1", UserRole::Admin, None, None)
+            .await
+            .unwrap();
+        let reopened = SessionStore::persistent(db.clone_pool(), 3600);
+        assert!(reopened.get(&session.id).await.unwrap().is_some());
+
+        // Expired rows are removed by cleanup_expired
+        let expired = SessionStore::persistent(db.clone_pool(), -10);
+        expired
+            .create("u1", UserRole::Admin, None, None)
+            .await
+            .unwrap();
+        assert_eq!(expired.cleanup_expired().await.unwrap(), 1);
+        assert_eq!(reopened.list_ids().await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,304 @@
+use rand::Rng;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::password::{hash_password, hash_token, verify_password};
+use super::Session;
+use crate::error::{AppError, Result};
+
+/// Prefix of share link secrets, passed as the `share` query parameter
+pub const SHARE_TOKEN_PREFIX: &str = "okvs_";
+
+/// `user_id` prefix of sessions created from a share link
+pub const SHARE_USER_PREFIX: &str = "share:";
+
+type ShareLinkRow = (
+    String,
+    String,
+    bool,
+    i64,
+    Option<String>,
+    Option<String>,
+    i64,
+    i64,
+);
+
+/// A view-only link to the video stream
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareLink {
+    pub id: String,
+    pub name: String,
+    /// Whether audio may be streamed too
+    pub audio: bool,
+    /// Maximum number of concurrent viewers
+    pub max_viewers: u32,
+    /// Whether a PIN must be entered before viewing
+    pub has_pin: bool,
+    #[serde(skip)]
+    pin_hash: Option<String>,
+    pub created_by: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+fn from_unix(ts: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp(ts).unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
+impl ShareLink {
+    fn from_row(row: ShareLinkRow) -> Self {
+        let (id, name, audio, max_viewers, pin_hash, created_by, created_at, expires_at) = row;
+        Self {
+            id,
+            name,
+            audio,
+            max_viewers: max_viewers.max(0) as u32,
+            has_pin: pin_hash.is_some(),
+            pin_hash,
+            created_by,
+            created_at: from_unix(created_at),
+            expires_at: from_unix(expires_at),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        OffsetDateTime::now_utc() > self.expires_at
+    }
+
+    /// `user_id` of the sessions opened through this link
+    pub fn session_user_id(&self) -> String {
+        format!("{}{}", SHARE_USER_PREFIX, self.id)
+    }
+
+    /// Links without a PIN accept anything.
+    pub fn verify_pin(&self, pin: Option<&str>) -> bool {
+        match (&self.pin_hash, pin) {
+            (None, _) => true,
+            (Some(hash), Some(pin)) => verify_password(pin, hash).unwrap_or(false),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Share viewers may only reach the video (and optionally audio) endpoints.
+    /// WebRTC signalling is further limited to the viewer's own sessions.
+    pub fn allows_path(&self, path: &str) -> bool {
+        let path = path.strip_prefix("/api").unwrap_or(path);
+        match path {
+            "/auth/check" | "/stream" | "/stream/mjpeg" | "/stream/status" | "/stream/mode"
+            | "/stream/codecs" | "/webrtc/offer" | "/webrtc/ice" | "/webrtc/close" => true,
+            "/ws/audio" | "/audio/status" => self.audio,
+            _ => false,
+        }
+    }
+}
+
+/// Endpoints that start a stream, and so take one of a link's viewer slots
+pub fn is_stream_path(path: &str) -> bool {
+    let path = path.strip_prefix("/api").unwrap_or(path);
+    matches!(path, "/stream" | "/stream/mjpeg" | "/webrtc/offer")
+}
+
+/// Share link ID of a session created from a link
+pub fn share_link_id(session: &Session) -> Option<&str> {
+    session.user_id.strip_prefix(SHARE_USER_PREFIX)
+}
+
+fn generate_token() -> String {
+    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    let mut rng = rand::rng();
+    let secret: String = (0..40)
+        .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
+        .collect();
+    format!("{}{}", SHARE_TOKEN_PREFIX, secret)
+}
+
+const SELECT_COLUMNS: &str =
+    "SELECT id, name, audio, max_viewers, pin_hash, created_by, created_at, expires_at FROM share_links";
+
+#[derive(Clone)]
+pub struct ShareLinkStore {
+    pool: Pool<Sqlite>,
+}
+
+impl ShareLinkStore {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// All links, including expired ones, oldest first
+    pub async fn list(&self) -> Result<Vec<ShareLink>> {
+        let rows: Vec<ShareLinkRow> =
+            sqlx::query_as(&format!("{} ORDER BY created_at ASC", SELECT_COLUMNS))
+                .fetch_all(&self.pool)
+                .await?;
+        Ok(rows.into_iter().map(ShareLink::from_row).collect())
+    }
+
+    /// Create a link. The plain-text secret is returned once and never stored.
+    pub async fn create(
+        &self,
+        name: &str,
+        audio: bool,
+        max_viewers: u32,
+        pin: Option<&str>,
+        expires_at: OffsetDateTime,
+        created_by: Option<String>,
+    ) -> Result<(ShareLink, String)> {
+        let secret = generate_token();
+        let pin_hash = pin.map(hash_password).transpose()?;
+        let link = ShareLink {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            audio,
+            max_viewers,
+            has_pin: pin_hash.is_some(),
+            pin_hash,
+            created_by,
+            created_at: OffsetDateTime::now_utc(),
+            expires_at,
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO share_links (id, name, token_hash, audio, max_viewers, pin_hash, created_by, created_at, expires_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            "#,
+        )
+        .bind(&link.id)
+        .bind(&link.name)
+        .bind(hash_token(&secret))
+        .bind(link.audio)
+        .bind(link.max_viewers as i64)
+        .bind(&link.pin_hash)
+        .bind(&link.created_by)
+        .bind(link.created_at.unix_timestamp())
+        .bind(link.expires_at.unix_timestamp())
+        .execute(&self.pool)
+        .await?;
+
+        Ok((link, secret))
+    }
+
+    /// Live (unexpired) link by ID
+    pub async fn get(&self, id: &str) -> Result<Option<ShareLink>> {
+        let row: Option<ShareLinkRow> =
+            sqlx::query_as(&format!("{} WHERE id = ?1", SELECT_COLUMNS))
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row
+            .map(ShareLink::from_row)
+            .filter(|link| !link.is_expired()))
+    }
+
+    /// Resolve a presented secret to a live link.
+    pub async fn authenticate(&self, secret: &str) -> Result<Option<ShareLink>> {
+        let row: Option<ShareLinkRow> =
+            sqlx::query_as(&format!("{} WHERE token_hash = ?1", SELECT_COLUMNS))
+                .bind(hash_token(secret))
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row
+            .map(ShareLink::from_row)
+            .filter(|link| !link.is_expired()))
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM share_links WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Share link not found".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_share_link_lifecycle() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let store = ShareLinkStore::new(db.clone_pool());
+
+        let expires_at = OffsetDateTime::now_utc() + time::Duration::hours(1);
+        let (link, secret) = store
+            .create("vendor", false, 2, Some("1234"), expires_at, None)
+            .await
+            .unwrap();
+        assert!(secret.starts_with(SHARE_TOKEN_PREFIX));
+
+        let authed = store.authenticate(&secret).await.unwrap().unwrap();
+        assert_eq!(authed.id, link.id);
+        assert!(authed.has_pin);
+        assert!(authed.verify_pin(Some("1234")));
+        assert!(!authed.verify_pin(Some("0000")));
+        assert!(!authed.verify_pin(None));
+        assert!(store.authenticate("okvs_wrong").await.unwrap().is_none());
+
+        let (_, expired) = store
+            .create(
+                "old",
+                true,
+                1,
+                None,
+                OffsetDateTime::now_utc() - time::Duration::hours(1),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(store.authenticate(&expired).await.unwrap().is_none());
+        assert_eq!(store.list().await.unwrap().len(), 2);
+
+        store.delete(&link.id).await.unwrap();
+        assert!(store.get(&link.id).await.unwrap().is_none());
+        assert!(store.authenticate(&secret).await.unwrap().is_none());
+        assert!(store.delete(&link.id).await.is_err());
+    }
+
+    #[test]
+    fn test_share_paths() {
+        let mut link = ShareLink::from_row((
+            "id".to_string(),
+            "n".to_string(),
+            false,
+            1,
+            None,
+            None,
+            0,
+            0,
+        ));
+        assert!(link.allows_path("/api/stream/mjpeg"));
+        assert!(link.allows_path("/webrtc/offer"));
+        assert!(link.allows_path("/api/webrtc/close"));
+        assert!(!link.allows_path("/api/webrtc/session"));
+        assert!(!link.allows_path("/api/webrtc/status"));
+        assert!(!link.allows_path("/api/webrtc/ice-servers"));
+        assert!(!link.allows_path("/api/ws/audio"));
+        assert!(!link.allows_path("/api/hid/status"));
+        assert!(!link.allows_path("/api/ws/hid"));
+        assert!(!link.allows_path("/api/snapshot"));
+        assert!(!link.allows_path("/api/atx/power"));
+
+        link.audio = true;
+        assert!(link.allows_path("/ws/audio"));
+
+        assert!(is_stream_path("/api/webrtc/offer"));
+        assert!(is_stream_path("/api/stream/mjpeg"));
+        assert!(!is_stream_path("/api/webrtc/ice"));
+    }
+}
@@ -7,7 +7,53 @@ use uuid::Uuid;
 use super::password::{hash_password, verify_password};
 use crate::error::{AppError, Result};
 
-type UserRow = (String, String, String);
+type UserRow = (String, String, String, String);
+
+/// Access level of a local account. Ordered from least to most privileged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Video, snapshots and status only
+    Viewer,
+    /// Viewer plus HID, MSD and ATX control
+    Operator,
+    /// Full access including configuration, updates and user management
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Whether this role grants at least the privileges of `required`.
+    pub fn allows(&self, required: UserRole) -> bool {
+        *self >= required
+    }
+}
+
+impl std::str::FromStr for UserRole {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "viewer" => Ok(Self::Viewer),
+            "operator" => Ok(Self::Operator),
+            "admin" => Ok(Self::Admin),
+            other => Err(AppError::BadRequest(format!("Invalid role: {}", other))),
+        }
+    }
+}
+
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -15,19 +61,30 @@ pub struct User {
     pub username: String,
     #[serde(skip_serializing)]
     pub password_hash: String,
+    pub role: UserRole,
 }
 
 impl User {
-    fn from_row(row: UserRow) -> Self {
-        let (id, username, password_hash) = row;
-        Self {
+    fn from_row(row: UserRow) -> Result<Self> {
+        let (id, username, password_hash, role) = row;
+        Ok(Self {
             id,
             username,
             password_hash,
-        }
+            role: role.parse()?,
+        })
     }
 }
 
+/// A user's two-factor state. The secret is stored encrypted by the caller.
+#[derive(Debug, Clone, Default)]
+pub struct UserTotp {
+    pub secret: Option<String>,
+    pub enabled: bool,
+    /// Last TOTP time step accepted; codes at or before it are replays
+    pub last_step: Option<i64>,
+}
+
 #[derive(Clone)]
 pub struct UserStore {
     pool: Pool<Sqlite>,
@@ -38,46 +95,89 @@ impl UserStore {
         Self { pool }
     }
 
-    /// The single local user, or `None` if none exists. Errors if more than one row is present.
-    pub async fn single_user(&self) -> Result<Option<User>> {
-        let mut rows: Vec<UserRow> = sqlx::query_as(
-            "SELECT id, username, password_hash FROM users ORDER BY rowid ASC LIMIT 2",
+    /// All local users in creation order.
+    pub async fn list(&self) -> Result<Vec<User>> {
+        let rows: Vec<UserRow> = sqlx::query_as(
+            "SELECT id, username, password_hash, role FROM users ORDER BY rowid ASC",
         )
         .fetch_all(&self.pool)
         .await?;
 
-        match rows.len() {
-            0 => Ok(None),
-            1 => Ok(Some(User::from_row(rows.remove(0)))),
-            _ => Err(AppError::Internal(
-                "Multiple user accounts in database; this build supports only one".to_string(),
-            )),
-        }
+        rows.into_iter().map(User::from_row).collect()
     }
 
+    pub async fn get(&self, user_id: &str) -> Result<Option<User>> {
+        let row: Option<UserRow> =
+            sqlx::query_as("SELECT id, username, password_hash, role FROM users WHERE id = ?1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        row.map(User::from_row).transpose()
+    }
+
+    pub async fn get_by_username(&self, username: &str) -> Result<Option<User>> {
+        let row: Option<UserRow> = sqlx::query_as(
+            "SELECT id, username, password_hash, role FROM users WHERE username = ?1",
+        )
+        .bind(username)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(User::from_row).transpose()
+    }
+
+    pub async fn count(&self) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
+    async fn count_admins(&self) -> Result<i64> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users WHERE role = 'admin'")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
+    /// Create the initial administrator during setup. Fails once any account exists.
     pub async fn create_first_user(&self, username: &str, password: &str) -> Result<User> {
-        if self.single_user().await?.is_some() {
+        if self.count().await? > 0 {
             return Err(AppError::BadRequest(
                 "A user account already exists".to_string(),
             ));
         }
 
+        self.create(username, password, UserRole::Admin).await
+    }
+
+    pub async fn create(&self, username: &str, password: &str, role: UserRole) -> Result<User> {
+        if self.get_by_username(username).await?.is_some() {
+            return Err(AppError::BadRequest(format!(
+                "User '{}' already exists",
+                username
+            )));
+        }
+
         let password_hash = hash_password(password)?;
         let user = User {
             id: Uuid::new_v4().to_string(),
             username: username.to_string(),
             password_hash,
+            role,
         };
 
         sqlx::query(
             r#"
-            INSERT INTO users (id, username, password_hash)
-            VALUES (?1, ?2, ?3)
+            INSERT INTO users (id, username, password_hash, role)
+            VALUES (?1, ?2, ?3, ?4)
             "#,
         )
         .bind(&user.id)
         .bind(&user.username)
         .bind(&user.password_hash)
+        .bind(user.role.as_str())
         .execute(&self.pool)
         .await?;
 
@@ -85,15 +185,11 @@ impl UserStore {
     }
 
     pub async fn verify(&self, username: &str, password: &str) -> Result<Option<User>> {
-        let user = match self.single_user().await? {
+        let user = match self.get_by_username(username).await? {
             Some(u) => u,
             None => return Ok(None),
         };
 
-        if user.username != username {
-            return Ok(None);
-        }
-
         if verify_password(password, &user.password_hash)? {
             Ok(Some(user))
         } else {
@@ -102,15 +198,6 @@ impl UserStore {
     }
 
     pub async fn update_password(&self, user_id: &str, new_password: &str) -> Result<()> {
-        let user = self
-            .single_user()
-            .await?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
-
-        if user.id != user_id {
-            return Err(AppError::AuthError("Invalid session".to_string()));
-        }
-
         let password_hash = hash_password(new_password)?;
         let now = OffsetDateTime::now_utc();
 
@@ -131,18 +218,21 @@ impl UserStore {
 
     pub async fn update_username(&self, user_id: &str, new_username: &str) -> Result<()> {
         let user = self
-            .single_user()
+            .get(user_id)
             .await?
             .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-        if user.id != user_id {
-            return Err(AppError::AuthError("Invalid session".to_string()));
-        }
-
         if new_username == user.username {
             return Ok(());
         }
 
+        if self.get_by_username(new_username).await?.is_some() {
+            return Err(AppError::BadRequest(format!(
+                "User '{}' already exists",
+                new_username
+            )));
+        }
+
         let now = OffsetDateTime::now_utc();
         let result = sqlx::query("UPDATE users SET username = ?1, updated_at = ?2 WHERE id = ?3")
             .bind(new_username)
@@ -157,4 +247,210 @@ impl UserStore {
 
         Ok(())
     }
+
+    /// Change a user's role. Refuses to demote the last administrator.
+    pub async fn update_role(&self, user_id: &str, role: UserRole) -> Result<()> {
+        let user = self
+            .get(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if user.role == role {
+            return Ok(());
+        }
+
+        if user.role == UserRole::Admin && self.count_admins().await? <= 1 {
+            return Err(AppError::BadRequest(
+                "Cannot demote the last administrator".to_string(),
+            ));
+        }
+
+        let now = OffsetDateTime::now_utc();
+        sqlx::query("UPDATE users SET role = ?1, updated_at = ?2 WHERE id = ?3")
+            .bind(role.as_str())
+            .bind(now.format(&Rfc3339).expect("RFC3339 format"))
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete a user. Refuses to delete the last administrator.
+    pub async fn delete(&self, user_id: &str) -> Result<()> {
+        let user = self
+            .get(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        if user.role == UserRole::Admin && self.count_admins().await? <= 1 {
+            return Err(AppError::BadRequest(
+                "Cannot delete the last administrator".to_string(),
+            ));
+        }
+
+        sqlx::query("DELETE FROM users WHERE id = ?1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        sqlx::query("DELETE FROM totp_recovery_codes WHERE user_id = ?1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn totp(&self, user_id: &str) -> Result<UserTotp> {
+        let row: Option<(Option<String>, bool, Option<i64>)> = sqlx::query_as(
+            "SELECT totp_secret, totp_enabled, totp_last_step FROM users WHERE id = ?1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let (secret, enabled, last_step) =
+            row.ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        Ok(UserTotp {
+            secret,
+            enabled,
+            last_step,
+        })
+    }
+
+    /// Store a new (pending) TOTP secret, or clear it with `None`. Either way
+    /// two-factor login is off until [`Self::enable_totp`] is called.
+    pub async fn set_totp_secret(&self, user_id: &str, secret: Option<&str>) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE users SET totp_secret = ?1, totp_enabled = 0, totp_last_step = NULL WHERE id = ?2",
+        )
+        .bind(secret)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("User not found".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn enable_totp(&self, user_id: &str) -> Result<()> {
+        sqlx::query("UPDATE users SET totp_enabled = 1 WHERE id = ?1 AND totp_secret IS NOT NULL")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record `step` as used. Returns false if it is not newer than the last
+    /// accepted step, i.e. the code is a replay.
+    pub async fn use_totp_step(&self, user_id: &str, step: i64) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE users SET totp_last_step = ?1
+            WHERE id = ?2 AND (totp_last_step IS NULL OR totp_last_step < ?1)
+            "#,
+        )
+        .bind(step)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_multi_user_roles() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let users = UserStore::new(db.clone_pool());
+
+        let admin = users.create_first_user("admin", "secret").await.unwrap();
+        assert_eq!(admin.role, UserRole::Admin);
+        assert!(users.create_first_user("other", "secret").await.is_err());
+
+        let viewer = users
+            .create("viewer", "secret", UserRole::Viewer)
+            .await
+            .unwrap();
+        assert!(users
+            .create("viewer", "secret", UserRole::Viewer)
+            .await
+            .is_err());
+        assert_eq!(users.list().await.unwrap().len(), 2);
+
+        let verified = users.verify("viewer", "secret").await.unwrap().unwrap();
+        assert_eq!(verified.id, viewer.id);
+        assert_eq!(verified.role, UserRole::Viewer);
+        assert!(users.verify("viewer", "wrong").await.unwrap().is_none());
+
+        // The only admin can be neither demoted nor deleted
+        assert!(users
+            .update_role(&admin.id, UserRole::Operator)
+            .await
+            .is_err());
+        assert!(users.delete(&admin.id).await.is_err());
+
+        users
+            .update_role(&viewer.id, UserRole::Admin)
+            .await
+            .unwrap();
+        users.delete(&admin.id).await.unwrap();
+        assert_eq!(users.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_totp_steps_are_single_use() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let users = UserStore::new(db.clone_pool());
+        let admin = users.create_first_user("admin", "secret").await.unwrap();
+        let viewer = users
+            .create("viewer", "secret", UserRole::Viewer)
+            .await
+            .unwrap();
+
+        users
+            .set_totp_secret(&admin.id, Some("SECRET"))
+            .await
+            .unwrap();
+        users.enable_totp(&admin.id).await.unwrap();
+        let totp = users.totp(&admin.id).await.unwrap();
+        assert!(totp.enabled);
+        assert_eq!(totp.secret.as_deref(), Some("SECRET"));
+        assert!(!users.totp(&viewer.id).await.unwrap().enabled);
+
+        assert!(users.use_totp_step(&admin.id, 100).await.unwrap());
+        assert!(!users.use_totp_step(&admin.id, 100).await.unwrap());
+        assert!(!users.use_totp_step(&admin.id, 99).await.unwrap());
+        assert!(users.use_totp_step(&admin.id, 101).await.unwrap());
+        // Steps are tracked per user
+        assert!(users.use_totp_step(&viewer.id, 100).await.unwrap());
+
+        users.set_totp_secret(&admin.id, None).await.unwrap();
+        let totp = users.totp(&admin.id).await.unwrap();
+        assert!(!totp.enabled && totp.secret.is_none() && totp.last_step.is_none());
+    }
+
+    #[test]
+    fn test_role_ordering() {
+        assert!(UserRole::Admin.allows(UserRole::Operator));
+        assert!(UserRole::Operator.allows(UserRole::Viewer));
+        assert!(!UserRole::Viewer.allows(UserRole::Operator));
+        assert_eq!("operator".parse::<UserRole>().unwrap(), UserRole::Operator);
+        assert!("root".parse::<UserRole>().is_err());
+    }
 }
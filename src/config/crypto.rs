@@ -0,0 +1,350 @@
+//! Envelope encryption of secret config fields.
+//!
+//! Secret fields are encrypted with a random data key (AES-256-GCM). The data
+//! key is stored in the `config` table, wrapped with a key-encryption key read
+//! from a file outside the database. Rotating the key file only re-wraps the
+//! data key, so it is safe while the server is running.
+
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::Rng;
+use serde_json::Value;
+use sqlx::{Pool, Sqlite};
+use std::path::{Path, PathBuf};
+
+use crate::error::{AppError, Result};
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Prefix of encrypted values in the stored config JSON
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// `config` table row holding the wrapped data key
+const DATA_KEY_ROW: &str = "data_key";
+
+/// Dotted paths of the config fields encrypted at rest
+pub const SECRET_FIELDS: &[&str] = &[
+    "auth.totp_secret",
+    "stream.turn_password",
+    "rtsp.password",
+    "rustdesk.device_password",
+    "rustdesk.relay_key",
+    "rustdesk.private_key",
+    "rustdesk.signing_private_key",
+    "extensions.gostc.key",
+    "extensions.easytier.network_secret",
+];
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::rng().fill(&mut bytes[..]);
+    bytes
+}
+
+fn cipher(key: &[u8; KEY_LEN]) -> Aes256Gcm {
+    Aes256Gcm::new(key.into())
+}
+
+/// `base64(nonce || ciphertext)`
+fn seal_bytes(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<String> {
+    let nonce = random_bytes::<NONCE_LEN>();
+    let ciphertext = cipher(key)
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| AppError::Config("Failed to encrypt config secret".to_string()))?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(STANDARD.encode(sealed))
+}
+
+fn open_bytes(key: &[u8; KEY_LEN], sealed: &str) -> Result<Vec<u8>> {
+    let sealed = STANDARD
+        .decode(sealed)
+        .map_err(|_| AppError::Config("Malformed encrypted config value".to_string()))?;
+    if sealed.len() < NONCE_LEN {
+        return Err(AppError::Config(
+            "Malformed encrypted config value".to_string(),
+        ));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher(key)
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::Config("Failed to decrypt config secret".to_string()))
+}
+
+/// Call `f` on each secret string field present in a serialized config.
+fn for_each_secret(config: &mut Value, mut f: impl FnMut(&mut String) -> Result<()>) -> Result<()> {
+    for path in SECRET_FIELDS {
+        let pointer = format!("/{}", path.replace('.', "/"));
+        if let Some(Value::String(value)) = config.pointer_mut(&pointer) {
+            f(value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Encrypts and decrypts secret fields with the data key
+pub struct SecretCipher {
+    data_key: [u8; KEY_LEN],
+}
+
+impl SecretCipher {
+    pub fn new(data_key: [u8; KEY_LEN]) -> Self {
+        Self { data_key }
+    }
+
+    /// Encrypt a single secret value, e.g. one stored outside the config.
+    pub fn seal_value(&self, value: &str) -> Result<String> {
+        Ok(format!(
+            "{}{}",
+            ENCRYPTED_PREFIX,
+            seal_bytes(&self.data_key, value.as_bytes())?
+        ))
+    }
+
+    /// Decrypt a value sealed by [`Self::seal_value`]; plain text is returned as is.
+    pub fn open_value(&self, value: &str) -> Result<String> {
+        match value.strip_prefix(ENCRYPTED_PREFIX) {
+            Some(sealed) => {
+                let plain = open_bytes(&self.data_key, sealed)?;
+                String::from_utf8(plain).map_err(|_| {
+                    AppError::Config("Decrypted config secret is not UTF-8".to_string())
+                })
+            }
+            None => Ok(value.to_string()),
+        }
+    }
+
+    /// Encrypt every non-empty secret field of a serialized config in place.
+    pub fn seal(&self, config: &mut Value) -> Result<()> {
+        for_each_secret(config, |value| {
+            if !value.is_empty() && !value.starts_with(ENCRYPTED_PREFIX) {
+                *value = self.seal_value(value)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Decrypt secret fields in place. Returns whether any secret was still
+    /// stored in plain text, i.e. the config needs to be re-saved.
+    pub fn open(&self, config: &mut Value) -> Result<bool> {
+        let mut plaintext_found = false;
+        for_each_secret(config, |value| {
+            if value.starts_with(ENCRYPTED_PREFIX) {
+                *value = self.open_value(value)?;
+            } else {
+                plaintext_found |= !value.is_empty();
+            }
+            Ok(())
+        })?;
+        Ok(plaintext_found)
+    }
+}
+
+fn read_key_file(path: &Path) -> Result<Option<[u8; KEY_LEN]>> {
+    let encoded = match std::fs::read_to_string(path) {
+        Ok(encoded) => encoded,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let key = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|key| <[u8; KEY_LEN]>::try_from(key).ok())
+        .ok_or_else(|| AppError::Config(format!("Invalid secret key file {}", path.display())))?;
+    Ok(Some(key))
+}
+
+/// Write a key file readable by the owner only.
+fn write_key_file(path: &Path, key: &[u8; KEY_LEN]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(STANDARD.encode(key).as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Key file written by an interrupted rotation
+fn pending_key_path(path: &Path) -> PathBuf {
+    let mut pending = path.as_os_str().to_owned();
+    pending.push(".new");
+    pending.into()
+}
+
+async fn load_wrapped_data_key(pool: &Pool<Sqlite>) -> Result<Option<String>> {
+    let row: Option<(String,)> = sqlx::query_as("SELECT value FROM config WHERE key = ?1")
+        .bind(DATA_KEY_ROW)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(|(value,)| value))
+}
+
+async fn store_wrapped_data_key(pool: &Pool<Sqlite>, wrapped: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO config (key, value, updated_at)
+        VALUES (?1, ?2, datetime('now'))
+        ON CONFLICT(key) DO UPDATE SET value = ?2, updated_at = datetime('now')
+        "#,
+    )
+    .bind(DATA_KEY_ROW)
+    .bind(wrapped)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn unwrap_data_key(key_file_key: &[u8; KEY_LEN], wrapped: &str) -> Option<[u8; KEY_LEN]> {
+    open_bytes(key_file_key, wrapped)
+        .ok()
+        .and_then(|key| <[u8; KEY_LEN]>::try_from(key).ok())
+}
+
+/// Load the data key, creating the key file and data key on first use.
+pub async fn load_cipher(pool: &Pool<Sqlite>, key_path: &Path) -> Result<SecretCipher> {
+    let wrapped = load_wrapped_data_key(pool).await?;
+
+    let Some(wrapped) = wrapped else {
+        let key_file_key = match read_key_file(key_path)? {
+            Some(key) => key,
+            None => {
+                let key = random_bytes::<KEY_LEN>();
+                write_key_file(key_path, &key)?;
+                tracing::info!("Created config secret key file {}", key_path.display());
+                key
+            }
+        };
+        let data_key = random_bytes::<KEY_LEN>();
+        store_wrapped_data_key(pool, &seal_bytes(&key_file_key, &data_key)?).await?;
+        return Ok(SecretCipher::new(data_key));
+    };
+
+    let key_file_key = read_key_file(key_path)?.ok_or_else(|| {
+        AppError::Config(format!(
+            "Secret key file {} is missing; encrypted config secrets cannot be read",
+            key_path.display()
+        ))
+    })?;
+    if let Some(data_key) = unwrap_data_key(&key_file_key, &wrapped) {
+        return Ok(SecretCipher::new(data_key));
+    }
+
+    // A rotation may have re-wrapped the data key but not yet replaced the key file
+    let pending = pending_key_path(key_path);
+    if let Some(pending_key) = read_key_file(&pending)? {
+        if let Some(data_key) = unwrap_data_key(&pending_key, &wrapped) {
+            std::fs::rename(&pending, key_path)?;
+            tracing::info!("Completed interrupted secret key rotation");
+            return Ok(SecretCipher::new(data_key));
+        }
+    }
+
+    Err(AppError::Config(format!(
+        "Secret key file {} does not match the database",
+        key_path.display()
+    )))
+}
+
+/// Replace the key file with a new random key and re-wrap the data key.
+/// Encrypted fields are untouched.
+pub async fn rotate_key_file(pool: &Pool<Sqlite>, key_path: &Path) -> Result<()> {
+    let cipher = load_cipher(pool, key_path).await?;
+    let new_key = random_bytes::<KEY_LEN>();
+    let pending = pending_key_path(key_path);
+
+    // New key first, then the database, then the swap: an interruption at any
+    // point leaves a key file that can unwrap the stored data key.
+    write_key_file(&pending, &new_key)?;
+    store_wrapped_data_key(pool, &seal_bytes(&new_key, &cipher.data_key)?).await?;
+    std::fs::rename(&pending, key_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn sample() -> Value {
+        json!({
+            "rtsp": { "password": "rtsp-pass", "port": 8554 },
+            "stream": { "turn_password": null },
+            "rustdesk": { "device_password": "", "private_key": "pk" },
+            "extensions": { "gostc": { "addr": "x" }, "easytier": { "network_secret": "s3" } }
+        })
+    }
+
+    #[test]
+    fn test_seal_and_open_round_trip() {
+        let cipher = SecretCipher::new(random_bytes());
+        let mut config = sample();
+        cipher.seal(&mut config).unwrap();
+
+        let sealed = config.to_string();
+        assert!(!sealed.contains("rtsp-pass"));
+        assert!(!sealed.contains("\"pk\""));
+        assert!(config["rtsp"]["password"]
+            .as_str()
+            .unwrap()
+            .starts_with(ENCRYPTED_PREFIX));
+        assert_eq!(config["rtsp"]["port"], 8554);
+        assert_eq!(config["rustdesk"]["device_password"], "");
+        assert!(config["stream"]["turn_password"].is_null());
+
+        assert!(!cipher.open(&mut config).unwrap());
+        assert_eq!(config, sample());
+
+        // Plain-text values from older installs are accepted and flagged
+        let mut legacy = sample();
+        assert!(cipher.open(&mut legacy).unwrap());
+        assert_eq!(legacy, sample());
+
+        let mut other = sample();
+        cipher.seal(&mut other).unwrap();
+        assert!(SecretCipher::new(random_bytes()).open(&mut other).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_key_file_and_rotation() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let pool = db.clone_pool();
+        let key_path = dir.path().join("config.key");
+
+        let cipher = load_cipher(&pool, &key_path).await.unwrap();
+        assert!(key_path.exists());
+        let mut config = sample();
+        cipher.seal(&mut config).unwrap();
+
+        let old_key = std::fs::read(&key_path).unwrap();
+        rotate_key_file(&pool, &key_path).await.unwrap();
+        assert_ne!(std::fs::read(&key_path).unwrap(), old_key);
+        assert!(!pending_key_path(&key_path).exists());
+
+        // Same data key, so existing ciphertexts still open
+        let reloaded = load_cipher(&pool, &key_path).await.unwrap();
+        reloaded.open(&mut config).unwrap();
+        assert_eq!(config, sample());
+
+        std::fs::write(&key_path, STANDARD.encode(random_bytes::<KEY_LEN>())).unwrap();
+        assert!(load_cipher(&pool, &key_path).await.is_err());
+        std::fs::remove_file(&key_path).unwrap();
+        assert!(load_cipher(&pool, &key_path).await.is_err());
+    }
+}
@@ -1,7 +1,9 @@
+mod crypto;
 mod persistence;
 mod schema;
 mod store;
 
-pub use persistence::ConfigChange;
+pub use crypto::rotate_key_file;
+pub use persistence::{ConfigChange, ConfigFieldChange};
 pub use schema::*;
 pub use store::ConfigStore;
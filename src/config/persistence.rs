@@ -1,5 +1,107 @@
+use serde::Serialize;
+use serde_json::Value;
+
 /// Configuration change event
 #[derive(Debug, Clone)]
 pub struct ConfigChange {
     pub key: String,
 }
+
+/// One leaf field changed by [`super::ConfigStore::update`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigFieldChange {
+    /// Dotted path, e.g. `video.fps`
+    pub path: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+const REDACTED: &str = "***";
+
+/// Fields holding credentials or key material; their values never appear in a diff.
+fn is_secret_field(name: &str) -> bool {
+    name.contains("password")
+        || name.contains("secret")
+        || name.contains("private_key")
+        || name == "key"
+}
+
+/// Field-level difference between two serialized configurations.
+pub fn diff_config_values(old: &Value, new: &Value) -> Vec<ConfigFieldChange> {
+    let mut changes = Vec::new();
+    diff_into("", old, new, &mut changes);
+    changes
+}
+
+fn diff_into(path: &str, old: &Value, new: &Value, out: &mut Vec<ConfigFieldChange>) {
+    if old == new {
+        return;
+    }
+
+    if let (Value::Object(old_map), Value::Object(new_map)) = (old, new) {
+        let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let child = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            diff_into(
+                &child,
+                old_map.get(key).unwrap_or(&Value::Null),
+                new_map.get(key).unwrap_or(&Value::Null),
+                out,
+            );
+        }
+        return;
+    }
+
+    let field = path.rsplit('.').next().unwrap_or(path);
+    let (old, new) = if is_secret_field(field) {
+        (Value::from(REDACTED), Value::from(REDACTED))
+    } else {
+        (old.clone(), new.clone())
+    };
+    out.push(ConfigFieldChange {
+        path: path.to_string(),
+        old,
+        new,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_reports_changed_leaves_and_redacts_secrets() {
+        let old = json!({
+            "video": {"fps": 30, "device": "/dev/video0"},
+            "stream": {"turn_password": "a"},
+        });
+        let new = json!({
+            "video": {"fps": 60, "device": "/dev/video0"},
+            "stream": {"turn_password": "b"},
+        });
+
+        let changes = diff_config_values(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                ConfigFieldChange {
+                    path: "stream.turn_password".to_string(),
+                    old: json!("***"),
+                    new: json!("***"),
+                },
+                ConfigFieldChange {
+                    path: "video.fps".to_string(),
+                    old: json!(30),
+                    new: json!(60),
+                },
+            ]
+        );
+    }
+}
@@ -5,6 +5,7 @@ use typeshare::typeshare;
 // These are simple data types defined in their respective modules;
 // keeping the re-export here is acceptable since they flow inward.
 pub use crate::extensions::ExtensionsConfig;
+pub use crate::hid::presets::KeyPreset;
 pub use crate::rustdesk::config::RustDeskConfig;
 
 /// Bitrate preset for video encoding
@@ -110,6 +111,35 @@ pub struct AppConfig {
     pub rustdesk: RustDeskConfig,
     /// RTSP streaming settings
     pub rtsp: RtspConfig,
+    /// IP allow/deny lists for the web, RTSP and RustDesk listeners
+    pub access: AccessConfig,
+}
+
+/// Allow/deny lists of CIDR blocks or single addresses
+#[typeshare]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AccessRules {
+    /// When non-empty, only matching addresses are accepted
+    pub allow: Vec<String>,
+    /// Matching addresses are always rejected
+    pub deny: Vec<String>,
+}
+
+/// Network access control. A peer must pass both the global rules and the
+/// rules of the service it connects to; deny entries win over allow entries.
+#[typeshare]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AccessConfig {
+    /// Rules applied to every listener
+    pub global: AccessRules,
+    /// Web UI and API (HTTP and HTTPS)
+    pub web: AccessRules,
+    /// RTSP clients
+    pub rtsp: AccessRules,
+    /// RustDesk direct TCP connections
+    pub rustdesk: AccessRules,
 }
 
 /// Authentication configuration
@@ -119,12 +149,31 @@ pub struct AppConfig {
 pub struct AuthConfig {
     /// Session timeout in seconds
     pub session_timeout_secs: u32,
-    /// Allow multiple concurrent web sessions (single-user mode)
+    /// Allow multiple concurrent web sessions per user
     pub single_user_allow_multiple_sessions: bool,
-    /// Enable 2FA
+    /// Keep web sessions in the database so they survive restarts (applied on restart)
+    pub persist_sessions: bool,
+    /// Legacy global 2FA switch; moved to the first administrator at startup
     pub totp_enabled: bool,
-    /// TOTP secret (encrypted)
+    /// Legacy global TOTP secret (encrypted); two-factor state is now per user
     pub totp_secret: Option<String>,
+    /// Failed logins (per client IP or per username) before a temporary lockout; 0 disables it
+    pub login_max_failures: u32,
+    /// Lockout duration in seconds once `login_max_failures` is reached
+    pub login_lockout_secs: u32,
+    /// Base delay in seconds between failed attempts, doubled after each failure; 0 disables backoff
+    pub login_backoff_secs: u32,
+    /// Days to keep audit log entries; 0 keeps them forever
+    pub audit_retention_days: u32,
+    /// Trust a username header set by an authenticating reverse proxy
+    pub proxy_auth_enabled: bool,
+    /// Header carrying the authenticated username (e.g. X-Forwarded-User)
+    pub proxy_auth_header: String,
+    /// Proxy addresses (CIDR or single IP) whose header is trusted
+    pub proxy_auth_trusted_cidrs: Vec<String>,
+    /// Role ("viewer", "operator" or "admin") for proxy users without a local
+    /// account, which is then created on first access; None rejects them
+    pub proxy_auth_default_role: Option<String>,
 }
 
 impl Default for AuthConfig {
@@ -132,8 +181,17 @@ impl Default for AuthConfig {
         Self {
             session_timeout_secs: 3600 * 24, // 24 hours
             single_user_allow_multiple_sessions: false,
+            persist_sessions: true,
             totp_enabled: false,
             totp_secret: None,
+            login_max_failures: 5,
+            login_lockout_secs: 300,
+            login_backoff_secs: 1,
+            audit_retention_days: 90,
+            proxy_auth_enabled: false,
+            proxy_auth_header: "X-Forwarded-User".to_string(),
+            proxy_auth_trusted_cidrs: Vec::new(),
+            proxy_auth_default_role: None,
         }
     }
 }
@@ -180,6 +238,10 @@ pub enum HidBackend {
     Otg,
     /// CH9329 serial HID controller
     Ch9329,
+    /// RP2040/Arduino-class microcontroller bridged over UART
+    Mcu,
+    /// No hardware; reports are recorded for tests and demos
+    Mock,
     /// Disabled
     #[default]
     None,
@@ -272,6 +334,8 @@ pub struct OtgHidFunctions {
     pub mouse_relative: bool,
     pub mouse_absolute: bool,
     pub consumer: bool,
+    /// Power down / sleep / wake usages
+    pub system_control: bool,
 }
 
 impl OtgHidFunctions {
@@ -281,6 +345,7 @@ impl OtgHidFunctions {
             mouse_relative: true,
             mouse_absolute: true,
             consumer: true,
+            system_control: false,
         }
     }
 
@@ -290,6 +355,7 @@ impl OtgHidFunctions {
             mouse_relative: true,
             mouse_absolute: true,
             consumer: false,
+            system_control: false,
         }
     }
 
@@ -299,6 +365,7 @@ impl OtgHidFunctions {
             mouse_relative: false,
             mouse_absolute: false,
             consumer: false,
+            system_control: false,
         }
     }
 
@@ -308,20 +375,28 @@ impl OtgHidFunctions {
             mouse_relative: true,
             mouse_absolute: false,
             consumer: false,
+            system_control: false,
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        !self.keyboard && !self.mouse_relative && !self.mouse_absolute && !self.consumer
+        !self.keyboard
+            && !self.mouse_relative
+            && !self.mouse_absolute
+            && !self.consumer
+            && !self.system_control
     }
 
-    pub fn endpoint_cost(&self, keyboard_leds: bool) -> u8 {
+    pub fn endpoint_cost(&self, keyboard_leds: bool, keyboard_nkro: bool) -> u8 {
         let mut endpoints = 0;
         if self.keyboard {
             endpoints += 1;
             if keyboard_leds {
                 endpoints += 1;
             }
+            if keyboard_nkro {
+                endpoints += 1;
+            }
         }
         if self.mouse_relative {
             endpoints += 1;
@@ -332,6 +407,9 @@ impl OtgHidFunctions {
         if self.consumer {
             endpoints += 1;
         }
+        if self.system_control {
+            endpoints += 1;
+        }
         endpoints
     }
 }
@@ -365,6 +443,42 @@ impl OtgHidProfile {
     }
 }
 
+/// Mouse movement sent by the jiggler
+#[typeshare]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum JigglerMode {
+    /// One-pixel relative move and back
+    #[default]
+    Relative,
+    /// Absolute move one step off the last pointer position and back
+    Absolute,
+}
+
+/// Mouse jiggler: keeps the target awake while nobody is sending input
+#[typeshare]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct JigglerConfig {
+    pub enabled: bool,
+    pub mode: JigglerMode,
+    /// Seconds between jiggles
+    pub interval_secs: u32,
+    /// Seconds without real input before jiggling starts
+    pub idle_secs: u32,
+}
+
+impl Default for JigglerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: JigglerMode::default(),
+            interval_secs: 60,
+            idle_secs: 60,
+        }
+    }
+}
+
 /// HID configuration
 #[typeshare]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -389,12 +503,36 @@ pub struct HidConfig {
     /// Enable keyboard LED/status feedback for OTG keyboard
     #[serde(default)]
     pub otg_keyboard_leds: bool,
+    /// Add an N-key rollover interface next to the 6-key boot keyboard
+    #[serde(default)]
+    pub otg_keyboard_nkro: bool,
     /// CH9329 serial port
     pub ch9329_port: String,
     /// CH9329 baud rate
     pub ch9329_baudrate: u32,
+    /// MCU bridge serial port
+    #[serde(default = "default_mcu_port")]
+    pub mcu_port: String,
+    /// MCU bridge baud rate
+    #[serde(default = "default_mcu_baudrate")]
+    pub mcu_baudrate: u32,
     /// Mouse mode: absolute or relative
     pub mouse_absolute: bool,
+    /// Mouse jiggler
+    #[serde(default)]
+    pub jiggler: JigglerConfig,
+    /// User-defined key combos, added to the built-in presets
+    #[serde(default)]
+    #[typeshare(skip)]
+    pub key_presets: Vec<KeyPreset>,
+}
+
+fn default_mcu_port() -> String {
+    "/dev/ttyACM0".to_string()
+}
+
+fn default_mcu_baudrate() -> u32 {
+    115200
 }
 
 impl Default for HidConfig {
@@ -407,9 +545,14 @@ impl Default for HidConfig {
             otg_endpoint_budget: OtgEndpointBudget::default(),
             otg_functions: OtgHidFunctions::default(),
             otg_keyboard_leds: false,
+            otg_keyboard_nkro: false,
             ch9329_port: "/dev/ttyUSB0".to_string(),
             ch9329_baudrate: 9600,
+            mcu_port: default_mcu_port(),
+            mcu_baudrate: default_mcu_baudrate(),
             mouse_absolute: true,
+            jiggler: JigglerConfig::default(),
+            key_presets: Vec::new(),
         }
     }
 }
@@ -426,6 +569,11 @@ impl HidConfig {
         self.otg_keyboard_leds && self.effective_otg_functions().keyboard
     }
 
+    /// Whether the OTG keyboard effectively uses N-key rollover reports.
+    pub fn effective_otg_keyboard_nkro(&self) -> bool {
+        self.otg_keyboard_nkro && self.effective_otg_functions().keyboard
+    }
+
     /// Effective HID functions after applying all constraints.
     pub fn constrained_otg_functions(&self) -> OtgHidFunctions {
         self.effective_otg_functions()
@@ -434,7 +582,10 @@ impl HidConfig {
     /// Calculate required endpoint count for the current function selection.
     pub fn effective_otg_required_endpoints(&self, msd_enabled: bool) -> u8 {
         let functions = self.effective_otg_functions();
-        let mut endpoints = functions.endpoint_cost(self.effective_otg_keyboard_leds());
+        let mut endpoints = functions.endpoint_cost(
+            self.effective_otg_keyboard_leds(),
+            self.effective_otg_keyboard_nkro(),
+        );
         if msd_enabled {
             endpoints += 2;
         }
@@ -793,6 +944,13 @@ pub struct WebConfig {
     pub ssl_cert_path: Option<String>,
     /// Custom SSL key path
     pub ssl_key_path: Option<String>,
+    /// PEM bundle of CAs trusted to issue client certificates
+    pub client_ca_path: Option<String>,
+    /// Whether HTTPS clients must present a certificate signed by the client CA
+    pub client_cert_mode: ClientCertMode,
+    /// Role for accounts created on first login with a client certificate
+    /// whose common name has no local account. `None` rejects such certificates.
+    pub client_cert_default_role: Option<String>,
 }
 
 impl Default for WebConfig {
@@ -805,6 +963,23 @@ impl Default for WebConfig {
             https_enabled: false,
             ssl_cert_path: None,
             ssl_key_path: None,
+            client_ca_path: None,
+            client_cert_mode: ClientCertMode::Off,
+            client_cert_default_role: None,
         }
     }
 }
+
+/// Client certificate (mutual TLS) mode of the HTTPS listener
+#[typeshare]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientCertMode {
+    /// Client certificates are not requested
+    #[default]
+    Off,
+    /// Certificates are verified when presented; other clients log in normally
+    Optional,
+    /// The TLS handshake fails without a valid client certificate
+    Required,
+}
@@ -1,10 +1,12 @@
 use arc_swap::ArcSwap;
 use sqlx::{Pool, Sqlite};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use tokio::sync::Mutex;
 
-use super::persistence::ConfigChange;
+use super::crypto::{load_cipher, SecretCipher, ENCRYPTED_PREFIX};
+use super::persistence::{diff_config_values, ConfigChange, ConfigFieldChange};
 use super::AppConfig;
 use crate::error::{AppError, Result};
 
@@ -20,6 +22,8 @@ pub struct ConfigStore {
     change_tx: broadcast::Sender<ConfigChange>,
     /// Serializes `set` / `update` so concurrent PATCH handlers cannot clobber each other
     write_lock: Arc<Mutex<()>>,
+    /// Encrypts secret fields at rest; `None` stores them as plain text
+    cipher: Option<Arc<SecretCipher>>,
 }
 
 impl ConfigStore {
@@ -32,18 +36,26 @@ impl ConfigStore {
             cache: Arc::new(ArcSwap::from_pointee(AppConfig::default())),
             change_tx: broadcast::channel(16).0,
             write_lock: Arc::new(Mutex::new(())),
+            cipher: None,
         })
     }
 
+    /// Encrypt secret fields at rest with the key file at `key_path`, which is
+    /// created on first use (call before load())
+    pub async fn with_secret_key(mut self, key_path: &Path) -> Result<Self> {
+        self.cipher = Some(Arc::new(load_cipher(&self.pool, key_path).await?));
+        Ok(self)
+    }
+
     /// Load configuration from database (call after new())
     pub async fn load(&self) -> Result<()> {
-        let config = Self::load_config(&self.pool).await?;
+        let config = Self::load_config(&self.pool, self.cipher.as_deref()).await?;
         self.cache.store(Arc::new(config));
         Ok(())
     }
 
     /// Load configuration from database
-    async fn load_config(pool: &Pool<Sqlite>) -> Result<AppConfig> {
+    async fn load_config(pool: &Pool<Sqlite>, cipher: Option<&SecretCipher>) -> Result<AppConfig> {
         let row: Option<(String,)> =
             sqlx::query_as("SELECT value FROM config WHERE key = 'app_config'")
                 .fetch_optional(pool)
@@ -51,20 +63,42 @@ impl ConfigStore {
 
         match row {
             Some((json,)) => {
-                serde_json::from_str(&json).map_err(|e| AppError::Config(e.to_string()))
+                let mut value: serde_json::Value =
+                    serde_json::from_str(&json).map_err(|e| AppError::Config(e.to_string()))?;
+                let plaintext_found = match cipher {
+                    Some(cipher) => cipher.open(&mut value)?,
+                    None => false,
+                };
+                let config: AppConfig =
+                    serde_json::from_value(value).map_err(|e| AppError::Config(e.to_string()))?;
+
+                // Installs from before encryption at rest still hold plain-text secrets
+                if plaintext_found {
+                    Self::save_config_to_db(pool, &config, cipher).await?;
+                    tracing::info!("Encrypted plain-text secrets in stored config");
+                }
+                Ok(config)
             }
             None => {
                 // Create default config
                 let config = AppConfig::default();
-                Self::save_config_to_db(pool, &config).await?;
+                Self::save_config_to_db(pool, &config, cipher).await?;
                 Ok(config)
             }
         }
     }
 
     /// Save configuration to database
-    async fn save_config_to_db(pool: &Pool<Sqlite>, config: &AppConfig) -> Result<()> {
-        let json = serde_json::to_string(config)?;
+    async fn save_config_to_db(
+        pool: &Pool<Sqlite>,
+        config: &AppConfig,
+        cipher: Option<&SecretCipher>,
+    ) -> Result<()> {
+        let mut value = serde_json::to_value(config)?;
+        if let Some(cipher) = cipher {
+            cipher.seal(&mut value)?;
+        }
+        let json = serde_json::to_string(&value)?;
 
         sqlx::query(
             r#"
@@ -88,10 +122,30 @@ impl ConfigStore {
         self.cache.load_full()
     }
 
+    /// Encrypt a secret kept outside the config (e.g. in another table) with
+    /// the config data key. Stored as plain text when encryption is off.
+    pub fn seal_secret(&self, value: &str) -> Result<String> {
+        match &self.cipher {
+            Some(cipher) => cipher.seal_value(value),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    /// Decrypt a secret produced by [`Self::seal_secret`].
+    pub fn open_secret(&self, value: &str) -> Result<String> {
+        match &self.cipher {
+            Some(cipher) => cipher.open_value(value),
+            None if value.starts_with(ENCRYPTED_PREFIX) => Err(AppError::Config(
+                "Encrypted secret found but no secret key is loaded".to_string(),
+            )),
+            None => Ok(value.to_string()),
+        }
+    }
+
     /// Set entire configuration
     pub async fn set(&self, config: AppConfig) -> Result<()> {
         let _guard = self.write_lock.lock().await;
-        Self::save_config_to_db(&self.pool, &config).await?;
+        Self::save_config_to_db(&self.pool, &config, self.cipher.as_deref()).await?;
         self.cache.store(Arc::new(config));
 
         // Notify subscribers
@@ -106,7 +160,9 @@ impl ConfigStore {
     ///
     /// Uses read-modify-write under a mutex so concurrent `update` / `set` calls are serialized
     /// and merged correctly (each closure sees the latest stored config).
-    pub async fn update<F>(&self, f: F) -> Result<()>
+    ///
+    /// Returns the changed fields (secrets redacted) for auditing.
+    pub async fn update<F>(&self, f: F) -> Result<Vec<ConfigFieldChange>>
     where
         F: FnOnce(&mut AppConfig),
     {
@@ -116,8 +172,13 @@ impl ConfigStore {
         let mut config = (**current).clone();
         f(&mut config);
 
+        let changes = diff_config_values(
+            &serde_json::to_value(&**current)?,
+            &serde_json::to_value(&config)?,
+        );
+
         // Persist to database first
-        Self::save_config_to_db(&self.pool, &config).await?;
+        Self::save_config_to_db(&self.pool, &config, self.cipher.as_deref()).await?;
 
         // Then update cache atomically
         self.cache.store(Arc::new(config));
@@ -127,7 +188,7 @@ impl ConfigStore {
             key: "app_config".to_string(),
         });
 
-        Ok(())
+        Ok(changes)
     }
 
     /// Subscribe to configuration changes
@@ -183,4 +244,65 @@ mod tests {
         assert!(config.initialized);
         assert_eq!(config.web.http_port, 9000);
     }
+
+    #[tokio::test]
+    async fn test_secrets_encrypted_at_rest() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let key_path = dir.path().join("config.key");
+        let stored_json = || async {
+            let (json,): (String,) =
+                sqlx::query_as("SELECT value FROM config WHERE key = 'app_config'")
+                    .fetch_one(&db.clone_pool())
+                    .await
+                    .unwrap();
+            json
+        };
+
+        // An existing install with plain-text secrets
+        let legacy = ConfigStore::new(db.clone_pool()).unwrap();
+        legacy.load().await.unwrap();
+        legacy
+            .update(|c| {
+                c.rtsp.password = Some("rtsp-secret".to_string());
+                c.extensions.easytier.network_secret = "mesh-secret".to_string();
+            })
+            .await
+            .unwrap();
+        assert!(stored_json().await.contains("rtsp-secret"));
+
+        // First start with encryption migrates them
+        let store = ConfigStore::new(db.clone_pool())
+            .unwrap()
+            .with_secret_key(&key_path)
+            .await
+            .unwrap();
+        store.load().await.unwrap();
+        let json = stored_json().await;
+        assert!(!json.contains("rtsp-secret"));
+        assert!(!json.contains("mesh-secret"));
+        assert_eq!(store.get().rtsp.password.as_deref(), Some("rtsp-secret"));
+
+        store
+            .update(|c| c.stream.turn_password = Some("turn-secret".to_string()))
+            .await
+            .unwrap();
+        assert!(!stored_json().await.contains("turn-secret"));
+
+        crate::config::rotate_key_file(&db.clone_pool(), &key_path)
+            .await
+            .unwrap();
+        let reopened = ConfigStore::new(db.clone_pool())
+            .unwrap()
+            .with_secret_key(&key_path)
+            .await
+            .unwrap();
+        reopened.load().await.unwrap();
+        let config = reopened.get();
+        assert_eq!(config.stream.turn_password.as_deref(), Some("turn-secret"));
+        assert_eq!(config.extensions.easytier.network_secret, "mesh-secret");
+    }
 }
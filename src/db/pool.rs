@@ -32,6 +32,11 @@ impl DatabasePool {
         self.create_users_table().await?;
         self.create_api_tokens_table().await?;
         self.create_wol_history_table().await?;
+        self.create_totp_recovery_codes_table().await?;
+        self.create_sessions_table().await?;
+        self.create_audit_log_table().await?;
+        self.create_share_links_table().await?;
+        self.create_hid_macros_table().await?;
         Ok(())
     }
 
@@ -57,6 +62,7 @@ impl DatabasePool {
                 id TEXT PRIMARY KEY,
                 username TEXT NOT NULL UNIQUE,
                 password_hash TEXT NOT NULL,
+                role TEXT NOT NULL DEFAULT 'admin',
                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
                 updated_at TEXT NOT NULL DEFAULT (datetime('now'))
             )
@@ -64,6 +70,34 @@ impl DatabasePool {
         )
         .execute(&self.pool)
         .await?;
+
+        // Databases from single-user builds lack `role`; their only account becomes admin.
+        self.add_column_if_missing("users", "role", "TEXT NOT NULL DEFAULT 'admin'")
+            .await?;
+        // Per-user two-factor state; the secret is encrypted with the config data key
+        self.add_column_if_missing("users", "totp_secret", "TEXT")
+            .await?;
+        self.add_column_if_missing("users", "totp_enabled", "INTEGER NOT NULL DEFAULT 0")
+            .await?;
+        self.add_column_if_missing("users", "totp_last_step", "INTEGER")
+            .await?;
+        Ok(())
+    }
+
+    async fn add_column_if_missing(&self, table: &str, column: &str, decl: &str) -> Result<()> {
+        let columns: Vec<(String,)> =
+            sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+                .fetch_all(&self.pool)
+                .await?;
+
+        if !columns.iter().any(|(name,)| name == column) {
+            sqlx::query(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, decl
+            ))
+            .execute(&self.pool)
+            .await?;
+        }
         Ok(())
     }
 
@@ -109,6 +143,129 @@ impl DatabasePool {
         Ok(())
     }
 
+    async fn create_totp_recovery_codes_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS totp_recovery_codes (
+                code_hash TEXT PRIMARY KEY,
+                user_id TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Codes from the single global TOTP setup have no owner until migrated
+        self.add_column_if_missing("totp_recovery_codes", "user_id", "TEXT")
+            .await?;
+        Ok(())
+    }
+
+    async fn create_sessions_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id_hash TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL,
+                ip TEXT,
+                user_agent TEXT,
+                data TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_sessions_user_id
+            ON sessions(user_id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_sessions_expires_at
+            ON sessions(expires_at)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn create_audit_log_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                actor TEXT,
+                ip TEXT,
+                action TEXT NOT NULL,
+                details TEXT,
+                outcome TEXT NOT NULL,
+                message TEXT
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp
+            ON audit_log(timestamp)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn create_share_links_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS share_links (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                audio INTEGER NOT NULL DEFAULT 0,
+                max_viewers INTEGER NOT NULL,
+                pin_hash TEXT,
+                created_by TEXT,
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn create_hid_macros_table(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS hid_macros (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                steps TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     pub fn pool(&self) -> &Pool<Sqlite> {
         &self.pool
     }
@@ -51,6 +51,10 @@ pub enum AppError {
 
     #[error("Service unavailable: {0}")]
     ServiceUnavailable(String),
+
+    /// Rendered as `429 Too Many Requests` with a `Retry-After` header.
+    #[error("Too many attempts, retry in {retry_after_secs}s")]
+    RateLimited { retry_after_secs: u64 },
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
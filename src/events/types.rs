@@ -37,6 +37,10 @@ pub struct HidDeviceInfo {
     pub device: Option<String>,
     pub error: Option<String>,
     pub error_code: Option<String>,
+    /// Mouse jiggler is enabled in the config
+    pub jiggler_enabled: bool,
+    /// Input has been idle long enough that the jiggler is moving the mouse
+    pub jiggler_active: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -203,6 +207,67 @@ pub enum SystemEvent {
         ttyd: TtydDeviceInfo,
     },
 
+    /// The exclusive HID control lock was taken or released
+    #[serde(rename = "hid.control_changed")]
+    HidControlChanged {
+        /// Owner now holding control; `None` once released
+        holder_id: Option<String>,
+        holder_name: Option<String>,
+        /// Taken or released over another owner's head
+        forced: bool,
+    },
+
+    /// Progress of a text typing job; `state` is running, completed,
+    /// cancelled or failed
+    #[serde(rename = "hid.type_progress")]
+    HidTypeProgress {
+        job_id: String,
+        typed: usize,
+        total: usize,
+        state: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+
+    /// Start and end of a macro playback; `state` as for `hid.type_progress`
+    #[serde(rename = "hid.macro_progress")]
+    HidMacroProgress {
+        run_id: String,
+        macro_id: String,
+        name: String,
+        played: usize,
+        total: usize,
+        state: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+
+    /// Repeated failed logins triggered a temporary lockout
+    #[serde(rename = "auth.login_locked")]
+    AuthLoginLocked {
+        username: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ip: Option<String>,
+        locked_secs: u64,
+    },
+
+    /// A connection was refused by the IP access policy
+    #[serde(rename = "access.denied")]
+    AccessDenied {
+        /// Listener that refused it: "web", "rtsp" or "rustdesk"
+        service: String,
+        ip: String,
+    },
+
+    /// The HTTPS certificate expires within `tls::EXPIRY_WARNING_DAYS`
+    #[serde(rename = "tls.cert_expiring")]
+    TlsCertExpiring {
+        subject: String,
+        /// RFC 3339 expiry time
+        not_after: String,
+        days_left: i64,
+    },
+
     #[serde(rename = "error")]
     Error { message: String },
 }
@@ -225,6 +290,12 @@ pub(crate) const EXACT_EVENT_TOPICS: &[&str] = &[
     "msd.upload_progress",
     "msd.download_progress",
     "system.device_info",
+    "hid.control_changed",
+    "hid.type_progress",
+    "hid.macro_progress",
+    "auth.login_locked",
+    "access.denied",
+    "tls.cert_expiring",
     "error",
 ];
 
@@ -247,6 +318,12 @@ impl SystemEvent {
             Self::MsdUploadProgress { .. } => "msd.upload_progress",
             Self::MsdDownloadProgress { .. } => "msd.download_progress",
             Self::DeviceInfo { .. } => "system.device_info",
+            Self::HidControlChanged { .. } => "hid.control_changed",
+            Self::HidTypeProgress { .. } => "hid.type_progress",
+            Self::HidMacroProgress { .. } => "hid.macro_progress",
+            Self::AuthLoginLocked { .. } => "auth.login_locked",
+            Self::AccessDenied { .. } => "access.denied",
+            Self::TlsCertExpiring { .. } => "tls.cert_expiring",
             Self::Error { .. } => "error",
         }
     }
@@ -383,6 +460,8 @@ mod tests {
                     device: None,
                     error: None,
                     error_code: None,
+                    jiggler_enabled: false,
+                    jiggler_active: false,
                 },
                 msd: None,
                 atx: None,
@@ -392,6 +471,41 @@ mod tests {
                     running: false,
                 },
             },
+            SystemEvent::HidControlChanged {
+                holder_id: None,
+                holder_name: None,
+                forced: false,
+            },
+            SystemEvent::HidTypeProgress {
+                job_id: String::new(),
+                typed: 0,
+                total: 0,
+                state: String::new(),
+                error: None,
+            },
+            SystemEvent::HidMacroProgress {
+                run_id: String::new(),
+                macro_id: String::new(),
+                name: String::new(),
+                played: 0,
+                total: 0,
+                state: String::new(),
+                error: None,
+            },
+            SystemEvent::AuthLoginLocked {
+                username: String::new(),
+                ip: None,
+                locked_secs: 0,
+            },
+            SystemEvent::AccessDenied {
+                service: String::new(),
+                ip: String::new(),
+            },
+            SystemEvent::TlsCertExpiring {
+                subject: String::new(),
+                not_after: String::new(),
+                days_left: 0,
+            },
             SystemEvent::Error {
                 message: String::new(),
             },
@@ -1,10 +1,10 @@
-//! `HidBackend` trait plus serde `HidBackendType` (OTG | CH9329 | disabled).
+//! `HidBackend` trait plus serde `HidBackendType` (OTG | CH9329 | MCU bridge | mock | disabled).
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tokio::sync::watch;
 
-use super::types::{ConsumerEvent, KeyboardEvent, MouseEvent};
+use super::types::{ConsumerEvent, KeyboardEvent, MouseEvent, SystemControlAction};
 use crate::error::Result;
 use crate::events::LedState;
 
@@ -12,6 +12,10 @@ fn default_ch9329_baud_rate() -> u32 {
     9600
 }
 
+fn default_mcu_baud_rate() -> u32 {
+    super::mcu::DEFAULT_BAUD_RATE
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 #[derive(Default)]
@@ -22,6 +26,13 @@ pub enum HidBackendType {
         #[serde(default = "default_ch9329_baud_rate")]
         baud_rate: u32,
     },
+    Mcu {
+        port: String,
+        #[serde(default = "default_mcu_baud_rate")]
+        baud_rate: u32,
+    },
+    /// Records reports instead of sending them; see `hid::mock`
+    Mock,
     #[default]
     None,
 }
@@ -31,6 +42,8 @@ impl HidBackendType {
         match self {
             Self::Otg => "otg",
             Self::Ch9329 { .. } => "ch9329",
+            Self::Mcu { .. } => "mcu",
+            Self::Mock => "mock",
             Self::None => "none",
         }
     }
@@ -63,6 +76,12 @@ pub trait HidBackend: Send + Sync {
         ))
     }
 
+    async fn send_system_control(&self, _action: SystemControlAction) -> Result<()> {
+        Err(crate::error::AppError::BadRequest(
+            "System control not supported by this backend".to_string(),
+        ))
+    }
+
     async fn reset(&self) -> Result<()>;
 
     async fn shutdown(&self) -> Result<()>;
@@ -0,0 +1,242 @@
+//! Batches of HID events posted over REST and run in order, with optional
+//! pauses and press/release/tap shorthands for keys and mouse buttons.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::keyboard::CanonicalKey;
+use super::macros::MacroEvent;
+use super::types::{
+    ConsumerEvent, KeyEventType, KeyboardEvent, KeyboardModifiers, MouseButton, MouseEvent,
+};
+use crate::error::{AppError, Result};
+
+pub const MAX_BATCH_STEPS: usize = 1000;
+const MAX_STEP_DELAY_MS: u64 = 60_000;
+/// The request stays open while the batch runs, so keep it bounded
+pub const MAX_BATCH_DURATION_MS: u64 = 300_000;
+
+/// What a shorthand step presses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BatchTarget {
+    Key { key: CanonicalKey },
+    Button { button: MouseButton },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BatchAction {
+    /// Raw events, in the same shape as macro steps
+    Keyboard {
+        event: KeyboardEvent,
+    },
+    Mouse {
+        event: MouseEvent,
+    },
+    Consumer {
+        event: ConsumerEvent,
+    },
+    Press {
+        #[serde(flatten)]
+        target: BatchTarget,
+    },
+    Release {
+        #[serde(flatten)]
+        target: BatchTarget,
+    },
+    /// Press, wait `hold_ms`, release
+    Tap {
+        #[serde(flatten)]
+        target: BatchTarget,
+        #[serde(default)]
+        hold_ms: u64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchStep {
+    /// Pause before this step, in milliseconds
+    #[serde(default)]
+    pub delay_ms: u64,
+    #[serde(flatten)]
+    pub action: BatchAction,
+}
+
+/// Event to send after waiting `delay`
+#[derive(Debug, Clone)]
+pub struct TimedEvent {
+    pub delay: Duration,
+    pub event: MacroEvent,
+}
+
+pub fn validate(steps: &[BatchStep]) -> Result<()> {
+    if steps.is_empty() {
+        return Err(AppError::BadRequest("Batch has no events".to_string()));
+    }
+    if steps.len() > MAX_BATCH_STEPS {
+        return Err(AppError::BadRequest(format!(
+            "Batch has more than {} events",
+            MAX_BATCH_STEPS
+        )));
+    }
+
+    let mut total = 0u64;
+    for step in steps {
+        let hold = match step.action {
+            BatchAction::Tap { hold_ms, .. } => hold_ms,
+            _ => 0,
+        };
+        if step.delay_ms > MAX_STEP_DELAY_MS || hold > MAX_STEP_DELAY_MS {
+            return Err(AppError::BadRequest(format!(
+                "Batch delays must be at most {} ms",
+                MAX_STEP_DELAY_MS
+            )));
+        }
+        total += step.delay_ms + hold;
+    }
+    if total > MAX_BATCH_DURATION_MS {
+        return Err(AppError::BadRequest(format!(
+            "Batch would run longer than {} ms",
+            MAX_BATCH_DURATION_MS
+        )));
+    }
+    Ok(())
+}
+
+/// Expand shorthands into timed events. Key shorthands carry the modifiers
+/// held at that point, so `press ControlLeft` + `tap KeyC` sends Ctrl+C.
+pub fn plan(steps: &[BatchStep]) -> Result<Vec<TimedEvent>> {
+    validate(steps)?;
+
+    let mut held_modifiers = 0u8;
+    let mut key_event = |key: CanonicalKey, event_type: KeyEventType| {
+        if let Some(bit) = key.modifier_bit() {
+            match event_type {
+                KeyEventType::Down => held_modifiers |= bit,
+                KeyEventType::Up => held_modifiers &= !bit,
+            }
+        }
+        MacroEvent::Keyboard(KeyboardEvent {
+            event_type,
+            key,
+            modifiers: KeyboardModifiers::from_hid_byte(held_modifiers),
+        })
+    };
+    let mut target_event = |target: &BatchTarget, event_type: KeyEventType| match *target {
+        BatchTarget::Key { key } => key_event(key, event_type),
+        BatchTarget::Button { button } => MacroEvent::Mouse(match event_type {
+            KeyEventType::Down => MouseEvent::button_down(button),
+            KeyEventType::Up => MouseEvent::button_up(button),
+        }),
+    };
+
+    let mut events = Vec::with_capacity(steps.len());
+    for step in steps {
+        let delay = Duration::from_millis(step.delay_ms);
+        match &step.action {
+            BatchAction::Keyboard { event } => events.push(TimedEvent {
+                delay,
+                event: MacroEvent::Keyboard(event.clone()),
+            }),
+            BatchAction::Mouse { event } => events.push(TimedEvent {
+                delay,
+                event: MacroEvent::Mouse(event.clone()),
+            }),
+            BatchAction::Consumer { event } => events.push(TimedEvent {
+                delay,
+                event: MacroEvent::Consumer(event.clone()),
+            }),
+            BatchAction::Press { target } => events.push(TimedEvent {
+                delay,
+                event: target_event(target, KeyEventType::Down),
+            }),
+            BatchAction::Release { target } => events.push(TimedEvent {
+                delay,
+                event: target_event(target, KeyEventType::Up),
+            }),
+            BatchAction::Tap { target, hold_ms } => {
+                events.push(TimedEvent {
+                    delay,
+                    event: target_event(target, KeyEventType::Down),
+                });
+                events.push(TimedEvent {
+                    delay: Duration::from_millis(*hold_ms),
+                    event: target_event(target, KeyEventType::Up),
+                });
+            }
+        }
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::MouseEventType;
+
+    #[test]
+    fn test_plan_shorthands() {
+        let steps: Vec<BatchStep> = serde_json::from_value(serde_json::json!([
+            { "kind": "press", "key": "ControlLeft" },
+            { "kind": "tap", "key": "KeyC", "hold_ms": 30, "delay_ms": 10 },
+            { "kind": "release", "key": "ControlLeft" },
+            { "kind": "tap", "button": "left" },
+            { "kind": "consumer", "event": { "usage": 233 } },
+        ]))
+        .unwrap();
+
+        let plan = plan(&steps).unwrap();
+        assert_eq!(plan.len(), 7);
+
+        let MacroEvent::Keyboard(ref copy) = plan[1].event else {
+            panic!("expected a key event");
+        };
+        assert_eq!(copy.key, CanonicalKey::KeyC);
+        assert!(copy.modifiers.left_ctrl);
+        assert_eq!(plan[1].delay, Duration::from_millis(10));
+        assert_eq!(plan[2].delay, Duration::from_millis(30));
+
+        let MacroEvent::Keyboard(ref released) = plan[3].event else {
+            panic!("expected a key event");
+        };
+        assert_eq!(released.event_type, KeyEventType::Up);
+        assert!(!released.modifiers.left_ctrl);
+
+        let MacroEvent::Mouse(ref click) = plan[4].event else {
+            panic!("expected a mouse event");
+        };
+        assert_eq!(click.event_type, MouseEventType::Down);
+        assert_eq!(click.button, Some(MouseButton::Left));
+    }
+
+    #[test]
+    fn test_raw_events_match_macro_steps() {
+        let steps: Vec<BatchStep> = serde_json::from_value(serde_json::json!([
+            { "kind": "mouse", "delay_ms": 5, "event": { "type": "move", "x": 3, "y": -2 } },
+        ]))
+        .unwrap();
+        let plan = plan(&steps).unwrap();
+        let MacroEvent::Mouse(ref event) = plan[0].event else {
+            panic!("expected a mouse event");
+        };
+        assert_eq!((event.x, event.y), (3, -2));
+    }
+
+    #[test]
+    fn test_validate_limits() {
+        assert!(plan(&[]).is_err());
+
+        let long = BatchStep {
+            delay_ms: MAX_STEP_DELAY_MS,
+            action: BatchAction::Tap {
+                target: BatchTarget::Key {
+                    key: CanonicalKey::KeyA,
+                },
+                hold_ms: 0,
+            },
+        };
+        let steps = vec![long; (MAX_BATCH_DURATION_MS / MAX_STEP_DELAY_MS) as usize + 1];
+        assert!(validate(&steps).is_err());
+    }
+}
@@ -11,11 +11,11 @@
 use async_trait::async_trait;
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8, Ordering};
 use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, Instant};
-use tokio::sync::watch;
+use tokio::sync::{oneshot, watch};
 use tracing::{info, trace, warn};
 
 use super::backend::{HidBackend, HidBackendRuntimeSnapshot};
@@ -41,6 +41,18 @@ const RECONNECT_DELAY_MS: u64 = 2000;
 
 const INIT_WAIT_MS: u64 = 3000;
 
+/// Time the chip needs after RESET before it answers on the serial port again
+const RESTART_WAIT_MS: u64 = 1000;
+
+/// Baud rates accepted for the chip's serial port
+pub const SUPPORTED_BAUD_RATES: [u32; 5] = [9600, 19200, 38400, 57600, 115200];
+
+/// Size of the GET_PARA_CFG / SET_PARA_CFG parameter block
+pub const PARA_CFG_LEN: usize = 50;
+
+/// Longest custom USB string descriptor the chip stores
+pub const MAX_USB_STRING_LEN: usize = 23;
+
 pub mod cmd {
     pub const GET_INFO: u8 = 0x01;
     pub const SEND_KB_GENERAL_DATA: u8 = 0x02;
@@ -48,6 +60,10 @@ pub mod cmd {
     pub const SEND_MS_ABS_DATA: u8 = 0x04;
     pub const SEND_MS_REL_DATA: u8 = 0x05;
     pub const SEND_MY_HID_DATA: u8 = 0x06;
+    pub const GET_PARA_CFG: u8 = 0x08;
+    pub const SET_PARA_CFG: u8 = 0x09;
+    pub const GET_USB_STRING: u8 = 0x0A;
+    pub const SET_USB_STRING: u8 = 0x0B;
     pub const SET_DEFAULT_CFG: u8 = 0x0C;
     pub const RESET: u8 = 0x0F;
 }
@@ -128,6 +144,230 @@ impl ChipInfo {
     }
 }
 
+/// Which USB functions the chip exposes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkMode {
+    /// Keyboard, mouse and custom HID composite device
+    Composite,
+    Keyboard,
+    Mouse,
+    CustomHid,
+}
+
+impl WorkMode {
+    fn from_byte(byte: u8) -> Self {
+        match byte & 0x03 {
+            0 => Self::Composite,
+            1 => Self::Keyboard,
+            2 => Self::Mouse,
+            _ => Self::CustomHid,
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Composite => 0,
+            Self::Keyboard => 1,
+            Self::Mouse => 2,
+            Self::CustomHid => 3,
+        }
+    }
+}
+
+/// How the chip interprets serial data; only `Protocol` can be driven by this backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerialMode {
+    Protocol,
+    Ascii,
+    Transparent,
+}
+
+impl SerialMode {
+    fn from_byte(byte: u8) -> Self {
+        match byte & 0x03 {
+            0 => Self::Protocol,
+            1 => Self::Ascii,
+            _ => Self::Transparent,
+        }
+    }
+}
+
+/// USB string descriptor selector for GET_USB_STRING / SET_USB_STRING
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsbStringKind {
+    Manufacturer,
+    Product,
+    SerialNumber,
+}
+
+impl UsbStringKind {
+    pub const ALL: [Self; 3] = [Self::Manufacturer, Self::Product, Self::SerialNumber];
+
+    fn code(self) -> u8 {
+        match self {
+            Self::Manufacturer => 0x00,
+            Self::Product => 0x01,
+            Self::SerialNumber => 0x02,
+        }
+    }
+
+    /// Bit in the PARA_CFG string enable byte
+    fn enable_bit(self) -> u8 {
+        match self {
+            Self::Manufacturer => 0x04,
+            Self::Product => 0x02,
+            Self::SerialNumber => 0x01,
+        }
+    }
+}
+
+/// Chip parameters from GET_PARA_CFG. Bytes not modelled here are kept as
+/// read, so writing the block back leaves them unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParaConfig {
+    pub work_mode: WorkMode,
+    /// The work mode comes from the MODE pins and `work_mode` is ignored
+    pub work_mode_from_pins: bool,
+    pub serial_mode: SerialMode,
+    pub address: u8,
+    pub baud_rate: u32,
+    pub packet_interval_ms: u16,
+    pub vid: u16,
+    pub pid: u16,
+    pub custom_manufacturer: bool,
+    pub custom_product: bool,
+    pub custom_serial_number: bool,
+    #[serde(skip)]
+    raw: [u8; PARA_CFG_LEN],
+}
+
+impl ParaConfig {
+    /// Multi-byte fields are big-endian except VID and PID
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let raw: [u8; PARA_CFG_LEN] = data.get(..PARA_CFG_LEN)?.try_into().ok()?;
+        let strings = raw[36];
+        Some(Self {
+            work_mode: WorkMode::from_byte(raw[0]),
+            work_mode_from_pins: raw[0] & 0x80 != 0,
+            serial_mode: SerialMode::from_byte(raw[1]),
+            address: raw[2],
+            baud_rate: u32::from_be_bytes([raw[3], raw[4], raw[5], raw[6]]),
+            packet_interval_ms: u16::from_be_bytes([raw[9], raw[10]]),
+            vid: u16::from_le_bytes([raw[11], raw[12]]),
+            pid: u16::from_le_bytes([raw[13], raw[14]]),
+            custom_manufacturer: strings & UsbStringKind::Manufacturer.enable_bit() != 0,
+            custom_product: strings & UsbStringKind::Product.enable_bit() != 0,
+            custom_serial_number: strings & UsbStringKind::SerialNumber.enable_bit() != 0,
+            raw,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; PARA_CFG_LEN] {
+        let mut raw = self.raw;
+        raw[0] = (raw[0] & 0x80) | self.work_mode.to_byte();
+        raw[2] = self.address;
+        raw[3..7].copy_from_slice(&self.baud_rate.to_be_bytes());
+        raw[9..11].copy_from_slice(&self.packet_interval_ms.to_be_bytes());
+        raw[11..13].copy_from_slice(&self.vid.to_le_bytes());
+        raw[13..15].copy_from_slice(&self.pid.to_le_bytes());
+
+        let mut strings = 0;
+        for (kind, enabled) in [
+            (UsbStringKind::Manufacturer, self.custom_manufacturer),
+            (UsbStringKind::Product, self.custom_product),
+            (UsbStringKind::SerialNumber, self.custom_serial_number),
+        ] {
+            if enabled {
+                strings |= kind.enable_bit();
+            }
+        }
+        if strings != 0 {
+            // Bit 7 switches the chip from its built-in strings to the custom ones
+            strings |= 0x80;
+        }
+        raw[36] = strings;
+        raw
+    }
+
+    fn set_custom_string(&mut self, kind: UsbStringKind, enabled: bool) {
+        match kind {
+            UsbStringKind::Manufacturer => self.custom_manufacturer = enabled,
+            UsbStringKind::Product => self.custom_product = enabled,
+            UsbStringKind::SerialNumber => self.custom_serial_number = enabled,
+        }
+    }
+}
+
+/// Chip parameters plus the stored custom USB strings
+#[derive(Debug, Clone, Serialize)]
+pub struct Ch9329Settings {
+    #[serde(flatten)]
+    pub config: ParaConfig,
+    pub manufacturer: String,
+    pub product: String,
+    pub serial_number: String,
+}
+
+/// Edits applied on top of the chip's current parameters. The serial mode and
+/// address are not editable since the backend could no longer talk to the
+/// chip; the baud rate changes through [`Ch9329Backend::switch_baud_rate`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Ch9329SettingsUpdate {
+    pub work_mode: Option<WorkMode>,
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    /// An empty string switches back to the chip's built-in string
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+}
+
+impl Ch9329SettingsUpdate {
+    fn strings(&self) -> [(UsbStringKind, Option<&str>); 3] {
+        [
+            (UsbStringKind::Manufacturer, self.manufacturer.as_deref()),
+            (UsbStringKind::Product, self.product.as_deref()),
+            (UsbStringKind::SerialNumber, self.serial_number.as_deref()),
+        ]
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for (_, value) in self.strings() {
+            let Some(value) = value else {
+                continue;
+            };
+            if value.len() > MAX_USB_STRING_LEN
+                || !value.chars().all(|c| c.is_ascii_graphic() || c == ' ')
+            {
+                return Err(AppError::BadRequest(format!(
+                    "CH9329 USB strings must be at most {} printable ASCII characters",
+                    MAX_USB_STRING_LEN
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&self, config: &mut ParaConfig) {
+        if let Some(work_mode) = self.work_mode {
+            config.work_mode = work_mode;
+        }
+        if let Some(vid) = self.vid {
+            config.vid = vid;
+        }
+        if let Some(pid) = self.pid {
+            config.pid = pid;
+        }
+        for (kind, value) in self.strings() {
+            if let Some(value) = value {
+                config.set_custom_string(kind, !value.is_empty());
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct LedStatus {
     pub num_lock: bool,
@@ -280,14 +520,29 @@ impl Ch9329RuntimeState {
 }
 
 enum WorkerCommand {
-    Packet { cmd: u8, data: Vec<u8> },
+    Packet {
+        cmd: u8,
+        data: Vec<u8>,
+    },
+    /// Packet whose response is handed back to the caller
+    Request {
+        cmd: u8,
+        data: Vec<u8>,
+        reply: oneshot::Sender<Result<Response>>,
+    },
+    /// Reset the chip so written parameters take effect, then reconnect at `baud_rate`
+    Restart {
+        baud_rate: u32,
+        reply: oneshot::Sender<Result<ChipInfo>>,
+    },
     ResetState,
     Shutdown,
 }
 
 pub struct Ch9329Backend {
     port_path: String,
-    baud_rate: u32,
+    /// Rate the worker currently talks at; changes after a baud rate switch
+    baud_rate: Arc<AtomicU32>,
     worker_tx: Mutex<Option<mpsc::Sender<WorkerCommand>>>,
     worker_handle: Mutex<Option<thread::JoinHandle<()>>>,
     keyboard_state: Mutex<KeyboardReport>,
@@ -310,7 +565,7 @@ impl Ch9329Backend {
     pub fn with_baud_rate(port_path: &str, baud_rate: u32) -> Result<Self> {
         Ok(Self {
             port_path: port_path.to_string(),
-            baud_rate,
+            baud_rate: Arc::new(AtomicU32::new(baud_rate)),
             worker_tx: Mutex::new(None),
             worker_handle: Mutex::new(None),
             keyboard_state: Mutex::new(KeyboardReport::default()),
@@ -532,6 +787,16 @@ impl Ch9329Backend {
             .ok_or_else(|| Self::backend_error("Failed to parse chip info", "invalid_response"))
     }
 
+    fn connect(
+        port_path: &str,
+        baud_rate: u32,
+        address: u8,
+    ) -> Result<(Box<dyn serialport::SerialPort>, ChipInfo)> {
+        let mut port = Self::open_port(port_path, baud_rate)?;
+        let info = Self::query_chip_info_on_port(port.as_mut(), address)?;
+        Ok((port, info))
+    }
+
     fn update_chip_info_cache(
         chip_info: &Arc<RwLock<Option<ChipInfo>>>,
         led_status: &Arc<RwLock<LedStatus>>,
@@ -572,6 +837,150 @@ impl Ch9329Backend {
         })
     }
 
+    /// Rate the chip's serial port currently runs at
+    pub fn baud_rate(&self) -> u32 {
+        self.baud_rate.load(Ordering::Relaxed)
+    }
+
+    async fn request(&self, cmd: u8, data: Vec<u8>) -> Result<Response> {
+        let (reply, response) = oneshot::channel();
+        self.enqueue_command(WorkerCommand::Request { cmd, data, reply })?;
+        let response = response.await.map_err(|_| {
+            Self::backend_error("CH9329 worker dropped the request", "no_response")
+        })??;
+        if response.is_error {
+            let reason = response
+                .error_code
+                .map(|e| format!("CH9329 error response: {}", e))
+                .unwrap_or_else(|| "CH9329 returned error response".to_string());
+            return Err(Self::backend_error(reason, "protocol_error"));
+        }
+        Ok(response)
+    }
+
+    /// Request for a SET command, which answers with a single status byte
+    async fn request_status(&self, cmd: u8, data: Vec<u8>) -> Result<()> {
+        let response = self.request(cmd, data).await?;
+        if !response.is_success() {
+            return Err(Self::backend_error(
+                format!(
+                    "CH9329 rejected command 0x{:02X}: {}",
+                    cmd,
+                    Ch9329Error::from(response.data[0])
+                ),
+                "protocol_error",
+            ));
+        }
+        Ok(())
+    }
+
+    async fn restart(&self, baud_rate: u32) -> Result<ChipInfo> {
+        let (reply, info) = oneshot::channel();
+        self.enqueue_command(WorkerCommand::Restart { baud_rate, reply })?;
+        info.await.map_err(|_| {
+            Self::backend_error("CH9329 worker stopped during restart", "worker_stopped")
+        })?
+    }
+
+    pub async fn para_config(&self) -> Result<ParaConfig> {
+        let response = self.request(cmd::GET_PARA_CFG, Vec::new()).await?;
+        ParaConfig::from_bytes(&response.data).ok_or_else(|| {
+            Self::backend_error("Failed to parse CH9329 parameters", "invalid_response")
+        })
+    }
+
+    async fn write_para_config(&self, config: &ParaConfig) -> Result<()> {
+        self.request_status(cmd::SET_PARA_CFG, config.to_bytes().to_vec())
+            .await
+    }
+
+    pub async fn usb_string(&self, kind: UsbStringKind) -> Result<String> {
+        let response = self.request(cmd::GET_USB_STRING, vec![kind.code()]).await?;
+        // Type, length, then the string
+        let len = *response.data.get(1).unwrap_or(&0) as usize;
+        let bytes = response.data.get(2..2 + len).ok_or_else(|| {
+            Self::backend_error("Failed to parse CH9329 USB string", "invalid_response")
+        })?;
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    async fn write_usb_string(&self, kind: UsbStringKind, value: &str) -> Result<()> {
+        let mut data = vec![kind.code(), value.len() as u8];
+        data.extend_from_slice(value.as_bytes());
+        self.request_status(cmd::SET_USB_STRING, data).await
+    }
+
+    pub async fn settings(&self) -> Result<Ch9329Settings> {
+        let config = self.para_config().await?;
+        let mut strings = Vec::with_capacity(UsbStringKind::ALL.len());
+        for kind in UsbStringKind::ALL {
+            strings.push(self.usb_string(kind).await?);
+        }
+        let [manufacturer, product, serial_number]: [String; 3] =
+            strings.try_into().expect("one string per kind");
+        Ok(Ch9329Settings {
+            config,
+            manufacturer,
+            product,
+            serial_number,
+        })
+    }
+
+    /// Read the parameters, apply `update`, write them back and reset the chip
+    /// so the host re-enumerates it with the new descriptors. The parameter
+    /// block goes first; a failed string write reports what was already saved.
+    pub async fn update_settings(&self, update: &Ch9329SettingsUpdate) -> Result<Ch9329Settings> {
+        update.validate()?;
+        let mut config = self.para_config().await?;
+        update.apply(&mut config);
+
+        self.write_para_config(&config).await?;
+        let mut written = Vec::new();
+        for (kind, value) in update.strings() {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                if let Err(e) = self.write_usb_string(kind, value).await {
+                    let mut applied = vec!["parameters".to_string()];
+                    applied.extend(written.iter().map(|kind| format!("{:?} string", kind)));
+                    return Err(Self::backend_error(
+                        format!(
+                            "Saved CH9329 {}, but writing the {:?} string failed: {}",
+                            applied.join(", "),
+                            kind,
+                            e
+                        ),
+                        "partial_update",
+                    ));
+                }
+                written.push(kind);
+            }
+        }
+        self.restart(self.baud_rate()).await?;
+        self.settings().await
+    }
+
+    /// Switch the chip's serial port to `baud_rate` in two steps: store the
+    /// rate in the chip parameters, then reset the chip and reconnect the
+    /// worker at the new rate. If the chip does not answer there the worker
+    /// goes back to the old rate.
+    pub async fn switch_baud_rate(&self, baud_rate: u32) -> Result<ChipInfo> {
+        if !SUPPORTED_BAUD_RATES.contains(&baud_rate) {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported CH9329 baud rate {}: use one of {:?}",
+                baud_rate, SUPPORTED_BAUD_RATES
+            )));
+        }
+
+        let mut config = self.para_config().await?;
+        if config.baud_rate != baud_rate {
+            config.baud_rate = baud_rate;
+            self.write_para_config(&config).await?;
+        }
+
+        let info = self.restart(baud_rate).await?;
+        info!("CH9329 serial port switched to {} baud", baud_rate);
+        Ok(info)
+    }
+
     fn worker_reconnect_loop(
         rx: &mpsc::Receiver<WorkerCommand>,
         port_path: &str,
@@ -589,10 +998,7 @@ impl Ch9329Backend {
                 Err(mpsc::RecvTimeoutError::Timeout) => {}
             }
 
-            match Self::open_port(port_path, baud_rate).and_then(|mut port| {
-                let info = Self::query_chip_info_on_port(port.as_mut(), address)?;
-                Ok((port, info))
-            }) {
+            match Self::connect(port_path, baud_rate, address) {
                 Ok((port, info)) => {
                     info!(
                         "CH9329 reconnected: {}, USB: {}",
@@ -659,9 +1065,20 @@ impl Ch9329Backend {
         Ok(())
     }
 
+    /// Wheel report on whichever mouse mode was used last
+    fn send_wheel(&self, buttons: u8, wheel: i8) -> Result<()> {
+        if self.relative_mouse_active.load(Ordering::Relaxed) {
+            self.send_mouse_relative(buttons, 0, 0, wheel)
+        } else {
+            let x = self.last_abs_x.load(Ordering::Relaxed);
+            let y = self.last_abs_y.load(Ordering::Relaxed);
+            self.send_mouse_absolute(buttons, x, y, wheel)
+        }
+    }
+
     fn worker_loop(
         port_path: String,
-        baud_rate: u32,
+        current_baud: Arc<AtomicU32>,
         address: u8,
         rx: mpsc::Receiver<WorkerCommand>,
         chip_info: Arc<RwLock<Option<ChipInfo>>>,
@@ -671,10 +1088,8 @@ impl Ch9329Backend {
     ) {
         runtime.set_initialized(true);
 
-        let mut port = match Self::open_port(&port_path, baud_rate).and_then(|mut port| {
-            let info = Self::query_chip_info_on_port(port.as_mut(), address)?;
-            Ok((port, info))
-        }) {
+        let mut baud_rate = current_baud.load(Ordering::Relaxed);
+        let mut port = match Self::connect(&port_path, baud_rate, address) {
             Ok((port, info)) => {
                 info!(
                     "CH9329 serial port opened: {} @ {} baud",
@@ -729,6 +1144,85 @@ impl Ch9329Backend {
                         runtime.set_online();
                     }
                 }
+                Ok(WorkerCommand::Request { cmd, data, reply }) => {
+                    let result = Self::xfer_packet(port.as_mut(), address, cmd, &data);
+                    match &result {
+                        Ok(_) => runtime.set_online(),
+                        Err(AppError::HidError {
+                            reason, error_code, ..
+                        }) => runtime.set_error(reason.clone(), error_code.clone()),
+                        Err(_) => {}
+                    }
+                    let _ = reply.send(result);
+                }
+                Ok(WorkerCommand::Restart {
+                    baud_rate: next,
+                    reply,
+                }) => {
+                    Self::try_best_effort_reset(port.as_mut(), address);
+                    // Ports are opened exclusively, so close before reopening
+                    drop(port);
+                    thread::sleep(Duration::from_millis(RESTART_WAIT_MS));
+
+                    // Fall back to the old rate in case the new one was not applied
+                    let previous = baud_rate;
+                    let mut connected = Self::connect(&port_path, next, address);
+                    if connected.is_ok() {
+                        baud_rate = next;
+                    } else if next != previous {
+                        connected = Self::connect(&port_path, previous, address);
+                    }
+
+                    match connected {
+                        Ok((new_port, info)) => {
+                            info!("CH9329 restarted at {} baud: {}", baud_rate, info.version);
+                            if Self::update_chip_info_cache(&chip_info, &led_status, info.clone()) {
+                                runtime.notify();
+                            }
+                            runtime.set_online();
+                            current_baud.store(baud_rate, Ordering::Relaxed);
+                            let result = if baud_rate == next {
+                                Ok(info)
+                            } else {
+                                Err(Self::backend_error(
+                                    format!(
+                                        "CH9329 did not answer at {} baud after reset, still using {} baud",
+                                        next, baud_rate
+                                    ),
+                                    "baud_switch_failed",
+                                ))
+                            };
+                            let _ = reply.send(result);
+                            port = new_port;
+                        }
+                        Err(err) => {
+                            // The chip took the new parameters, so it comes back
+                            // at the new rate once it answers again
+                            baud_rate = next;
+                            current_baud.store(baud_rate, Ordering::Relaxed);
+                            if let AppError::HidError {
+                                reason, error_code, ..
+                            } = &err
+                            {
+                                runtime.set_error(reason.clone(), error_code.clone());
+                            }
+                            let _ = reply.send(Err(err));
+
+                            let Some(new_port) = Self::worker_reconnect_loop(
+                                &rx,
+                                &port_path,
+                                baud_rate,
+                                address,
+                                &chip_info,
+                                &led_status,
+                                &runtime,
+                            ) else {
+                                break;
+                            };
+                            port = new_port;
+                        }
+                    }
+                }
                 Ok(WorkerCommand::ResetState) => {
                     let reset_sequence = [
                         (cmd::SEND_KB_GENERAL_DATA, vec![0; 8]),
@@ -821,7 +1315,7 @@ impl HidBackend for Ch9329Backend {
         let (tx, rx) = mpsc::channel();
         let (init_tx, init_rx) = mpsc::channel();
         let port_path = self.port_path.clone();
-        let baud_rate = self.baud_rate;
+        let baud_rate = self.baud_rate.clone();
         let address = self.address;
         let chip_info = self.chip_info.clone();
         let led_status = self.led_status.clone();
@@ -860,13 +1354,17 @@ impl HidBackend for Ch9329Backend {
                 self.record_error(
                     format!(
                         "CH9329 not responding on {} @ {} baud: {}",
-                        self.port_path, self.baud_rate, err
+                        self.port_path,
+                        self.baud_rate(),
+                        err
                     ),
                     "init_failed",
                 );
                 Err(AppError::Internal(format!(
                     "CH9329 not responding on {} @ {} baud: {}",
-                    self.port_path, self.baud_rate, err
+                    self.port_path,
+                    self.baud_rate(),
+                    err
                 )))
             }
             Err(_) => {
@@ -967,12 +1465,19 @@ impl HidBackend for Ch9329Backend {
                 }
             }
             MouseEventType::Scroll => {
-                if self.relative_mouse_active.load(Ordering::Relaxed) {
-                    self.send_mouse_relative(buttons, 0, 0, event.scroll)?;
-                } else {
-                    let x = self.last_abs_x.load(Ordering::Relaxed);
-                    let y = self.last_abs_y.load(Ordering::Relaxed);
-                    self.send_mouse_absolute(buttons, x, y, event.scroll)?;
+                if event.scroll != 0 {
+                    self.send_wheel(buttons, event.scroll)?;
+                }
+                if event.scroll_x != 0 {
+                    // The chip's fixed descriptor has no AC Pan; Shift + wheel
+                    // scrolls horizontally on common desktops instead.
+                    let state = self.keyboard_state.lock();
+                    let mut shifted = state.clone();
+                    shifted.modifiers |= 0x02;
+                    self.send_keyboard_report(&shifted)?;
+                    let result = self.send_wheel(buttons, -event.scroll_x);
+                    self.send_keyboard_report(&state)?;
+                    result?;
                 }
             }
         }
@@ -1155,6 +1660,74 @@ mod tests {
         assert!(!led.scroll_lock);
     }
 
+    fn sample_para_config() -> [u8; PARA_CFG_LEN] {
+        let mut raw = [0u8; PARA_CFG_LEN];
+        raw[0] = 0x80; // Work mode 0, set by the MODE pins
+        raw[3..7].copy_from_slice(&[0x00, 0x00, 0x25, 0x80]); // 9600 baud
+        raw[9..11].copy_from_slice(&[0x00, 0x03]); // 3 ms packet interval
+        raw[11..15].copy_from_slice(&[0x86, 0x1A, 0x29, 0xE1]); // 1A86:E129
+        raw[19] = 0x01; // ASCII mode auto-enter
+        raw[20] = 0x0D;
+        raw[36] = 0x84; // Custom manufacturer string
+        raw
+    }
+
+    #[test]
+    fn test_para_config_parsing() {
+        let config = ParaConfig::from_bytes(&sample_para_config()).unwrap();
+        assert_eq!(config.work_mode, WorkMode::Composite);
+        assert!(config.work_mode_from_pins);
+        assert_eq!(config.serial_mode, SerialMode::Protocol);
+        assert_eq!(config.baud_rate, 9600);
+        assert_eq!(config.packet_interval_ms, 3);
+        assert_eq!((config.vid, config.pid), (0x1A86, 0xE129));
+        assert!(config.custom_manufacturer);
+        assert!(!config.custom_product);
+        assert_eq!(config.to_bytes(), sample_para_config());
+
+        assert!(ParaConfig::from_bytes(&[0; PARA_CFG_LEN - 1]).is_none());
+    }
+
+    #[test]
+    fn test_para_config_edit_keeps_other_bytes() {
+        let mut config = ParaConfig::from_bytes(&sample_para_config()).unwrap();
+        let update = Ch9329SettingsUpdate {
+            work_mode: Some(WorkMode::Keyboard),
+            pid: Some(0x1234),
+            manufacturer: Some(String::new()),
+            product: Some("One-KVM".to_string()),
+            ..Default::default()
+        };
+        assert!(update.validate().is_ok());
+        update.apply(&mut config);
+        config.baud_rate = 115200;
+
+        let raw = config.to_bytes();
+        assert_eq!(raw[0], 0x81);
+        assert_eq!(&raw[3..7], &115200u32.to_be_bytes());
+        assert_eq!(&raw[11..15], &[0x86, 0x1A, 0x34, 0x12]);
+        assert_eq!(raw[36], 0x82);
+        assert_eq!((raw[19], raw[20]), (0x01, 0x0D));
+
+        config.set_custom_string(UsbStringKind::Product, false);
+        assert_eq!(config.to_bytes()[36], 0x00);
+    }
+
+    #[test]
+    fn test_settings_update_validation() {
+        let too_long = Ch9329SettingsUpdate {
+            product: Some("x".repeat(MAX_USB_STRING_LEN + 1)),
+            ..Default::default()
+        };
+        assert!(too_long.validate().is_err());
+
+        let non_ascii = Ch9329SettingsUpdate {
+            serial_number: Some("Schlüssel".to_string()),
+            ..Default::default()
+        };
+        assert!(non_ascii.validate().is_err());
+    }
+
     #[test]
     fn test_error_codes() {
         assert_eq!(Ch9329Error::from(0x00), Ch9329Error::Success);
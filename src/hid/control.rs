@@ -0,0 +1,316 @@
+//! Presence of connected input clients and the exclusive "take control" lock.
+//!
+//! While nobody holds control every client may send input. Once an owner takes
+//! control, input from all other owners is dropped until it is released.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tracing::info;
+
+use crate::error::{AppError, Result};
+use crate::events::{EventBus, SystemEvent};
+
+/// Transport an input client is connected through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputSource {
+    WebRtc,
+    WebSocket,
+    RustDesk,
+}
+
+/// Identity that takes control; one owner may have several connections
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ControlOwner {
+    /// Web session key, or `rustdesk:<peer id>`
+    pub id: String,
+    /// Display name, e.g. the username
+    pub name: String,
+}
+
+impl ControlOwner {
+    pub fn rustdesk(peer_id: &str, peer_name: &str) -> Self {
+        let name = if peer_name.is_empty() {
+            peer_id
+        } else {
+            peer_name
+        };
+        Self {
+            id: format!("rustdesk:{}", peer_id),
+            name: format!("{} (RustDesk)", name),
+        }
+    }
+}
+
+/// One connected input client
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceEntry {
+    pub client_id: String,
+    pub source: InputSource,
+    pub owner: ControlOwner,
+    #[serde(with = "time::serde::rfc3339")]
+    pub connected_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlHolder {
+    pub owner: ControlOwner,
+    #[serde(with = "time::serde::rfc3339")]
+    pub since: OffsetDateTime,
+}
+
+/// Snapshot returned by the control API
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlStatus {
+    pub holder: Option<ControlHolder>,
+    pub clients: Vec<PresenceEntry>,
+}
+
+#[derive(Default)]
+struct ControlState {
+    clients: HashMap<String, PresenceEntry>,
+    holder: Option<ControlHolder>,
+}
+
+#[derive(Default)]
+pub struct HidControl {
+    state: Mutex<ControlState>,
+    events: Mutex<Option<Arc<EventBus>>>,
+}
+
+impl HidControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_event_bus(&self, events: Arc<EventBus>) {
+        *self.events.lock() = Some(events);
+    }
+
+    fn publish(&self, holder: Option<&ControlHolder>, forced: bool) {
+        if let Some(events) = self.events.lock().as_ref() {
+            events.publish(SystemEvent::HidControlChanged {
+                holder_id: holder.map(|h| h.owner.id.clone()),
+                holder_name: holder.map(|h| h.owner.name.clone()),
+                forced,
+            });
+        }
+    }
+
+    /// Register a connected client. It stays listed until the guard is dropped.
+    pub fn connect(self: &Arc<Self>, source: InputSource, owner: ControlOwner) -> PresenceGuard {
+        let client_id = uuid::Uuid::new_v4().to_string();
+        self.state.lock().clients.insert(
+            client_id.clone(),
+            PresenceEntry {
+                client_id: client_id.clone(),
+                source,
+                owner,
+                connected_at: OffsetDateTime::now_utc(),
+            },
+        );
+        PresenceGuard {
+            control: self.clone(),
+            client_id,
+        }
+    }
+
+    /// Remove a client; control is released once its owner has no clients left.
+    fn disconnect(&self, client_id: &str) {
+        let released = {
+            let mut state = self.state.lock();
+            let Some(entry) = state.clients.remove(client_id) else {
+                return;
+            };
+            let owner_gone = !state.clients.values().any(|c| c.owner.id == entry.owner.id);
+            let held = state
+                .holder
+                .as_ref()
+                .is_some_and(|h| h.owner.id == entry.owner.id);
+            if owner_gone && held {
+                state.holder = None;
+                Some(entry.owner)
+            } else {
+                None
+            }
+        };
+
+        if let Some(owner) = released {
+            info!("HID control released: {} disconnected", owner.name);
+            self.publish(None, false);
+        }
+    }
+
+    /// Whether input from `owner_id` reaches the target
+    pub fn accepts(&self, owner_id: &str) -> bool {
+        match &self.state.lock().holder {
+            None => true,
+            Some(holder) => holder.owner.id == owner_id,
+        }
+    }
+
+    pub fn holder(&self) -> Option<ControlHolder> {
+        self.state.lock().holder.clone()
+    }
+
+    pub fn status(&self) -> ControlStatus {
+        let state = self.state.lock();
+        let mut clients: Vec<_> = state.clients.values().cloned().collect();
+        clients.sort_by_key(|c| c.connected_at);
+        ControlStatus {
+            holder: state.holder.clone(),
+            clients,
+        }
+    }
+
+    /// Take control. Fails if another owner holds it, unless `force` is set.
+    pub fn acquire(&self, owner: ControlOwner, force: bool) -> Result<ControlHolder> {
+        let (holder, forced) = {
+            let mut state = self.state.lock();
+            let forced = match &state.holder {
+                Some(current) if current.owner.id == owner.id => return Ok(current.clone()),
+                Some(current) if !force => {
+                    return Err(AppError::BadRequest(format!(
+                        "HID control is held by {}",
+                        current.owner.name
+                    )))
+                }
+                Some(_) => true,
+                None => false,
+            };
+            let holder = ControlHolder {
+                owner,
+                since: OffsetDateTime::now_utc(),
+            };
+            state.holder = Some(holder.clone());
+            (holder, forced)
+        };
+
+        info!(
+            "HID control {} by {}",
+            if forced { "force-taken" } else { "taken" },
+            holder.owner.name
+        );
+        self.publish(Some(&holder), forced);
+        Ok(holder)
+    }
+
+    /// Give up control. Only the holder may release unless `force` is set.
+    pub fn release(&self, owner_id: &str, force: bool) -> Result<()> {
+        let previous = {
+            let mut state = self.state.lock();
+            match &state.holder {
+                None => return Ok(()),
+                Some(current) if current.owner.id != owner_id && !force => {
+                    return Err(AppError::BadRequest(format!(
+                        "HID control is held by {}",
+                        current.owner.name
+                    )))
+                }
+                Some(_) => state.holder.take(),
+            }
+        };
+
+        if let Some(previous) = previous {
+            info!("HID control released by {}", previous.owner.name);
+            self.publish(None, previous.owner.id != owner_id);
+        }
+        Ok(())
+    }
+}
+
+/// Keeps a client in the presence list for as long as it is alive
+pub struct PresenceGuard {
+    control: Arc<HidControl>,
+    client_id: String,
+}
+
+impl PresenceGuard {
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+}
+
+impl Drop for PresenceGuard {
+    fn drop(&mut self) {
+        self.control.disconnect(&self.client_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner(id: &str) -> ControlOwner {
+        ControlOwner {
+            id: id.to_string(),
+            name: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lock_gates_input() {
+        let control = Arc::new(HidControl::new());
+        let _alice = control.connect(InputSource::WebRtc, owner("alice"));
+        let _bob = control.connect(InputSource::WebSocket, owner("bob"));
+        assert!(control.accepts("alice") && control.accepts("bob"));
+
+        control.acquire(owner("alice"), false).unwrap();
+        assert!(control.accepts("alice"));
+        assert!(!control.accepts("bob"));
+
+        assert!(control.acquire(owner("bob"), false).is_err());
+        assert!(control.release("bob", false).is_err());
+
+        control.acquire(owner("bob"), true).unwrap();
+        assert!(control.accepts("bob"));
+        assert!(!control.accepts("alice"));
+
+        control.release("bob", false).unwrap();
+        assert!(control.holder().is_none());
+        assert!(control.accepts("alice"));
+    }
+
+    #[tokio::test]
+    async fn test_presence_and_auto_release() {
+        let control = Arc::new(HidControl::new());
+        let events = Arc::new(EventBus::new());
+        control.set_event_bus(events.clone());
+        let mut rx = events.subscribe();
+
+        let tab = control.connect(InputSource::WebRtc, owner("alice"));
+        let ws = control.connect(InputSource::WebSocket, owner("alice"));
+        let rd = control.connect(
+            InputSource::RustDesk,
+            ControlOwner::rustdesk("123456789", ""),
+        );
+        assert_eq!(control.status().clients.len(), 3);
+        assert_eq!(control.status().clients[2].owner.id, "rustdesk:123456789");
+
+        control.acquire(owner("alice"), false).unwrap();
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            SystemEvent::HidControlChanged { holder_id: Some(ref id), forced: false, .. } if id == "alice"
+        ));
+
+        // Still connected through the WebSocket
+        drop(tab);
+        assert!(control.holder().is_some());
+
+        drop(ws);
+        assert!(control.holder().is_none());
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            SystemEvent::HidControlChanged {
+                holder_id: None,
+                ..
+            }
+        ));
+
+        drop(rd);
+        assert!(control.status().clients.is_empty());
+    }
+}
@@ -30,21 +30,47 @@
 //! - Bytes 2-3: X coordinate (i16 LE for relative, u16 LE for absolute)
 //! - Bytes 4-5: Y coordinate (i16 LE for relative, u16 LE for absolute)
 //! - Byte 6: Button (0=left, 1=middle, 2=right) or Scroll delta (i8)
+//! - Byte 7: Horizontal scroll delta (i8, Scroll only, optional)
 //!
 //! Consumer control event (type 0x03):
 //! - Bytes 1-2: Usage code (u16 LE)
+//!
+//! Type text (type 0x04):
+//! - Byte 1: Keyboard layout index (see `KeyboardLayout::ALL`)
+//! - Bytes 2-3: Delay after each key in ms (u16 LE)
+//! - Remaining bytes: UTF-8 text
+//!
+//! Cancel typing (type 0x05): no payload
+//!
+//! Play macro (type 0x06):
+//! - Bytes 1-2: Speed in percent (u16 LE, 100 = recorded timing)
+//! - Remaining bytes: Macro ID (UTF-8)
+//!
+//! Abort macro (type 0x07): no payload
+//!
+//! System control (type 0x08):
+//! - Byte 1: Action (0x01 = power down, 0x02 = sleep, 0x03 = wake up)
+//!
+//! Key preset (type 0x09):
+//! - Remaining bytes: Preset ID (UTF-8), see `GET /api/hid/presets`
 
 use tracing::warn;
 
-use super::types::ConsumerEvent;
+use super::types::{ConsumerEvent, SystemControlAction};
 use super::{
-    CanonicalKey, KeyEventType, KeyboardEvent, KeyboardModifiers, MouseButton, MouseEvent,
-    MouseEventType,
+    CanonicalKey, KeyEventType, KeyboardEvent, KeyboardLayout, KeyboardModifiers, MouseButton,
+    MouseEvent, MouseEventType, TypeTextRequest,
 };
 
 pub const MSG_KEYBOARD: u8 = 0x01;
 pub const MSG_MOUSE: u8 = 0x02;
 pub const MSG_CONSUMER: u8 = 0x03;
+pub const MSG_TYPE_TEXT: u8 = 0x04;
+pub const MSG_TYPE_CANCEL: u8 = 0x05;
+pub const MSG_MACRO_PLAY: u8 = 0x06;
+pub const MSG_MACRO_ABORT: u8 = 0x07;
+pub const MSG_SYSTEM_CONTROL: u8 = 0x08;
+pub const MSG_KEY_PRESET: u8 = 0x09;
 
 pub const KB_EVENT_DOWN: u8 = 0x00;
 pub const KB_EVENT_UP: u8 = 0x01;
@@ -60,6 +86,12 @@ pub enum HidChannelEvent {
     Keyboard(KeyboardEvent),
     Mouse(MouseEvent),
     Consumer(ConsumerEvent),
+    TypeText(TypeTextRequest),
+    CancelType,
+    PlayMacro { macro_id: String, speed: f64 },
+    AbortMacro,
+    SystemControl(SystemControlAction),
+    RunPreset { preset_id: String },
 }
 
 pub fn parse_hid_message(data: &[u8]) -> Option<HidChannelEvent> {
@@ -74,6 +106,12 @@ pub fn parse_hid_message(data: &[u8]) -> Option<HidChannelEvent> {
         MSG_KEYBOARD => parse_keyboard_message(&data[1..]),
         MSG_MOUSE => parse_mouse_message(&data[1..]),
         MSG_CONSUMER => parse_consumer_message(&data[1..]),
+        MSG_TYPE_TEXT => parse_type_text_message(&data[1..]),
+        MSG_TYPE_CANCEL => Some(HidChannelEvent::CancelType),
+        MSG_MACRO_PLAY => parse_macro_play_message(&data[1..]),
+        MSG_MACRO_ABORT => Some(HidChannelEvent::AbortMacro),
+        MSG_SYSTEM_CONTROL => parse_system_control_message(&data[1..]),
+        MSG_KEY_PRESET => parse_key_preset_message(&data[1..]),
         _ => {
             warn!("Unknown HID message type: 0x{:02X}", msg_type);
             None
@@ -159,6 +197,11 @@ fn parse_mouse_message(data: &[u8]) -> Option<HidChannelEvent> {
         MouseEventType::Scroll => (None, data[5] as i8),
         _ => (None, 0i8),
     };
+    // Older clients send no horizontal delta
+    let scroll_x = match event_type {
+        MouseEventType::Scroll => data.get(6).map_or(0, |&b| b as i8),
+        _ => 0,
+    };
 
     Some(HidChannelEvent::Mouse(MouseEvent {
         event_type,
@@ -166,6 +209,7 @@ fn parse_mouse_message(data: &[u8]) -> Option<HidChannelEvent> {
         y,
         button,
         scroll,
+        scroll_x,
     }))
 }
 
@@ -180,6 +224,81 @@ fn parse_consumer_message(data: &[u8]) -> Option<HidChannelEvent> {
     Some(HidChannelEvent::Consumer(ConsumerEvent { usage }))
 }
 
+fn parse_system_control_message(data: &[u8]) -> Option<HidChannelEvent> {
+    let Some(&value) = data.first() else {
+        warn!("System control message too short");
+        return None;
+    };
+    match SystemControlAction::from_report_value(value) {
+        Some(action) => Some(HidChannelEvent::SystemControl(action)),
+        None => {
+            warn!("Unknown system control action: 0x{:02X}", value);
+            None
+        }
+    }
+}
+
+fn parse_type_text_message(data: &[u8]) -> Option<HidChannelEvent> {
+    if data.len() < 3 {
+        warn!("Type text message too short: {} bytes", data.len());
+        return None;
+    }
+
+    let Some(layout) = KeyboardLayout::from_index(data[0]) else {
+        warn!("Unknown keyboard layout index: {}", data[0]);
+        return None;
+    };
+    let delay_ms = u16::from_le_bytes([data[1], data[2]]) as u64;
+    let text = match std::str::from_utf8(&data[3..]) {
+        Ok(text) => text.to_string(),
+        Err(_) => {
+            warn!("Type text message is not valid UTF-8");
+            return None;
+        }
+    };
+
+    Some(HidChannelEvent::TypeText(TypeTextRequest {
+        text,
+        layout,
+        delay_ms,
+    }))
+}
+
+fn parse_macro_play_message(data: &[u8]) -> Option<HidChannelEvent> {
+    if data.len() < 3 {
+        warn!("Macro play message too short: {} bytes", data.len());
+        return None;
+    }
+
+    let speed = u16::from_le_bytes([data[0], data[1]]) as f64 / 100.0;
+    let macro_id = match std::str::from_utf8(&data[2..]) {
+        Ok(id) => id.to_string(),
+        Err(_) => {
+            warn!("Macro ID is not valid UTF-8");
+            return None;
+        }
+    };
+
+    Some(HidChannelEvent::PlayMacro { macro_id, speed })
+}
+
+fn parse_key_preset_message(data: &[u8]) -> Option<HidChannelEvent> {
+    if data.is_empty() {
+        warn!("Key preset message has no preset ID");
+        return None;
+    }
+
+    match std::str::from_utf8(data) {
+        Ok(id) => Some(HidChannelEvent::RunPreset {
+            preset_id: id.to_string(),
+        }),
+        Err(_) => {
+            warn!("Key preset ID is not valid UTF-8");
+            None
+        }
+    }
+}
+
 pub fn encode_keyboard_event(event: &KeyboardEvent) -> Vec<u8> {
     let event_type = match event.event_type {
         KeyEventType::Down => KB_EVENT_DOWN,
@@ -231,6 +350,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_mouse_scroll() {
+        let legacy = [MSG_MOUSE, MS_EVENT_SCROLL, 0, 0, 0, 0, 0xFF];
+        let pan = [MSG_MOUSE, MS_EVENT_SCROLL, 0, 0, 0, 0, 0x00, 0x02];
+
+        match parse_hid_message(&legacy).unwrap() {
+            HidChannelEvent::Mouse(ms) => assert_eq!((ms.scroll, ms.scroll_x), (-1, 0)),
+            _ => panic!("Expected mouse event"),
+        }
+        match parse_hid_message(&pan).unwrap() {
+            HidChannelEvent::Mouse(ms) => assert_eq!((ms.scroll, ms.scroll_x), (0, 2)),
+            _ => panic!("Expected mouse event"),
+        }
+    }
+
+    #[test]
+    fn test_parse_system_control() {
+        assert!(matches!(
+            parse_hid_message(&[MSG_SYSTEM_CONTROL, 0x02]),
+            Some(HidChannelEvent::SystemControl(SystemControlAction::Sleep))
+        ));
+        assert!(parse_hid_message(&[MSG_SYSTEM_CONTROL, 0x04]).is_none());
+        assert!(parse_hid_message(&[MSG_SYSTEM_CONTROL]).is_none());
+    }
+
+    #[test]
+    fn test_parse_type_text() {
+        let mut data = vec![MSG_TYPE_TEXT, KeyboardLayout::De.index(), 0x32, 0x00];
+        data.extend_from_slice("Grüße".as_bytes());
+
+        match parse_hid_message(&data).unwrap() {
+            HidChannelEvent::TypeText(req) => {
+                assert_eq!(req.text, "Grüße");
+                assert_eq!(req.layout, KeyboardLayout::De);
+                assert_eq!(req.delay_ms, 50);
+            }
+            _ => panic!("Expected type text event"),
+        }
+
+        assert!(parse_hid_message(&[MSG_TYPE_TEXT, 0xFF, 0x00, 0x00]).is_none());
+        assert!(parse_hid_message(&[MSG_TYPE_TEXT, 0x00, 0x00, 0x00, 0xC3]).is_none());
+        assert!(matches!(
+            parse_hid_message(&[MSG_TYPE_CANCEL]),
+            Some(HidChannelEvent::CancelType)
+        ));
+    }
+
+    #[test]
+    fn test_parse_macro_play() {
+        let mut data = vec![MSG_MACRO_PLAY, 0xC8, 0x00];
+        data.extend_from_slice(b"bios");
+
+        match parse_hid_message(&data).unwrap() {
+            HidChannelEvent::PlayMacro { macro_id, speed } => {
+                assert_eq!(macro_id, "bios");
+                assert_eq!(speed, 2.0);
+            }
+            _ => panic!("Expected play macro event"),
+        }
+        assert!(parse_hid_message(&[MSG_MACRO_PLAY, 0x64, 0x00]).is_none());
+    }
+
+    #[test]
+    fn test_parse_key_preset() {
+        let mut data = vec![MSG_KEY_PRESET];
+        data.extend_from_slice(b"ctrl_alt_del");
+
+        match parse_hid_message(&data).unwrap() {
+            HidChannelEvent::RunPreset { preset_id } => assert_eq!(preset_id, "ctrl_alt_del"),
+            _ => panic!("Expected key preset event"),
+        }
+        assert!(parse_hid_message(&[MSG_KEY_PRESET]).is_none());
+        assert!(parse_hid_message(&[MSG_KEY_PRESET, 0xFF]).is_none());
+    }
+
     #[test]
     fn test_encode_keyboard() {
         let event = KeyboardEvent {
@@ -251,4 +445,28 @@ mod tests {
         let encoded = encode_keyboard_event(&event);
         assert_eq!(encoded, vec![MSG_KEYBOARD, KB_EVENT_DOWN, 0x04, 0x01]);
     }
+
+    #[test]
+    fn test_international_key_round_trip() {
+        for key in [
+            CanonicalKey::IntlRo,
+            CanonicalKey::KanaMode,
+            CanonicalKey::IntlYen,
+            CanonicalKey::Convert,
+            CanonicalKey::NonConvert,
+            CanonicalKey::Lang1,
+            CanonicalKey::Lang2,
+            CanonicalKey::Lang5,
+        ] {
+            let event = KeyboardEvent {
+                event_type: KeyEventType::Up,
+                key,
+                modifiers: KeyboardModifiers::default(),
+            };
+            match parse_hid_message(&encode_keyboard_event(&event)).unwrap() {
+                HidChannelEvent::Keyboard(parsed) => assert_eq!(parsed, event),
+                _ => panic!("Expected keyboard event"),
+            }
+        }
+    }
 }
@@ -0,0 +1,205 @@
+//! Mouse jiggler: nudges the pointer while no real input arrives, so the
+//! target does not lock its screen during long unattended jobs.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use super::types::MouseEvent;
+use crate::config::{JigglerConfig, JigglerMode};
+use crate::error::{AppError, Result};
+
+/// How often the jiggler task checks whether a jiggle is due
+pub(crate) const JIGGLER_TICK: Duration = Duration::from_secs(1);
+const MAX_JIGGLER_INTERVAL_SECS: u32 = 3600;
+const MAX_JIGGLER_IDLE_SECS: u32 = 86400;
+/// Absolute coordinates span 0..=32767; used until a real absolute move is seen
+const ABS_CENTER: i32 = 16384;
+const ABS_MAX: i32 = 32767;
+
+pub fn validate_config(config: &JigglerConfig) -> Result<()> {
+    if !(1..=MAX_JIGGLER_INTERVAL_SECS).contains(&config.interval_secs) {
+        return Err(AppError::BadRequest(format!(
+            "Jiggler interval must be 1-{} seconds",
+            MAX_JIGGLER_INTERVAL_SECS
+        )));
+    }
+    if config.idle_secs > MAX_JIGGLER_IDLE_SECS {
+        return Err(AppError::BadRequest(format!(
+            "Jiggler idle time must be at most {} seconds",
+            MAX_JIGGLER_IDLE_SECS
+        )));
+    }
+    Ok(())
+}
+
+/// Jiggler state reported in the HID device info
+#[derive(Debug, Clone, Serialize)]
+pub struct JigglerStatus {
+    #[serde(flatten)]
+    pub config: JigglerConfig,
+    /// Whether the idle threshold has passed and jiggles are being sent
+    pub active: bool,
+    /// Jiggles sent since startup
+    pub jiggles: u64,
+}
+
+struct JigglerState {
+    config: JigglerConfig,
+    last_input: Instant,
+    last_jiggle: Option<Instant>,
+    last_abs: (i32, i32),
+    active: bool,
+    jiggles: u64,
+}
+
+pub(crate) struct Jiggler {
+    state: Mutex<JigglerState>,
+}
+
+impl Jiggler {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(JigglerState {
+                config: JigglerConfig::default(),
+                last_input: Instant::now(),
+                last_jiggle: None,
+                last_abs: (ABS_CENTER, ABS_CENTER),
+                active: false,
+                jiggles: 0,
+            }),
+        }
+    }
+
+    pub fn set_config(&self, config: JigglerConfig) {
+        let mut state = self.state.lock();
+        if !config.enabled {
+            state.active = false;
+        }
+        state.config = config;
+    }
+
+    pub fn status(&self) -> JigglerStatus {
+        let state = self.state.lock();
+        JigglerStatus {
+            config: state.config.clone(),
+            active: state.active,
+            jiggles: state.jiggles,
+        }
+    }
+
+    /// Real input arrived; returns true if the jiggler just went inactive.
+    pub fn note_input(&self, event: Option<&MouseEvent>) -> bool {
+        let mut state = self.state.lock();
+        state.last_input = Instant::now();
+        if let Some(event) = event.filter(|e| e.event_type == super::MouseEventType::MoveAbs) {
+            state.last_abs = (event.x, event.y);
+        }
+        std::mem::replace(&mut state.active, false)
+    }
+
+    /// Moves to send now, if a jiggle is due. The second value tells whether
+    /// the active flag changed.
+    pub fn poll(&self, now: Instant) -> (Option<[MouseEvent; 2]>, bool) {
+        let mut state = self.state.lock();
+        let config = state.config.clone();
+        if !config.enabled {
+            return (None, false);
+        }
+
+        let idle = now.saturating_duration_since(state.last_input);
+        if idle < Duration::from_secs(config.idle_secs as u64) {
+            return (None, false);
+        }
+        let became_active = !std::mem::replace(&mut state.active, true);
+
+        let interval = Duration::from_secs(config.interval_secs as u64);
+        if state
+            .last_jiggle
+            .is_some_and(|last| now.saturating_duration_since(last) < interval)
+        {
+            return (None, became_active);
+        }
+        state.last_jiggle = Some(now);
+        state.jiggles += 1;
+
+        let moves = match config.mode {
+            JigglerMode::Relative => [MouseEvent::move_rel(1, 0), MouseEvent::move_rel(-1, 0)],
+            JigglerMode::Absolute => {
+                let (x, y) = state.last_abs;
+                let nudged = if x < ABS_MAX { x + 1 } else { x - 1 };
+                [MouseEvent::move_abs(nudged, y), MouseEvent::move_abs(x, y)]
+            }
+        };
+        (Some(moves), became_active)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::MouseEventType;
+
+    fn enabled(mode: JigglerMode) -> JigglerConfig {
+        JigglerConfig {
+            enabled: true,
+            mode,
+            interval_secs: 10,
+            idle_secs: 30,
+        }
+    }
+
+    #[test]
+    fn test_jiggles_only_while_idle() {
+        let jiggler = Jiggler::new();
+        let start = Instant::now();
+        assert!(jiggler.poll(start + Duration::from_secs(120)).0.is_none());
+
+        jiggler.set_config(enabled(JigglerMode::Relative));
+        jiggler.note_input(None);
+        let start = Instant::now();
+        assert!(jiggler.poll(start + Duration::from_secs(5)).0.is_none());
+
+        let (moves, became_active) = jiggler.poll(start + Duration::from_secs(31));
+        let moves = moves.unwrap();
+        assert!(became_active);
+        assert_eq!((moves[0].x, moves[1].x), (1, -1));
+        assert!(jiggler.status().active);
+
+        // Not again before the interval
+        let (moves, became_active) = jiggler.poll(start + Duration::from_secs(35));
+        assert!(moves.is_none() && !became_active);
+        assert!(jiggler.poll(start + Duration::from_secs(42)).0.is_some());
+        assert_eq!(jiggler.status().jiggles, 2);
+
+        assert!(jiggler.note_input(None));
+        assert!(!jiggler.status().active);
+    }
+
+    #[test]
+    fn test_absolute_wiggle_returns_to_last_position() {
+        let jiggler = Jiggler::new();
+        jiggler.set_config(JigglerConfig {
+            idle_secs: 0,
+            ..enabled(JigglerMode::Absolute)
+        });
+        jiggler.note_input(Some(&MouseEvent::move_abs(ABS_MAX, 100)));
+
+        let moves = jiggler.poll(Instant::now()).0.unwrap();
+        assert!(moves
+            .iter()
+            .all(|m| m.event_type == MouseEventType::MoveAbs));
+        assert_eq!((moves[0].x, moves[0].y), (ABS_MAX - 1, 100));
+        assert_eq!((moves[1].x, moves[1].y), (ABS_MAX, 100));
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(validate_config(&enabled(JigglerMode::Relative)).is_ok());
+        assert!(validate_config(&JigglerConfig {
+            interval_secs: 0,
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
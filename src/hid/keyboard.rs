@@ -50,6 +50,8 @@ pub enum CanonicalKey {
     BracketLeft,
     BracketRight,
     Backslash,
+    /// Non-US # and ~ (ISO keyboards)
+    IntlHash,
     Semicolon,
     Quote,
     Backquote,
@@ -101,6 +103,8 @@ pub enum CanonicalKey {
     NumpadDecimal,
     IntlBackslash,
     ContextMenu,
+    Power,
+    NumpadEqual,
     F13,
     F14,
     F15,
@@ -113,6 +117,116 @@ pub enum CanonicalKey {
     F22,
     F23,
     F24,
+    /// Execute
+    Open,
+    Help,
+    /// Menu
+    Props,
+    Select,
+    Stop,
+    Again,
+    Undo,
+    Cut,
+    Copy,
+    Paste,
+    Find,
+    AudioVolumeMute,
+    AudioVolumeUp,
+    AudioVolumeDown,
+    LockingCapsLock,
+    LockingNumLock,
+    LockingScrollLock,
+    NumpadComma,
+    /// Keypad = on AS/400 keyboards
+    NumpadEqualAs400,
+    /// International1 (JIS Ro, ABNT /?)
+    IntlRo,
+    /// International2 (JIS Katakana/Hiragana)
+    KanaMode,
+    /// International3 (JIS Yen)
+    IntlYen,
+    /// International4 (JIS Henkan)
+    Convert,
+    /// International5 (JIS Muhenkan)
+    NonConvert,
+    International6,
+    International7,
+    International8,
+    International9,
+    /// Korean Hangul/English toggle
+    Lang1,
+    /// Korean Hanja conversion
+    Lang2,
+    /// JIS Katakana
+    Lang3,
+    /// JIS Hiragana
+    Lang4,
+    /// JIS Zenkaku/Hankaku
+    Lang5,
+    Lang6,
+    Lang7,
+    Lang8,
+    Lang9,
+    AltErase,
+    SysReq,
+    Cancel,
+    Clear,
+    Prior,
+    /// Keyboard Return, distinct from Enter (0x28)
+    Return,
+    Separator,
+    Out,
+    Oper,
+    ClearAgain,
+    CrSel,
+    ExSel,
+    Numpad00,
+    Numpad000,
+    ThousandsSeparator,
+    DecimalSeparator,
+    CurrencyUnit,
+    CurrencySubunit,
+    NumpadParenLeft,
+    NumpadParenRight,
+    NumpadBraceLeft,
+    NumpadBraceRight,
+    NumpadTab,
+    NumpadBackspace,
+    NumpadA,
+    NumpadB,
+    NumpadC,
+    NumpadD,
+    NumpadE,
+    NumpadF,
+    NumpadXor,
+    NumpadCaret,
+    NumpadPercent,
+    NumpadLess,
+    NumpadGreater,
+    NumpadAmpersand,
+    NumpadDoubleAmpersand,
+    NumpadPipe,
+    NumpadDoublePipe,
+    NumpadColon,
+    NumpadHash,
+    NumpadSpace,
+    NumpadAt,
+    NumpadExclamation,
+    NumpadMemoryStore,
+    NumpadMemoryRecall,
+    NumpadMemoryClear,
+    NumpadMemoryAdd,
+    NumpadMemorySubtract,
+    NumpadMemoryMultiply,
+    NumpadMemoryDivide,
+    NumpadPlusMinus,
+    NumpadClear,
+    NumpadClearEntry,
+    NumpadBinary,
+    NumpadOctal,
+    /// Keypad Decimal (number base)
+    NumpadDecimalBase,
+    NumpadHexadecimal,
     ControlLeft,
     ShiftLeft,
     AltLeft,
@@ -172,6 +286,7 @@ impl CanonicalKey {
             Self::BracketLeft => 0x2F,
             Self::BracketRight => 0x30,
             Self::Backslash => 0x31,
+            Self::IntlHash => 0x32,
             Self::Semicolon => 0x33,
             Self::Quote => 0x34,
             Self::Backquote => 0x35,
@@ -223,6 +338,8 @@ impl CanonicalKey {
             Self::NumpadDecimal => 0x63,
             Self::IntlBackslash => 0x64,
             Self::ContextMenu => 0x65,
+            Self::Power => 0x66,
+            Self::NumpadEqual => 0x67,
             Self::F13 => 0x68,
             Self::F14 => 0x69,
             Self::F15 => 0x6A,
@@ -235,6 +352,101 @@ impl CanonicalKey {
             Self::F22 => 0x71,
             Self::F23 => 0x72,
             Self::F24 => 0x73,
+            Self::Open => 0x74,
+            Self::Help => 0x75,
+            Self::Props => 0x76,
+            Self::Select => 0x77,
+            Self::Stop => 0x78,
+            Self::Again => 0x79,
+            Self::Undo => 0x7A,
+            Self::Cut => 0x7B,
+            Self::Copy => 0x7C,
+            Self::Paste => 0x7D,
+            Self::Find => 0x7E,
+            Self::AudioVolumeMute => 0x7F,
+            Self::AudioVolumeUp => 0x80,
+            Self::AudioVolumeDown => 0x81,
+            Self::LockingCapsLock => 0x82,
+            Self::LockingNumLock => 0x83,
+            Self::LockingScrollLock => 0x84,
+            Self::NumpadComma => 0x85,
+            Self::NumpadEqualAs400 => 0x86,
+            Self::IntlRo => 0x87,
+            Self::KanaMode => 0x88,
+            Self::IntlYen => 0x89,
+            Self::Convert => 0x8A,
+            Self::NonConvert => 0x8B,
+            Self::International6 => 0x8C,
+            Self::International7 => 0x8D,
+            Self::International8 => 0x8E,
+            Self::International9 => 0x8F,
+            Self::Lang1 => 0x90,
+            Self::Lang2 => 0x91,
+            Self::Lang3 => 0x92,
+            Self::Lang4 => 0x93,
+            Self::Lang5 => 0x94,
+            Self::Lang6 => 0x95,
+            Self::Lang7 => 0x96,
+            Self::Lang8 => 0x97,
+            Self::Lang9 => 0x98,
+            Self::AltErase => 0x99,
+            Self::SysReq => 0x9A,
+            Self::Cancel => 0x9B,
+            Self::Clear => 0x9C,
+            Self::Prior => 0x9D,
+            Self::Return => 0x9E,
+            Self::Separator => 0x9F,
+            Self::Out => 0xA0,
+            Self::Oper => 0xA1,
+            Self::ClearAgain => 0xA2,
+            Self::CrSel => 0xA3,
+            Self::ExSel => 0xA4,
+            Self::Numpad00 => 0xB0,
+            Self::Numpad000 => 0xB1,
+            Self::ThousandsSeparator => 0xB2,
+            Self::DecimalSeparator => 0xB3,
+            Self::CurrencyUnit => 0xB4,
+            Self::CurrencySubunit => 0xB5,
+            Self::NumpadParenLeft => 0xB6,
+            Self::NumpadParenRight => 0xB7,
+            Self::NumpadBraceLeft => 0xB8,
+            Self::NumpadBraceRight => 0xB9,
+            Self::NumpadTab => 0xBA,
+            Self::NumpadBackspace => 0xBB,
+            Self::NumpadA => 0xBC,
+            Self::NumpadB => 0xBD,
+            Self::NumpadC => 0xBE,
+            Self::NumpadD => 0xBF,
+            Self::NumpadE => 0xC0,
+            Self::NumpadF => 0xC1,
+            Self::NumpadXor => 0xC2,
+            Self::NumpadCaret => 0xC3,
+            Self::NumpadPercent => 0xC4,
+            Self::NumpadLess => 0xC5,
+            Self::NumpadGreater => 0xC6,
+            Self::NumpadAmpersand => 0xC7,
+            Self::NumpadDoubleAmpersand => 0xC8,
+            Self::NumpadPipe => 0xC9,
+            Self::NumpadDoublePipe => 0xCA,
+            Self::NumpadColon => 0xCB,
+            Self::NumpadHash => 0xCC,
+            Self::NumpadSpace => 0xCD,
+            Self::NumpadAt => 0xCE,
+            Self::NumpadExclamation => 0xCF,
+            Self::NumpadMemoryStore => 0xD0,
+            Self::NumpadMemoryRecall => 0xD1,
+            Self::NumpadMemoryClear => 0xD2,
+            Self::NumpadMemoryAdd => 0xD3,
+            Self::NumpadMemorySubtract => 0xD4,
+            Self::NumpadMemoryMultiply => 0xD5,
+            Self::NumpadMemoryDivide => 0xD6,
+            Self::NumpadPlusMinus => 0xD7,
+            Self::NumpadClear => 0xD8,
+            Self::NumpadClearEntry => 0xD9,
+            Self::NumpadBinary => 0xDA,
+            Self::NumpadOctal => 0xDB,
+            Self::NumpadDecimalBase => 0xDC,
+            Self::NumpadHexadecimal => 0xDD,
             Self::ControlLeft => 0xE0,
             Self::ShiftLeft => 0xE1,
             Self::AltLeft => 0xE2,
@@ -294,6 +506,7 @@ impl CanonicalKey {
             0x2F => Some(Self::BracketLeft),
             0x30 => Some(Self::BracketRight),
             0x31 => Some(Self::Backslash),
+            0x32 => Some(Self::IntlHash),
             0x33 => Some(Self::Semicolon),
             0x34 => Some(Self::Quote),
             0x35 => Some(Self::Backquote),
@@ -345,6 +558,8 @@ impl CanonicalKey {
             0x63 => Some(Self::NumpadDecimal),
             0x64 => Some(Self::IntlBackslash),
             0x65 => Some(Self::ContextMenu),
+            0x66 => Some(Self::Power),
+            0x67 => Some(Self::NumpadEqual),
             0x68 => Some(Self::F13),
             0x69 => Some(Self::F14),
             0x6A => Some(Self::F15),
@@ -357,6 +572,101 @@ impl CanonicalKey {
             0x71 => Some(Self::F22),
             0x72 => Some(Self::F23),
             0x73 => Some(Self::F24),
+            0x74 => Some(Self::Open),
+            0x75 => Some(Self::Help),
+            0x76 => Some(Self::Props),
+            0x77 => Some(Self::Select),
+            0x78 => Some(Self::Stop),
+            0x79 => Some(Self::Again),
+            0x7A => Some(Self::Undo),
+            0x7B => Some(Self::Cut),
+            0x7C => Some(Self::Copy),
+            0x7D => Some(Self::Paste),
+            0x7E => Some(Self::Find),
+            0x7F => Some(Self::AudioVolumeMute),
+            0x80 => Some(Self::AudioVolumeUp),
+            0x81 => Some(Self::AudioVolumeDown),
+            0x82 => Some(Self::LockingCapsLock),
+            0x83 => Some(Self::LockingNumLock),
+            0x84 => Some(Self::LockingScrollLock),
+            0x85 => Some(Self::NumpadComma),
+            0x86 => Some(Self::NumpadEqualAs400),
+            0x87 => Some(Self::IntlRo),
+            0x88 => Some(Self::KanaMode),
+            0x89 => Some(Self::IntlYen),
+            0x8A => Some(Self::Convert),
+            0x8B => Some(Self::NonConvert),
+            0x8C => Some(Self::International6),
+            0x8D => Some(Self::International7),
+            0x8E => Some(Self::International8),
+            0x8F => Some(Self::International9),
+            0x90 => Some(Self::Lang1),
+            0x91 => Some(Self::Lang2),
+            0x92 => Some(Self::Lang3),
+            0x93 => Some(Self::Lang4),
+            0x94 => Some(Self::Lang5),
+            0x95 => Some(Self::Lang6),
+            0x96 => Some(Self::Lang7),
+            0x97 => Some(Self::Lang8),
+            0x98 => Some(Self::Lang9),
+            0x99 => Some(Self::AltErase),
+            0x9A => Some(Self::SysReq),
+            0x9B => Some(Self::Cancel),
+            0x9C => Some(Self::Clear),
+            0x9D => Some(Self::Prior),
+            0x9E => Some(Self::Return),
+            0x9F => Some(Self::Separator),
+            0xA0 => Some(Self::Out),
+            0xA1 => Some(Self::Oper),
+            0xA2 => Some(Self::ClearAgain),
+            0xA3 => Some(Self::CrSel),
+            0xA4 => Some(Self::ExSel),
+            0xB0 => Some(Self::Numpad00),
+            0xB1 => Some(Self::Numpad000),
+            0xB2 => Some(Self::ThousandsSeparator),
+            0xB3 => Some(Self::DecimalSeparator),
+            0xB4 => Some(Self::CurrencyUnit),
+            0xB5 => Some(Self::CurrencySubunit),
+            0xB6 => Some(Self::NumpadParenLeft),
+            0xB7 => Some(Self::NumpadParenRight),
+            0xB8 => Some(Self::NumpadBraceLeft),
+            0xB9 => Some(Self::NumpadBraceRight),
+            0xBA => Some(Self::NumpadTab),
+            0xBB => Some(Self::NumpadBackspace),
+            0xBC => Some(Self::NumpadA),
+            0xBD => Some(Self::NumpadB),
+            0xBE => Some(Self::NumpadC),
+            0xBF => Some(Self::NumpadD),
+            0xC0 => Some(Self::NumpadE),
+            0xC1 => Some(Self::NumpadF),
+            0xC2 => Some(Self::NumpadXor),
+            0xC3 => Some(Self::NumpadCaret),
+            0xC4 => Some(Self::NumpadPercent),
+            0xC5 => Some(Self::NumpadLess),
+            0xC6 => Some(Self::NumpadGreater),
+            0xC7 => Some(Self::NumpadAmpersand),
+            0xC8 => Some(Self::NumpadDoubleAmpersand),
+            0xC9 => Some(Self::NumpadPipe),
+            0xCA => Some(Self::NumpadDoublePipe),
+            0xCB => Some(Self::NumpadColon),
+            0xCC => Some(Self::NumpadHash),
+            0xCD => Some(Self::NumpadSpace),
+            0xCE => Some(Self::NumpadAt),
+            0xCF => Some(Self::NumpadExclamation),
+            0xD0 => Some(Self::NumpadMemoryStore),
+            0xD1 => Some(Self::NumpadMemoryRecall),
+            0xD2 => Some(Self::NumpadMemoryClear),
+            0xD3 => Some(Self::NumpadMemoryAdd),
+            0xD4 => Some(Self::NumpadMemorySubtract),
+            0xD5 => Some(Self::NumpadMemoryMultiply),
+            0xD6 => Some(Self::NumpadMemoryDivide),
+            0xD7 => Some(Self::NumpadPlusMinus),
+            0xD8 => Some(Self::NumpadClear),
+            0xD9 => Some(Self::NumpadClearEntry),
+            0xDA => Some(Self::NumpadBinary),
+            0xDB => Some(Self::NumpadOctal),
+            0xDC => Some(Self::NumpadDecimalBase),
+            0xDD => Some(Self::NumpadHexadecimal),
             0xE0 => Some(Self::ControlLeft),
             0xE1 => Some(Self::ShiftLeft),
             0xE2 => Some(Self::AltLeft),
@@ -397,3 +707,49 @@ impl CanonicalKey {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hid_usage_round_trip() {
+        for usage in 0..=u8::MAX {
+            if let Some(key) = CanonicalKey::from_hid_usage(usage) {
+                assert_eq!(key.to_hid_usage(), usage, "{:?}", key);
+            }
+        }
+
+        // Every defined usage on the keyboard page has a key; 0xA5-0xAF and
+        // 0xDE-0xDF are reserved
+        for usage in (0x04..=0xE7).filter(|u| !matches!(u, 0xA5..=0xAF | 0xDE..=0xDF)) {
+            assert!(
+                CanonicalKey::from_hid_usage(usage).is_some(),
+                "usage 0x{:02X} has no key",
+                usage
+            );
+        }
+    }
+
+    #[test]
+    fn test_international_keys() {
+        let keys = [
+            (0x85, CanonicalKey::NumpadComma),
+            (0x87, CanonicalKey::IntlRo),
+            (0x88, CanonicalKey::KanaMode),
+            (0x89, CanonicalKey::IntlYen),
+            (0x8A, CanonicalKey::Convert),
+            (0x8B, CanonicalKey::NonConvert),
+            (0x90, CanonicalKey::Lang1),
+            (0x91, CanonicalKey::Lang2),
+            (0x92, CanonicalKey::Lang3),
+            (0x93, CanonicalKey::Lang4),
+            (0x94, CanonicalKey::Lang5),
+        ];
+        for (usage, key) in keys {
+            assert_eq!(CanonicalKey::from_hid_usage(usage), Some(key));
+            assert_eq!(key.to_hid_usage(), usage);
+            assert!(!key.is_modifier());
+        }
+    }
+}
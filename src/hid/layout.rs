@@ -0,0 +1,472 @@
+//! Target keyboard layouts: which key presses produce a given character.
+//!
+//! The target OS interprets scancodes with its own layout, so typing text
+//! means mapping each character back to the key (plus Shift/AltGr) that
+//! produces it there. Dead keys are followed by Space to emit the bare
+//! accent, or by the base letter to compose an accented one.
+
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
+
+use super::keyboard::CanonicalKey;
+use super::types::{KeyboardEvent, KeyboardModifiers};
+
+#[typeshare]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardLayout {
+    #[default]
+    Us,
+    Uk,
+    De,
+    Fr,
+    Es,
+    Jp,
+}
+
+impl KeyboardLayout {
+    pub const ALL: [KeyboardLayout; 6] = [
+        KeyboardLayout::Us,
+        KeyboardLayout::Uk,
+        KeyboardLayout::De,
+        KeyboardLayout::Fr,
+        KeyboardLayout::Es,
+        KeyboardLayout::Jp,
+    ];
+
+    /// Stable index used by the datachannel type-text message
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn index(self) -> u8 {
+        Self::ALL.iter().position(|l| *l == self).unwrap_or(0) as u8
+    }
+
+    fn table(self) -> &'static LayoutTable {
+        match self {
+            Self::Us => &US,
+            Self::Uk => &UK,
+            Self::De => &DE,
+            Self::Fr => &FR,
+            Self::Es => &ES,
+            Self::Jp => &JP,
+        }
+    }
+
+    /// Key presses that type `ch`, or `None` if the layout cannot produce it.
+    pub fn strokes(self, ch: char) -> Option<Vec<KeyStroke>> {
+        let table = self.table();
+        if let Some(stroke) = table.lookup(ch) {
+            return Some(vec![stroke]);
+        }
+        if let Some(stroke) = find_key(table.dead_keys, ch) {
+            return Some(vec![stroke, KeyStroke::plain(CanonicalKey::Space)]);
+        }
+
+        let (accent, base) = decompose(ch)?;
+        Some(vec![
+            find_key(table.dead_keys, accent)?,
+            table.lookup(base)?,
+        ])
+    }
+}
+
+/// One key press with the modifiers held for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyStroke {
+    pub key: CanonicalKey,
+    pub shift: bool,
+    pub altgr: bool,
+}
+
+impl KeyStroke {
+    const fn plain(key: CanonicalKey) -> Self {
+        Self {
+            key,
+            shift: false,
+            altgr: false,
+        }
+    }
+
+    pub fn modifiers(&self) -> KeyboardModifiers {
+        KeyboardModifiers {
+            left_shift: self.shift,
+            right_alt: self.altgr,
+            ..Default::default()
+        }
+    }
+
+    pub fn press(&self) -> KeyboardEvent {
+        KeyboardEvent::key_down(self.key, self.modifiers())
+    }
+
+    /// Release the key and the modifiers pressed for it; backends take the
+    /// event's modifiers as the new state, so Shift/AltGr would stay held.
+    pub fn release(&self) -> KeyboardEvent {
+        KeyboardEvent::key_up(self.key, KeyboardModifiers::default())
+    }
+}
+
+/// Characters on one key: plain, with Shift, with AltGr (`'\0'` if none)
+type KeyChars = (CanonicalKey, [char; 3]);
+
+struct LayoutTable {
+    /// Letter keys in a..z order (covers QWERTZ/AZERTY moves)
+    letters: [CanonicalKey; 26],
+    keys: &'static [KeyChars],
+    /// Dead keys: they only emit their accent together with the next key
+    dead_keys: &'static [KeyChars],
+}
+
+impl LayoutTable {
+    fn lookup(&self, ch: char) -> Option<KeyStroke> {
+        match ch {
+            '\n' => return Some(KeyStroke::plain(CanonicalKey::Enter)),
+            '\t' => return Some(KeyStroke::plain(CanonicalKey::Tab)),
+            ' ' => return Some(KeyStroke::plain(CanonicalKey::Space)),
+            'a'..='z' | 'A'..='Z' => {
+                let key = self.letters[(ch.to_ascii_lowercase() as u8 - b'a') as usize];
+                return Some(KeyStroke {
+                    key,
+                    shift: ch.is_ascii_uppercase(),
+                    altgr: false,
+                });
+            }
+            _ => {}
+        }
+
+        find_key(self.keys, ch)
+    }
+}
+
+fn find_key(keys: &[KeyChars], ch: char) -> Option<KeyStroke> {
+    if ch == N {
+        return None;
+    }
+    keys.iter().find_map(|(key, chars)| {
+        let level = chars.iter().position(|c| *c == ch)?;
+        Some(KeyStroke {
+            key: *key,
+            shift: level == 1,
+            altgr: level == 2,
+        })
+    })
+}
+
+/// Accented letters typed as dead key + base letter
+fn decompose(ch: char) -> Option<(char, char)> {
+    const COMPOSED: &[(char, &str, &str)] = &[
+        ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        ('¨', "aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+        ('´', "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+        ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+        ('~', "anoANO", "ãñõÃÑÕ"),
+    ];
+    COMPOSED.iter().find_map(|(accent, bases, composed)| {
+        let index = composed.chars().position(|c| c == ch)?;
+        Some((*accent, bases.chars().nth(index)?))
+    })
+}
+
+const N: char = '\0';
+
+const QWERTY: [CanonicalKey; 26] = {
+    use CanonicalKey::*;
+    [
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM, KeyN, KeyO,
+        KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyY, KeyZ,
+    ]
+};
+
+const QWERTZ: [CanonicalKey; 26] = {
+    use CanonicalKey::*;
+    [
+        KeyA, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, KeyM, KeyN, KeyO,
+        KeyP, KeyQ, KeyR, KeyS, KeyT, KeyU, KeyV, KeyW, KeyX, KeyZ, KeyY,
+    ]
+};
+
+const AZERTY: [CanonicalKey; 26] = {
+    use CanonicalKey::*;
+    [
+        KeyQ, KeyB, KeyC, KeyD, KeyE, KeyF, KeyG, KeyH, KeyI, KeyJ, KeyK, KeyL, Semicolon, KeyN,
+        KeyO, KeyP, KeyA, KeyR, KeyS, KeyT, KeyU, KeyV, KeyZ, KeyX, KeyY, KeyW,
+    ]
+};
+
+static US: LayoutTable = LayoutTable {
+    letters: QWERTY,
+    keys: &[
+        (CanonicalKey::Backquote, ['`', '~', N]),
+        (CanonicalKey::Digit1, ['1', '!', N]),
+        (CanonicalKey::Digit2, ['2', '@', N]),
+        (CanonicalKey::Digit3, ['3', '#', N]),
+        (CanonicalKey::Digit4, ['4', '$', N]),
+        (CanonicalKey::Digit5, ['5', '%', N]),
+        (CanonicalKey::Digit6, ['6', '^', N]),
+        (CanonicalKey::Digit7, ['7', '&', N]),
+        (CanonicalKey::Digit8, ['8', '*', N]),
+        (CanonicalKey::Digit9, ['9', '(', N]),
+        (CanonicalKey::Digit0, ['0', ')', N]),
+        (CanonicalKey::Minus, ['-', '_', N]),
+        (CanonicalKey::Equal, ['=', '+', N]),
+        (CanonicalKey::BracketLeft, ['[', '{', N]),
+        (CanonicalKey::BracketRight, [']', '}', N]),
+        (CanonicalKey::Backslash, ['\\', '|', N]),
+        (CanonicalKey::Semicolon, [';', ':', N]),
+        (CanonicalKey::Quote, ['\'', '"', N]),
+        (CanonicalKey::Comma, [',', '<', N]),
+        (CanonicalKey::Period, ['.', '>', N]),
+        (CanonicalKey::Slash, ['/', '?', N]),
+    ],
+    dead_keys: &[],
+};
+
+static UK: LayoutTable = LayoutTable {
+    letters: QWERTY,
+    keys: &[
+        (CanonicalKey::Backquote, ['`', '¬', '¦']),
+        (CanonicalKey::Digit1, ['1', '!', N]),
+        (CanonicalKey::Digit2, ['2', '"', N]),
+        (CanonicalKey::Digit3, ['3', '£', N]),
+        (CanonicalKey::Digit4, ['4', '$', '€']),
+        (CanonicalKey::Digit5, ['5', '%', N]),
+        (CanonicalKey::Digit6, ['6', '^', N]),
+        (CanonicalKey::Digit7, ['7', '&', N]),
+        (CanonicalKey::Digit8, ['8', '*', N]),
+        (CanonicalKey::Digit9, ['9', '(', N]),
+        (CanonicalKey::Digit0, ['0', ')', N]),
+        (CanonicalKey::Minus, ['-', '_', N]),
+        (CanonicalKey::Equal, ['=', '+', N]),
+        (CanonicalKey::BracketLeft, ['[', '{', N]),
+        (CanonicalKey::BracketRight, [']', '}', N]),
+        (CanonicalKey::Backslash, ['#', '~', N]),
+        (CanonicalKey::Semicolon, [';', ':', N]),
+        (CanonicalKey::Quote, ['\'', '@', N]),
+        (CanonicalKey::IntlBackslash, ['\\', '|', N]),
+        (CanonicalKey::Comma, [',', '<', N]),
+        (CanonicalKey::Period, ['.', '>', N]),
+        (CanonicalKey::Slash, ['/', '?', N]),
+    ],
+    dead_keys: &[],
+};
+
+static DE: LayoutTable = LayoutTable {
+    letters: QWERTZ,
+    keys: &[
+        (CanonicalKey::Backquote, [N, '°', N]),
+        (CanonicalKey::Digit1, ['1', '!', N]),
+        (CanonicalKey::Digit2, ['2', '"', '²']),
+        (CanonicalKey::Digit3, ['3', '§', '³']),
+        (CanonicalKey::Digit4, ['4', '$', N]),
+        (CanonicalKey::Digit5, ['5', '%', N]),
+        (CanonicalKey::Digit6, ['6', '&', N]),
+        (CanonicalKey::Digit7, ['7', '/', '{']),
+        (CanonicalKey::Digit8, ['8', '(', '[']),
+        (CanonicalKey::Digit9, ['9', ')', ']']),
+        (CanonicalKey::Digit0, ['0', '=', '}']),
+        (CanonicalKey::Minus, ['ß', '?', '\\']),
+        (CanonicalKey::BracketLeft, ['ü', 'Ü', N]),
+        (CanonicalKey::BracketRight, ['+', '*', '~']),
+        (CanonicalKey::Backslash, ['#', '\'', N]),
+        (CanonicalKey::Semicolon, ['ö', 'Ö', N]),
+        (CanonicalKey::Quote, ['ä', 'Ä', N]),
+        (CanonicalKey::IntlBackslash, ['<', '>', '|']),
+        (CanonicalKey::Comma, [',', ';', N]),
+        (CanonicalKey::Period, ['.', ':', N]),
+        (CanonicalKey::Slash, ['-', '_', N]),
+        (CanonicalKey::KeyQ, [N, N, '@']),
+        (CanonicalKey::KeyE, [N, N, '€']),
+        (CanonicalKey::KeyM, [N, N, 'µ']),
+    ],
+    dead_keys: &[
+        (CanonicalKey::Backquote, ['^', N, N]),
+        (CanonicalKey::Equal, ['´', '`', N]),
+    ],
+};
+
+static FR: LayoutTable = LayoutTable {
+    letters: AZERTY,
+    keys: &[
+        (CanonicalKey::Backquote, ['²', N, N]),
+        (CanonicalKey::Digit1, ['&', '1', N]),
+        (CanonicalKey::Digit2, ['é', '2', N]),
+        (CanonicalKey::Digit3, ['"', '3', '#']),
+        (CanonicalKey::Digit4, ['\'', '4', '{']),
+        (CanonicalKey::Digit5, ['(', '5', '[']),
+        (CanonicalKey::Digit6, ['-', '6', '|']),
+        (CanonicalKey::Digit7, ['è', '7', N]),
+        (CanonicalKey::Digit8, ['_', '8', '\\']),
+        (CanonicalKey::Digit9, ['ç', '9', '^']),
+        (CanonicalKey::Digit0, ['à', '0', '@']),
+        (CanonicalKey::Minus, [')', '°', ']']),
+        (CanonicalKey::Equal, ['=', '+', '}']),
+        (CanonicalKey::BracketRight, ['$', '£', '¤']),
+        (CanonicalKey::Quote, ['ù', '%', N]),
+        (CanonicalKey::Backslash, ['*', 'µ', N]),
+        (CanonicalKey::IntlBackslash, ['<', '>', N]),
+        (CanonicalKey::KeyM, [',', '?', N]),
+        (CanonicalKey::Comma, [';', '.', N]),
+        (CanonicalKey::Period, [':', '/', N]),
+        (CanonicalKey::Slash, ['!', '§', N]),
+        (CanonicalKey::KeyE, [N, N, '€']),
+    ],
+    // AltGr+9 gives a plain caret, so only accented letters use the dead one.
+    dead_keys: &[
+        (CanonicalKey::Digit2, [N, N, '~']),
+        (CanonicalKey::Digit7, [N, N, '`']),
+        (CanonicalKey::BracketLeft, ['^', '¨', N]),
+    ],
+};
+
+static ES: LayoutTable = LayoutTable {
+    letters: QWERTY,
+    keys: &[
+        (CanonicalKey::Backquote, ['º', 'ª', '\\']),
+        (CanonicalKey::Digit1, ['1', '!', '|']),
+        (CanonicalKey::Digit2, ['2', '"', '@']),
+        (CanonicalKey::Digit3, ['3', '·', '#']),
+        (CanonicalKey::Digit4, ['4', '$', '~']),
+        (CanonicalKey::Digit5, ['5', '%', '€']),
+        (CanonicalKey::Digit6, ['6', '&', '¬']),
+        (CanonicalKey::Digit7, ['7', '/', N]),
+        (CanonicalKey::Digit8, ['8', '(', N]),
+        (CanonicalKey::Digit9, ['9', ')', N]),
+        (CanonicalKey::Digit0, ['0', '=', N]),
+        (CanonicalKey::Minus, ['\'', '?', N]),
+        (CanonicalKey::Equal, ['¡', '¿', N]),
+        (CanonicalKey::BracketLeft, [N, N, '[']),
+        (CanonicalKey::BracketRight, ['+', '*', ']']),
+        (CanonicalKey::Semicolon, ['ñ', 'Ñ', N]),
+        (CanonicalKey::Quote, [N, N, '{']),
+        (CanonicalKey::Backslash, ['ç', 'Ç', '}']),
+        (CanonicalKey::IntlBackslash, ['<', '>', N]),
+        (CanonicalKey::Comma, [',', ';', N]),
+        (CanonicalKey::Period, ['.', ':', N]),
+        (CanonicalKey::Slash, ['-', '_', N]),
+    ],
+    dead_keys: &[
+        (CanonicalKey::BracketLeft, ['`', '^', N]),
+        (CanonicalKey::Quote, ['´', '¨', N]),
+    ],
+};
+
+static JP: LayoutTable = LayoutTable {
+    letters: QWERTY,
+    keys: &[
+        (CanonicalKey::Digit1, ['1', '!', N]),
+        (CanonicalKey::Digit2, ['2', '"', N]),
+        (CanonicalKey::Digit3, ['3', '#', N]),
+        (CanonicalKey::Digit4, ['4', '$', N]),
+        (CanonicalKey::Digit5, ['5', '%', N]),
+        (CanonicalKey::Digit6, ['6', '&', N]),
+        (CanonicalKey::Digit7, ['7', '\'', N]),
+        (CanonicalKey::Digit8, ['8', '(', N]),
+        (CanonicalKey::Digit9, ['9', ')', N]),
+        (CanonicalKey::Digit0, ['0', N, N]),
+        (CanonicalKey::Minus, ['-', '=', N]),
+        (CanonicalKey::Equal, ['^', '~', N]),
+        (CanonicalKey::IntlYen, ['¥', '|', N]),
+        (CanonicalKey::BracketLeft, ['@', '`', N]),
+        (CanonicalKey::BracketRight, ['[', '{', N]),
+        (CanonicalKey::Backslash, [']', '}', N]),
+        (CanonicalKey::Semicolon, [';', '+', N]),
+        (CanonicalKey::Quote, [':', '*', N]),
+        (CanonicalKey::Comma, [',', '<', N]),
+        (CanonicalKey::Period, ['.', '>', N]),
+        (CanonicalKey::Slash, ['/', '?', N]),
+        (CanonicalKey::IntlRo, ['\\', '_', N]),
+    ],
+    dead_keys: &[],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(layout: KeyboardLayout, ch: char) -> Vec<(CanonicalKey, bool, bool)> {
+        layout
+            .strokes(ch)
+            .unwrap_or_else(|| panic!("{:?} cannot type {:?}", layout, ch))
+            .into_iter()
+            .map(|s| (s.key, s.shift, s.altgr))
+            .collect()
+    }
+
+    #[test]
+    fn test_us_covers_printable_ascii() {
+        for ch in (0x20u8..0x7F).map(char::from).chain(['\n', '\t']) {
+            assert!(KeyboardLayout::Us.strokes(ch).is_some(), "{:?}", ch);
+        }
+        assert_eq!(
+            keys(KeyboardLayout::Us, 'A'),
+            vec![(CanonicalKey::KeyA, true, false)]
+        );
+        assert!(KeyboardLayout::Us.strokes('é').is_none());
+    }
+
+    #[test]
+    fn test_layout_specific_keys() {
+        assert_eq!(
+            keys(KeyboardLayout::De, 'z'),
+            vec![(CanonicalKey::KeyY, false, false)]
+        );
+        assert_eq!(
+            keys(KeyboardLayout::De, '@'),
+            vec![(CanonicalKey::KeyQ, false, true)]
+        );
+        assert_eq!(
+            keys(KeyboardLayout::Fr, 'a'),
+            vec![(CanonicalKey::KeyQ, false, false)]
+        );
+        assert_eq!(
+            keys(KeyboardLayout::Fr, '1'),
+            vec![(CanonicalKey::Digit1, true, false)]
+        );
+        assert_eq!(
+            keys(KeyboardLayout::Uk, '"'),
+            vec![(CanonicalKey::Digit2, true, false)]
+        );
+        assert_eq!(
+            keys(KeyboardLayout::Jp, '_'),
+            vec![(CanonicalKey::IntlRo, true, false)]
+        );
+    }
+
+    #[test]
+    fn test_dead_keys() {
+        // Bare accent: dead key followed by Space
+        assert_eq!(
+            keys(KeyboardLayout::De, '^'),
+            vec![
+                (CanonicalKey::Backquote, false, false),
+                (CanonicalKey::Space, false, false)
+            ]
+        );
+        // Composed letter: dead key followed by the base letter
+        assert_eq!(
+            keys(KeyboardLayout::Fr, 'ê'),
+            vec![
+                (CanonicalKey::BracketLeft, false, false),
+                (CanonicalKey::KeyE, false, false)
+            ]
+        );
+        assert_eq!(
+            keys(KeyboardLayout::Es, 'á'),
+            vec![
+                (CanonicalKey::Quote, false, false),
+                (CanonicalKey::KeyA, false, false)
+            ]
+        );
+        assert!(KeyboardLayout::Uk.strokes('ê').is_none());
+    }
+
+    #[test]
+    fn test_layout_index_roundtrip() {
+        for layout in KeyboardLayout::ALL {
+            assert_eq!(KeyboardLayout::from_index(layout.index()), Some(layout));
+        }
+        assert_eq!(KeyboardLayout::from_index(200), None);
+    }
+}
@@ -0,0 +1,344 @@
+//! Recorded keyboard/mouse macros, stored in SQLite and replayed through
+//! [`HidController`](super::HidController).
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::time::Instant;
+use time::OffsetDateTime;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::datachannel::HidChannelEvent;
+use super::types::{ConsumerEvent, KeyboardEvent, MouseEvent};
+use crate::error::{AppError, Result};
+
+/// Longest macro that is recorded or imported
+pub const MAX_MACRO_STEPS: usize = 10_000;
+/// Longest pause between two steps; longer recorded gaps are shortened to it
+pub const MAX_STEP_DELAY_MS: u64 = 60_000;
+pub const MIN_PLAYBACK_SPEED: f64 = 0.1;
+pub const MAX_PLAYBACK_SPEED: f64 = 10.0;
+
+/// One recorded HID event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "event", rename_all = "lowercase")]
+pub enum MacroEvent {
+    Keyboard(KeyboardEvent),
+    Mouse(MouseEvent),
+    Consumer(ConsumerEvent),
+}
+
+impl From<MacroEvent> for HidChannelEvent {
+    fn from(event: MacroEvent) -> Self {
+        match event {
+            MacroEvent::Keyboard(ev) => HidChannelEvent::Keyboard(ev),
+            MacroEvent::Mouse(ev) => HidChannelEvent::Mouse(ev),
+            MacroEvent::Consumer(ev) => HidChannelEvent::Consumer(ev),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroStep {
+    /// Pause before this event, in milliseconds
+    #[serde(default)]
+    pub delay_ms: u64,
+    #[serde(flatten)]
+    pub event: MacroEvent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HidMacro {
+    pub id: String,
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl HidMacro {
+    /// Total run time at normal speed, in milliseconds
+    pub fn duration_ms(&self) -> u64 {
+        self.steps
+            .iter()
+            .fold(0, |total: u64, s| total.saturating_add(s.delay_ms))
+    }
+}
+
+/// Listing entry without the steps
+#[derive(Debug, Clone, Serialize)]
+pub struct HidMacroSummary {
+    pub id: String,
+    pub name: String,
+    pub steps: usize,
+    pub duration_ms: u64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl From<&HidMacro> for HidMacroSummary {
+    fn from(m: &HidMacro) -> Self {
+        Self {
+            id: m.id.clone(),
+            name: m.name.clone(),
+            steps: m.steps.len(),
+            duration_ms: m.duration_ms(),
+            created_at: m.created_at,
+        }
+    }
+}
+
+type MacroRow = (String, String, String, i64);
+
+fn from_row(row: MacroRow) -> Result<HidMacro> {
+    let (id, name, steps, created_at) = row;
+    Ok(HidMacro {
+        id,
+        name,
+        steps: serde_json::from_str(&steps)?,
+        created_at: OffsetDateTime::from_unix_timestamp(created_at)
+            .unwrap_or(OffsetDateTime::UNIX_EPOCH),
+    })
+}
+
+fn validate(name: &str, steps: &[MacroStep]) -> Result<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::BadRequest("Macro name is required".to_string()));
+    }
+    if steps.is_empty() {
+        return Err(AppError::BadRequest("Macro has no steps".to_string()));
+    }
+    if steps.len() > MAX_MACRO_STEPS {
+        return Err(AppError::BadRequest(format!(
+            "Macro has more than {} steps",
+            MAX_MACRO_STEPS
+        )));
+    }
+    if steps.iter().any(|s| s.delay_ms > MAX_STEP_DELAY_MS) {
+        return Err(AppError::BadRequest(format!(
+            "Macro step delays must be at most {} ms",
+            MAX_STEP_DELAY_MS
+        )));
+    }
+    Ok(())
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, name, steps, created_at FROM hid_macros";
+
+#[derive(Clone)]
+pub struct MacroStore {
+    pool: Pool<Sqlite>,
+}
+
+impl MacroStore {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<HidMacro>> {
+        let rows: Vec<MacroRow> = sqlx::query_as(&format!("{} ORDER BY name ASC", SELECT_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter().map(from_row).collect()
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<HidMacro>> {
+        let row: Option<MacroRow> = sqlx::query_as(&format!("{} WHERE id = ?1", SELECT_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(from_row).transpose()
+    }
+
+    /// Save a new macro; names are unique.
+    pub async fn create(&self, name: &str, steps: Vec<MacroStep>) -> Result<HidMacro> {
+        let name = name.trim();
+        validate(name, &steps)?;
+
+        let exists: Option<(String,)> = sqlx::query_as("SELECT id FROM hid_macros WHERE name = ?1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+        if exists.is_some() {
+            return Err(AppError::BadRequest(format!(
+                "A macro named '{}' already exists",
+                name
+            )));
+        }
+
+        let hid_macro = HidMacro {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            steps,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        sqlx::query("INSERT INTO hid_macros (id, name, steps, created_at) VALUES (?1, ?2, ?3, ?4)")
+            .bind(&hid_macro.id)
+            .bind(&hid_macro.name)
+            .bind(serde_json::to_string(&hid_macro.steps)?)
+            .bind(hid_macro.created_at.unix_timestamp())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(hid_macro)
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let result = sqlx::query("DELETE FROM hid_macros WHERE id = ?1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Macro not found".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Events captured since recording started
+pub(crate) struct Recording {
+    last: Instant,
+    steps: Vec<MacroStep>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, event: MacroEvent) {
+        if self.steps.len() >= MAX_MACRO_STEPS {
+            return;
+        }
+        let now = Instant::now();
+        let delay_ms = if self.steps.is_empty() {
+            0
+        } else {
+            (now.duration_since(self.last).as_millis() as u64).min(MAX_STEP_DELAY_MS)
+        };
+        self.last = now;
+        self.steps.push(MacroStep { delay_ms, event });
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn into_steps(self) -> Vec<MacroStep> {
+        self.steps
+    }
+}
+
+/// The macro currently playing, if any
+pub(crate) struct RunningMacro {
+    pub id: String,
+    pub abort: CancellationToken,
+}
+
+impl RunningMacro {
+    pub fn abort(&self) {
+        self.abort.cancel();
+    }
+}
+
+pub(crate) fn validate_speed(speed: f64) -> Result<f64> {
+    if !(MIN_PLAYBACK_SPEED..=MAX_PLAYBACK_SPEED).contains(&speed) {
+        return Err(AppError::BadRequest(format!(
+            "speed must be between {} and {}",
+            MIN_PLAYBACK_SPEED, MAX_PLAYBACK_SPEED
+        )));
+    }
+    Ok(speed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DatabasePool;
+    use crate::hid::{CanonicalKey, KeyboardModifiers};
+    use tempfile::tempdir;
+
+    fn key_steps() -> Vec<MacroStep> {
+        let key = CanonicalKey::F2;
+        vec![
+            MacroStep {
+                delay_ms: 0,
+                event: MacroEvent::Keyboard(KeyboardEvent::key_down(
+                    key,
+                    KeyboardModifiers::default(),
+                )),
+            },
+            MacroStep {
+                delay_ms: 80,
+                event: MacroEvent::Keyboard(KeyboardEvent::key_up(
+                    key,
+                    KeyboardModifiers::default(),
+                )),
+            },
+            MacroStep {
+                delay_ms: 500,
+                event: MacroEvent::Mouse(MouseEvent::scroll(-1)),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_step_json_format() {
+        let json = serde_json::to_value(&key_steps()[1]).unwrap();
+        assert_eq!(json["delay_ms"], 80);
+        assert_eq!(json["kind"], "keyboard");
+        assert_eq!(json["event"]["type"], "up");
+        assert_eq!(json["event"]["key"], "F2");
+
+        let imported: Vec<MacroStep> = serde_json::from_str(
+            r#"[{"kind":"mouse","event":{"type":"down","button":"left"}},
+                {"kind":"consumer","delay_ms":10,"event":{"usage":233}}]"#,
+        )
+        .unwrap();
+        assert_eq!(imported[0].delay_ms, 0);
+        assert!(matches!(imported[1].event, MacroEvent::Consumer(ref c) if c.usage == 233));
+    }
+
+    #[tokio::test]
+    async fn test_macro_store() {
+        let dir = tempdir().unwrap();
+        let db = DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let store = MacroStore::new(db.clone_pool());
+
+        let saved = store.create(" Enter BIOS ", key_steps()).await.unwrap();
+        assert_eq!(saved.name, "Enter BIOS");
+        assert_eq!(saved.duration_ms(), 580);
+        assert!(store.create("Enter BIOS", key_steps()).await.is_err());
+        assert!(store.create("empty", vec![]).await.is_err());
+        let mut slow = key_steps();
+        slow[2].delay_ms = MAX_STEP_DELAY_MS + 1;
+        assert!(store.create("slow", slow).await.is_err());
+
+        let loaded = store.get(&saved.id).await.unwrap().unwrap();
+        assert_eq!(loaded.steps.len(), 3);
+        assert_eq!(store.list().await.unwrap().len(), 1);
+
+        store.delete(&saved.id).await.unwrap();
+        assert!(store.get(&saved.id).await.unwrap().is_none());
+        assert!(store.delete(&saved.id).await.is_err());
+    }
+
+    #[test]
+    fn test_recording_timing() {
+        let mut recording = Recording::new();
+        recording.push(MacroEvent::Mouse(MouseEvent::move_rel(1, 1)));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        recording.push(MacroEvent::Mouse(MouseEvent::move_rel(1, 1)));
+
+        let steps = recording.into_steps();
+        assert_eq!(steps[0].delay_ms, 0);
+        assert!(steps[1].delay_ms >= 20);
+    }
+}
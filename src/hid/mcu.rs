@@ -0,0 +1,1029 @@
+//! Microcontroller HID bridge (RP2040 / Arduino Leonardo class) over UART.
+//!
+//! The MCU enumerates as a USB keyboard + mouse + consumer device on the
+//! target and replays reports it receives on its serial port. Every frame is:
+//! ```text
+//! ┌───────┬──────┬─────┬─────┬───────────┬──────────┐
+//! │ SYNC  │ TYPE │ SEQ │ LEN │  PAYLOAD  │  CRC16   │
+//! ├───────┼──────┼─────┼─────┼───────────┼──────────┤
+//! │ A5 5A │  xx  │ xx  │  N  │  N bytes  │ LE, 2 B  │
+//! └───────┴──────┴─────┴─────┴───────────┴──────────┘
+//! ```
+//! CRC-16/CCITT-FALSE (poly 0x1021, init 0xFFFF) over TYPE..PAYLOAD.
+//!
+//! Host → MCU (`SEQ` 1..=255, every frame is answered with the same `SEQ`):
+//! - `0x01` HELLO, empty → INFO `[protocol, firmware, flags, leds]`,
+//!   flags bit 0 = target USB host configured
+//! - `0x02` KEYBOARD `[modifiers, 0, k1..k6]` (boot report)
+//! - `0x03` MOUSE_REL `[buttons, dx, dy, wheel, pan]`
+//! - `0x04` MOUSE_ABS `[buttons, x_lo, x_hi, y_lo, y_hi, wheel, pan]`, 0..=32767
+//! - `0x05` CONSUMER `[usage_lo, usage_hi]`, usage 0 releases
+//! - `0x06` RESET, empty: release every key and button
+//!
+//! MCU → host:
+//! - `0x80` ACK `[status]`, see [`ack_status`]
+//! - `0x81` INFO, reply to HELLO
+//! - `0x82` LEDS `[leds]`, unsolicited with `SEQ` 0 whenever the target
+//!   changes its keyboard LEDs (HID LED bit order)
+//!
+//! Frames answered with a CRC NAK or not answered at all are retransmitted;
+//! a link that stays silent is reopened.
+
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{info, trace, warn};
+
+use super::backend::{HidBackend, HidBackendRuntimeSnapshot};
+use super::types::{
+    ConsumerEvent, KeyEventType, KeyboardEvent, KeyboardReport, MouseEvent, MouseEventType,
+};
+use crate::error::{AppError, Result};
+use crate::events::LedState;
+
+const SYNC: [u8; 2] = [0xA5, 0x5A];
+
+/// Protocol revision reported in INFO; firmware speaking another one is rejected
+pub const PROTOCOL_VERSION: u8 = 1;
+
+pub const DEFAULT_BAUD_RATE: u32 = 115200;
+
+/// SYNC + TYPE + SEQ + LEN + CRC16
+const FRAME_OVERHEAD: usize = 7;
+
+const MAX_PAYLOAD_LEN: usize = 32;
+
+const RESPONSE_TIMEOUT_MS: u64 = 200;
+
+/// Attempts per frame before the link is considered lost
+const MAX_ATTEMPTS: usize = 3;
+
+const PROBE_INTERVAL_MS: u64 = 500;
+
+const RECONNECT_DELAY_MS: u64 = 2000;
+
+const INIT_WAIT_MS: u64 = 3000;
+
+pub mod frame_type {
+    pub const HELLO: u8 = 0x01;
+    pub const KEYBOARD: u8 = 0x02;
+    pub const MOUSE_REL: u8 = 0x03;
+    pub const MOUSE_ABS: u8 = 0x04;
+    pub const CONSUMER: u8 = 0x05;
+    pub const RESET: u8 = 0x06;
+    pub const ACK: u8 = 0x80;
+    pub const INFO: u8 = 0x81;
+    pub const LEDS: u8 = 0x82;
+}
+
+pub mod ack_status {
+    pub const OK: u8 = 0x00;
+    pub const CRC_ERROR: u8 = 0x01;
+    pub const UNKNOWN_TYPE: u8 = 0x02;
+    pub const BAD_LENGTH: u8 = 0x03;
+    /// The target has not configured the MCU's USB device (powered off, in BIOS POST, ...)
+    pub const USB_NOT_READY: u8 = 0x04;
+}
+
+/// CRC-16/CCITT-FALSE
+pub fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xFFFF, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub frame_type: u8,
+    pub seq: u8,
+    pub payload: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(frame_type: u8, seq: u8, payload: &[u8]) -> Self {
+        debug_assert!(payload.len() <= MAX_PAYLOAD_LEN, "Payload too long");
+        Self {
+            frame_type,
+            seq,
+            payload: payload.to_vec(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(FRAME_OVERHEAD + self.payload.len());
+        buf.extend_from_slice(&SYNC);
+        buf.push(self.frame_type);
+        buf.push(self.seq);
+        buf.push(self.payload.len() as u8);
+        buf.extend_from_slice(&self.payload);
+        let crc = crc16(&buf[SYNC.len()..]);
+        buf.extend_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    /// Find the next valid frame in `buffer`, skipping noise and frames with a
+    /// bad CRC. Returns the frame and the number of bytes consumed, or `None`
+    /// when more input is needed.
+    pub fn decode(buffer: &[u8]) -> Option<(Frame, usize)> {
+        let mut offset = 0;
+        while offset + FRAME_OVERHEAD <= buffer.len() {
+            if buffer[offset..offset + 2] != SYNC {
+                offset += 1;
+                continue;
+            }
+
+            let len = buffer[offset + 4] as usize;
+            if len > MAX_PAYLOAD_LEN {
+                offset += 1;
+                continue;
+            }
+
+            let end = offset + FRAME_OVERHEAD + len;
+            if end > buffer.len() {
+                return None;
+            }
+
+            let body = &buffer[offset + 2..end - 2];
+            let crc = u16::from_le_bytes([buffer[end - 2], buffer[end - 1]]);
+            if crc16(body) == crc {
+                let frame = Frame::new(body[0], body[1], &body[3..]);
+                return Some((frame, end));
+            }
+
+            offset += 1;
+        }
+
+        None
+    }
+}
+
+/// INFO payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct McuInfo {
+    pub protocol_version: u8,
+    pub firmware_version: u8,
+    pub usb_connected: bool,
+    pub leds: u8,
+}
+
+impl McuInfo {
+    pub fn from_payload(payload: &[u8]) -> Option<Self> {
+        if payload.len() < 4 {
+            return None;
+        }
+        Some(Self {
+            protocol_version: payload[0],
+            firmware_version: payload[1],
+            usb_connected: payload[2] & 0x01 != 0,
+            leds: payload[3],
+        })
+    }
+}
+
+struct McuRuntimeState {
+    initialized: AtomicBool,
+    online: AtomicBool,
+    leds: AtomicU8,
+    last_error: RwLock<Option<(String, String)>>,
+    notify_tx: watch::Sender<()>,
+}
+
+impl McuRuntimeState {
+    fn new() -> Self {
+        let (notify_tx, _notify_rx) = watch::channel(());
+        Self {
+            initialized: AtomicBool::new(false),
+            online: AtomicBool::new(false),
+            leds: AtomicU8::new(0),
+            last_error: RwLock::new(None),
+            notify_tx,
+        }
+    }
+
+    fn subscribe(&self) -> watch::Receiver<()> {
+        self.notify_tx.subscribe()
+    }
+
+    fn notify(&self) {
+        let _ = self.notify_tx.send(());
+    }
+
+    fn clear_error(&self) {
+        let mut guard = self.last_error.write();
+        if guard.is_some() {
+            *guard = None;
+            self.notify();
+        }
+    }
+
+    fn set_online(&self) {
+        let was_online = self.online.swap(true, Ordering::Relaxed);
+        let mut error = self.last_error.write();
+        let cleared_error = error.take().is_some();
+        drop(error);
+        if !was_online || cleared_error {
+            self.notify();
+        }
+    }
+
+    fn set_error(&self, reason: impl Into<String>, error_code: impl Into<String>) {
+        let reason = reason.into();
+        let error_code = error_code.into();
+        let was_online = self.online.swap(false, Ordering::Relaxed);
+        let mut error = self.last_error.write();
+        let changed = error.as_ref() != Some(&(reason.clone(), error_code.clone()));
+        *error = Some((reason, error_code));
+        drop(error);
+        if was_online || changed {
+            self.notify();
+        }
+    }
+
+    fn record(&self, err: AppError) {
+        if let AppError::HidError {
+            reason, error_code, ..
+        } = err
+        {
+            self.set_error(reason, error_code);
+        }
+    }
+
+    fn set_leds(&self, leds: u8) {
+        if self.leds.swap(leds, Ordering::Relaxed) != leds {
+            self.notify();
+        }
+    }
+
+    fn set_initialized(&self, initialized: bool) {
+        if self.initialized.swap(initialized, Ordering::Relaxed) != initialized {
+            self.notify();
+        }
+    }
+
+    fn set_offline(&self) {
+        if self.online.swap(false, Ordering::Relaxed) {
+            self.notify();
+        }
+    }
+}
+
+fn backend_error(reason: impl Into<String>, error_code: impl Into<String>) -> AppError {
+    AppError::HidError {
+        backend: "mcu".to_string(),
+        reason: reason.into(),
+        error_code: error_code.into(),
+    }
+}
+
+/// An open serial port plus the receive state of the framing layer
+struct Link {
+    port: Box<dyn serialport::SerialPort>,
+    seq: u8,
+    pending: Vec<u8>,
+}
+
+impl Link {
+    fn open(port_path: &str, baud_rate: u32) -> Result<Self> {
+        if !std::path::Path::new(port_path).exists() {
+            return Err(backend_error(
+                format!("Serial port {} not found", port_path),
+                "port_not_found",
+            ));
+        }
+
+        let port = serialport::new(port_path, baud_rate)
+            .timeout(Duration::from_millis(RESPONSE_TIMEOUT_MS))
+            .open()
+            .map_err(|e| {
+                let error_code = match e.kind() {
+                    serialport::ErrorKind::NoDevice => "port_not_found",
+                    serialport::ErrorKind::InvalidInput => "invalid_config",
+                    serialport::ErrorKind::Io(_) => "io_error",
+                    _ => "serial_error",
+                };
+                backend_error(format!("Failed to open serial port: {}", e), error_code)
+            })?;
+        let _ = port.clear(serialport::ClearBuffer::Input);
+
+        Ok(Self {
+            port,
+            seq: 0,
+            pending: Vec::with_capacity(64),
+        })
+    }
+
+    /// SEQ 0 is reserved for unsolicited MCU frames
+    fn next_seq(&mut self) -> u8 {
+        self.seq = self.seq.wrapping_add(1).max(1);
+        self.seq
+    }
+
+    /// Send a frame and wait for the reply carrying its SEQ. Unanswered or
+    /// CRC-rejected frames are resent; LED frames arriving meanwhile update
+    /// `runtime`.
+    fn xfer(&mut self, frame_type: u8, payload: &[u8], runtime: &McuRuntimeState) -> Result<Frame> {
+        let seq = self.next_seq();
+        let packet = Frame::new(frame_type, seq, payload).encode();
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            self.port.write_all(&packet).map_err(|e| {
+                backend_error(format!("Failed to write to MCU: {}", e), "write_failed")
+            })?;
+
+            match self.read_reply(seq, runtime)? {
+                Some(reply)
+                    if reply.frame_type == frame_type::ACK
+                        && reply.payload.first() == Some(&ack_status::CRC_ERROR) =>
+                {
+                    trace!("MCU NAK (CRC) for seq {}, attempt {}", seq, attempt);
+                }
+                Some(reply) => return Ok(reply),
+                None => trace!("MCU timeout for seq {}, attempt {}", seq, attempt),
+            }
+        }
+
+        Err(backend_error(
+            format!("No response from MCU for frame 0x{:02X}", frame_type),
+            "no_response",
+        ))
+    }
+
+    fn read_reply(&mut self, seq: u8, runtime: &McuRuntimeState) -> Result<Option<Frame>> {
+        let deadline = Instant::now() + Duration::from_millis(RESPONSE_TIMEOUT_MS);
+
+        loop {
+            while let Some((frame, consumed)) = Frame::decode(&self.pending) {
+                self.pending.drain(..consumed);
+                if frame.frame_type == frame_type::LEDS {
+                    if let Some(&leds) = frame.payload.first() {
+                        runtime.set_leds(leds);
+                    }
+                } else if frame.seq == seq {
+                    return Ok(Some(frame));
+                } else {
+                    trace!(
+                        "MCU ignored stale frame 0x{:02X} seq {} (waiting for {})",
+                        frame.frame_type,
+                        frame.seq,
+                        seq
+                    );
+                }
+            }
+
+            let max_frame = FRAME_OVERHEAD + MAX_PAYLOAD_LEN;
+            if self.pending.len() > max_frame * 4 {
+                let excess = self.pending.len() - max_frame;
+                self.pending.drain(..excess);
+            }
+
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+
+            let mut chunk = [0u8; 64];
+            match self.port.read(&mut chunk) {
+                Ok(n) => self.pending.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                Err(e) => {
+                    return Err(backend_error(
+                        format!("Failed to read from MCU: {}", e),
+                        "read_failed",
+                    ));
+                }
+            }
+        }
+    }
+
+    fn hello(&mut self, runtime: &McuRuntimeState) -> Result<McuInfo> {
+        let reply = self.xfer(frame_type::HELLO, &[], runtime)?;
+        if reply.frame_type != frame_type::INFO {
+            return Err(backend_error(
+                format!("Unexpected reply 0x{:02X} to HELLO", reply.frame_type),
+                "invalid_response",
+            ));
+        }
+        let info = McuInfo::from_payload(&reply.payload)
+            .ok_or_else(|| backend_error("Malformed MCU info frame", "invalid_response"))?;
+        if info.protocol_version != PROTOCOL_VERSION {
+            return Err(backend_error(
+                format!(
+                    "MCU speaks protocol v{}, expected v{}",
+                    info.protocol_version, PROTOCOL_VERSION
+                ),
+                "unsupported_firmware",
+            ));
+        }
+        runtime.set_leds(info.leds);
+        Ok(info)
+    }
+}
+
+enum WorkerCommand {
+    Frame { frame_type: u8, payload: Vec<u8> },
+    Shutdown,
+}
+
+pub struct McuBackend {
+    port_path: String,
+    baud_rate: u32,
+    worker_tx: Mutex<Option<mpsc::Sender<WorkerCommand>>>,
+    worker_handle: Mutex<Option<thread::JoinHandle<()>>>,
+    keyboard_state: Mutex<KeyboardReport>,
+    mouse_buttons: AtomicU8,
+    screen_resolution: RwLock<(u32, u32)>,
+    last_abs: Mutex<(u16, u16)>,
+    relative_mouse_active: AtomicBool,
+    runtime: Arc<McuRuntimeState>,
+}
+
+impl McuBackend {
+    pub fn new(port_path: &str) -> Result<Self> {
+        Self::with_baud_rate(port_path, DEFAULT_BAUD_RATE)
+    }
+
+    pub fn with_baud_rate(port_path: &str, baud_rate: u32) -> Result<Self> {
+        Ok(Self {
+            port_path: port_path.to_string(),
+            baud_rate,
+            worker_tx: Mutex::new(None),
+            worker_handle: Mutex::new(None),
+            keyboard_state: Mutex::new(KeyboardReport::default()),
+            mouse_buttons: AtomicU8::new(0),
+            screen_resolution: RwLock::new((1920, 1080)),
+            last_abs: Mutex::new((0, 0)),
+            relative_mouse_active: AtomicBool::new(false),
+            runtime: Arc::new(McuRuntimeState::new()),
+        })
+    }
+
+    pub fn check_port_exists(&self) -> bool {
+        std::path::Path::new(&self.port_path).exists()
+    }
+
+    fn connect(
+        port_path: &str,
+        baud_rate: u32,
+        runtime: &McuRuntimeState,
+    ) -> Result<(Link, McuInfo)> {
+        let mut link = Link::open(port_path, baud_rate)?;
+        let info = link.hello(runtime)?;
+        Ok((link, info))
+    }
+
+    /// Map an ACK/INFO reply onto the runtime state
+    fn apply_reply(reply: &Frame, runtime: &McuRuntimeState) {
+        let usb_connected = match reply.frame_type {
+            frame_type::INFO => match McuInfo::from_payload(&reply.payload) {
+                Some(info) => {
+                    runtime.set_leds(info.leds);
+                    info.usb_connected
+                }
+                None => {
+                    runtime.set_error("Malformed MCU info frame", "invalid_response");
+                    return;
+                }
+            },
+            _ => match reply.payload.first().copied() {
+                Some(ack_status::OK) => true,
+                Some(ack_status::USB_NOT_READY) => false,
+                status => {
+                    runtime.set_error(
+                        format!("MCU rejected frame with status {:?}", status),
+                        "protocol_error",
+                    );
+                    return;
+                }
+            },
+        };
+
+        if usb_connected {
+            runtime.set_online();
+        } else {
+            runtime.set_error(
+                "MCU is not connected to the target USB host",
+                "usb_not_ready",
+            );
+        }
+    }
+
+    fn enqueue_command(&self, command: WorkerCommand) -> Result<()> {
+        let guard = self.worker_tx.lock();
+        let Some(sender) = guard.as_ref() else {
+            self.runtime
+                .set_error("MCU worker is not running", "worker_stopped");
+            return Err(backend_error("MCU worker is not running", "worker_stopped"));
+        };
+
+        sender.send(command).map_err(|_| {
+            self.runtime
+                .set_error("MCU worker stopped", "worker_stopped");
+            backend_error("MCU worker stopped", "worker_stopped")
+        })
+    }
+
+    fn send_frame(&self, frame_type: u8, payload: &[u8]) -> Result<()> {
+        self.enqueue_command(WorkerCommand::Frame {
+            frame_type,
+            payload: payload.to_vec(),
+        })
+    }
+
+    fn send_keyboard_report(&self, report: &KeyboardReport) -> Result<()> {
+        self.send_frame(frame_type::KEYBOARD, &report.to_bytes())
+    }
+
+    fn send_mouse_relative(&self, buttons: u8, dx: i8, dy: i8, wheel: i8, pan: i8) -> Result<()> {
+        self.send_frame(
+            frame_type::MOUSE_REL,
+            &[buttons, dx as u8, dy as u8, wheel as u8, pan as u8],
+        )
+    }
+
+    fn send_mouse_absolute(&self, buttons: u8, wheel: i8, pan: i8) -> Result<()> {
+        let (x, y) = *self.last_abs.lock();
+        let [x_lo, x_hi] = x.to_le_bytes();
+        let [y_lo, y_hi] = y.to_le_bytes();
+        self.send_frame(
+            frame_type::MOUSE_ABS,
+            &[buttons, x_lo, x_hi, y_lo, y_hi, wheel as u8, pan as u8],
+        )
+    }
+
+    /// Button/wheel report on whichever mouse mode was used last
+    fn send_mouse_state(&self, buttons: u8, wheel: i8, pan: i8) -> Result<()> {
+        if self.relative_mouse_active.load(Ordering::Relaxed) {
+            self.send_mouse_relative(buttons, 0, 0, wheel, pan)
+        } else {
+            self.send_mouse_absolute(buttons, wheel, pan)
+        }
+    }
+
+    fn worker_reconnect_loop(
+        rx: &mpsc::Receiver<WorkerCommand>,
+        port_path: &str,
+        baud_rate: u32,
+        runtime: &McuRuntimeState,
+    ) -> Option<Link> {
+        loop {
+            match rx.recv_timeout(Duration::from_millis(RECONNECT_DELAY_MS)) {
+                Ok(WorkerCommand::Shutdown) => return None,
+                Ok(_) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return None,
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+            }
+
+            match Self::connect(port_path, baud_rate, runtime) {
+                Ok((link, info)) => {
+                    info!(
+                        "MCU HID bridge reconnected: firmware {}, USB: {}",
+                        info.firmware_version,
+                        if info.usb_connected {
+                            "connected"
+                        } else {
+                            "disconnected"
+                        }
+                    );
+                    runtime.set_online();
+                    return Some(link);
+                }
+                Err(err) => runtime.record(err),
+            }
+        }
+    }
+
+    fn worker_loop(
+        port_path: String,
+        baud_rate: u32,
+        rx: mpsc::Receiver<WorkerCommand>,
+        runtime: Arc<McuRuntimeState>,
+        init_tx: mpsc::Sender<Result<McuInfo>>,
+    ) {
+        runtime.set_initialized(true);
+
+        let mut link = match Self::connect(&port_path, baud_rate, &runtime) {
+            Ok((link, info)) => {
+                info!("MCU serial port opened: {} @ {} baud", port_path, baud_rate);
+                runtime.set_online();
+                let _ = init_tx.send(Ok(info));
+                link
+            }
+            Err(err) => {
+                if let AppError::HidError {
+                    reason, error_code, ..
+                } = &err
+                {
+                    runtime.set_error(reason.clone(), error_code.clone());
+                }
+                let _ = init_tx.send(Err(err));
+                runtime.set_initialized(false);
+                return;
+            }
+        };
+
+        loop {
+            let result = match rx.recv_timeout(Duration::from_millis(PROBE_INTERVAL_MS)) {
+                Ok(WorkerCommand::Frame {
+                    frame_type,
+                    payload,
+                }) => link.xfer(frame_type, &payload, &runtime),
+                Err(mpsc::RecvTimeoutError::Timeout) => link.xfer(frame_type::HELLO, &[], &runtime),
+                Ok(WorkerCommand::Shutdown) | Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+
+            match result {
+                Ok(reply) => Self::apply_reply(&reply, &runtime),
+                Err(err) => {
+                    runtime.record(err);
+                    drop(link);
+                    let Some(new_link) =
+                        Self::worker_reconnect_loop(&rx, &port_path, baud_rate, &runtime)
+                    else {
+                        break;
+                    };
+                    link = new_link;
+                }
+            }
+        }
+
+        runtime.set_offline();
+        runtime.set_initialized(false);
+    }
+}
+
+#[async_trait]
+impl HidBackend for McuBackend {
+    async fn init(&self) -> Result<()> {
+        if self.worker_handle.lock().is_some() {
+            return Ok(());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let (init_tx, init_rx) = mpsc::channel();
+        let port_path = self.port_path.clone();
+        let baud_rate = self.baud_rate;
+        let runtime = self.runtime.clone();
+
+        let handle = thread::Builder::new()
+            .name("mcu-hid-worker".to_string())
+            .spawn(move || {
+                Self::worker_loop(port_path, baud_rate, rx, runtime, init_tx);
+            })
+            .map_err(|e| AppError::Internal(format!("Failed to spawn MCU worker: {}", e)))?;
+
+        match init_rx.recv_timeout(Duration::from_millis(INIT_WAIT_MS)) {
+            Ok(Ok(info)) => {
+                info!(
+                    "MCU HID bridge detected: protocol v{}, firmware {}, USB: {}",
+                    info.protocol_version,
+                    info.firmware_version,
+                    if info.usb_connected {
+                        "connected"
+                    } else {
+                        "disconnected"
+                    }
+                );
+                *self.worker_tx.lock() = Some(tx);
+                *self.worker_handle.lock() = Some(handle);
+                Ok(())
+            }
+            Ok(Err(err)) => {
+                let _ = handle.join();
+                let reason = format!(
+                    "MCU not responding on {} @ {} baud: {}",
+                    self.port_path, self.baud_rate, err
+                );
+                self.runtime.set_error(reason.clone(), "init_failed");
+                Err(AppError::Internal(reason))
+            }
+            Err(_) => {
+                let _ = tx.send(WorkerCommand::Shutdown);
+                let _ = handle.join();
+                self.runtime
+                    .set_error("Timed out waiting for MCU worker init", "init_timeout");
+                Err(AppError::Internal(
+                    "Timed out waiting for MCU initialization".to_string(),
+                ))
+            }
+        }
+    }
+
+    async fn send_keyboard(&self, event: KeyboardEvent) -> Result<()> {
+        let mut state = self.keyboard_state.lock();
+
+        if event.key.is_modifier() {
+            if let Some(bit) = event.key.modifier_bit() {
+                match event.event_type {
+                    KeyEventType::Down => state.modifiers |= bit,
+                    KeyEventType::Up => state.modifiers &= !bit,
+                }
+            }
+        } else {
+            state.modifiers = event.modifiers.to_hid_byte();
+            let usb_key = event.key.to_hid_usage();
+            match event.event_type {
+                KeyEventType::Down => {
+                    state.add_key(usb_key);
+                }
+                KeyEventType::Up => state.remove_key(usb_key),
+            }
+        }
+
+        let report = state.clone();
+        drop(state);
+        self.send_keyboard_report(&report)
+    }
+
+    async fn send_mouse(&self, event: MouseEvent) -> Result<()> {
+        let buttons = self.mouse_buttons.load(Ordering::Relaxed);
+
+        match event.event_type {
+            MouseEventType::Move => {
+                self.relative_mouse_active.store(true, Ordering::Relaxed);
+                let dx = event.x.clamp(-127, 127) as i8;
+                let dy = event.y.clamp(-127, 127) as i8;
+                self.send_mouse_relative(buttons, dx, dy, 0, 0)
+            }
+            MouseEventType::MoveAbs => {
+                self.relative_mouse_active.store(false, Ordering::Relaxed);
+                *self.last_abs.lock() = (
+                    event.x.clamp(0, 32767) as u16,
+                    event.y.clamp(0, 32767) as u16,
+                );
+                self.send_mouse_absolute(buttons, 0, 0)
+            }
+            MouseEventType::Down => match event.button {
+                Some(button) => {
+                    let bit = button.to_hid_bit();
+                    let buttons = self.mouse_buttons.fetch_or(bit, Ordering::Relaxed) | bit;
+                    self.send_mouse_state(buttons, 0, 0)
+                }
+                None => Ok(()),
+            },
+            MouseEventType::Up => match event.button {
+                Some(button) => {
+                    let bit = button.to_hid_bit();
+                    let buttons = self.mouse_buttons.fetch_and(!bit, Ordering::Relaxed) & !bit;
+                    self.send_mouse_state(buttons, 0, 0)
+                }
+                None => Ok(()),
+            },
+            MouseEventType::Scroll => self.send_mouse_state(buttons, event.scroll, event.scroll_x),
+        }
+    }
+
+    async fn send_consumer(&self, event: ConsumerEvent) -> Result<()> {
+        self.send_frame(frame_type::CONSUMER, &event.usage.to_le_bytes())?;
+        self.send_frame(frame_type::CONSUMER, &[0, 0])
+    }
+
+    async fn reset(&self) -> Result<()> {
+        self.keyboard_state.lock().clear();
+        self.mouse_buttons.store(0, Ordering::Relaxed);
+        *self.last_abs.lock() = (0, 0);
+        self.relative_mouse_active.store(false, Ordering::Relaxed);
+        self.send_frame(frame_type::RESET, &[])?;
+
+        info!("MCU HID state reset");
+        Ok(())
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        let sender = self.worker_tx.lock().take();
+        if let Some(sender) = sender {
+            let _ = sender.send(WorkerCommand::Frame {
+                frame_type: frame_type::RESET,
+                payload: Vec::new(),
+            });
+            let _ = sender.send(WorkerCommand::Shutdown);
+        }
+        if let Some(handle) = self.worker_handle.lock().take() {
+            if handle.join().is_err() {
+                warn!("MCU worker panicked");
+            }
+        }
+        self.runtime.set_offline();
+        self.runtime.set_initialized(false);
+        self.runtime.clear_error();
+
+        info!("MCU backend shutdown");
+        Ok(())
+    }
+
+    fn runtime_snapshot(&self) -> HidBackendRuntimeSnapshot {
+        let initialized = self.runtime.initialized.load(Ordering::Relaxed);
+        let mut online = initialized && self.runtime.online.load(Ordering::Relaxed);
+        let mut error = self.runtime.last_error.read().clone();
+
+        if initialized && !self.check_port_exists() {
+            online = false;
+            error = Some((
+                format!("Serial port {} not found", self.port_path),
+                "port_not_found".to_string(),
+            ));
+        }
+
+        HidBackendRuntimeSnapshot {
+            initialized,
+            online,
+            supports_absolute_mouse: true,
+            keyboard_leds_enabled: true,
+            led_state: LedState::from_byte(self.runtime.leds.load(Ordering::Relaxed)),
+            screen_resolution: Some(*self.screen_resolution.read()),
+            device: Some(self.port_path.clone()),
+            error: error.as_ref().map(|(reason, _)| reason.clone()),
+            error_code: error.as_ref().map(|(_, code)| code.clone()),
+        }
+    }
+
+    fn subscribe_runtime(&self) -> watch::Receiver<()> {
+        self.runtime.subscribe()
+    }
+
+    fn set_screen_resolution(&self, width: u32, height: u32) {
+        *self.screen_resolution.write() = (width, height);
+        self.runtime.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::{CanonicalKey, KeyboardModifiers};
+    use std::ffi::CStr;
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::os::fd::FromRawFd;
+
+    #[test]
+    fn test_crc16() {
+        // CRC-16/CCITT-FALSE check value
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+        assert_eq!(crc16(&[]), 0xFFFF);
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frame = Frame::new(frame_type::KEYBOARD, 7, &[0x02, 0, 0x04, 0, 0, 0, 0, 0]);
+        let bytes = frame.encode();
+        assert_eq!(&bytes[..5], &[0xA5, 0x5A, 0x02, 7, 8]);
+        assert_eq!(bytes.len(), FRAME_OVERHEAD + 8);
+
+        let (decoded, consumed) = Frame::decode(&bytes).unwrap();
+        assert_eq!(decoded, frame);
+        assert_eq!(consumed, bytes.len());
+    }
+
+    #[test]
+    fn test_frame_decode_skips_noise_and_bad_crc() {
+        let good = Frame::new(frame_type::ACK, 3, &[ack_status::OK]).encode();
+        let mut corrupt = Frame::new(frame_type::ACK, 2, &[ack_status::OK]).encode();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xFF;
+
+        let mut buffer = vec![0x00, 0xA5, 0x13];
+        buffer.extend_from_slice(&corrupt);
+        buffer.extend_from_slice(&good);
+
+        let (frame, consumed) = Frame::decode(&buffer).unwrap();
+        assert_eq!(frame.seq, 3);
+        assert_eq!(consumed, buffer.len());
+
+        // Incomplete frame waits for more input
+        assert!(Frame::decode(&good[..good.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn test_info_parsing() {
+        let info = McuInfo::from_payload(&[1, 3, 0x01, 0x02]).unwrap();
+        assert_eq!(info.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(info.firmware_version, 3);
+        assert!(info.usb_connected);
+        assert!(LedState::from_byte(info.leds).caps_lock);
+        assert!(McuInfo::from_payload(&[1, 3]).is_none());
+    }
+
+    /// Master side of a pty plus the path of its slave, which the backend opens
+    fn open_pty() -> (File, String) {
+        unsafe {
+            let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(master >= 0, "posix_openpt failed");
+            assert_eq!(libc::grantpt(master), 0);
+            assert_eq!(libc::unlockpt(master), 0);
+            let mut name = [0 as libc::c_char; 64];
+            assert_eq!(libc::ptsname_r(master, name.as_mut_ptr(), name.len()), 0);
+            let path = CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned();
+            (File::from_raw_fd(master), path)
+        }
+    }
+
+    /// Minimal firmware stand-in: NAKs the first keyboard frame, then acks
+    /// everything and reports Caps Lock on after the first accepted key.
+    fn simulate_mcu(mut master: File, stop: Arc<AtomicBool>, frames: mpsc::Sender<Frame>) {
+        let mut pending = Vec::new();
+        let mut nak_sent = false;
+        let mut leds = 0u8;
+        let mut chunk = [0u8; 64];
+
+        while !stop.load(Ordering::Relaxed) {
+            let n = match master.read(&mut chunk) {
+                Ok(n) if n > 0 => n,
+                // EIO until the backend opens the slave side
+                _ => {
+                    thread::sleep(Duration::from_millis(5));
+                    continue;
+                }
+            };
+            pending.extend_from_slice(&chunk[..n]);
+
+            while let Some((frame, consumed)) = Frame::decode(&pending) {
+                pending.drain(..consumed);
+                let mut replies = Vec::new();
+                match frame.frame_type {
+                    frame_type::HELLO => replies.push(Frame::new(
+                        frame_type::INFO,
+                        frame.seq,
+                        &[PROTOCOL_VERSION, 3, 0x01, leds],
+                    )),
+                    frame_type::KEYBOARD if !nak_sent => {
+                        nak_sent = true;
+                        replies.push(Frame::new(
+                            frame_type::ACK,
+                            frame.seq,
+                            &[ack_status::CRC_ERROR],
+                        ));
+                    }
+                    _ => {
+                        replies.push(Frame::new(frame_type::ACK, frame.seq, &[ack_status::OK]));
+                        if frame.frame_type == frame_type::KEYBOARD {
+                            leds = 0x02;
+                            replies.push(Frame::new(frame_type::LEDS, 0, &[leds]));
+                        }
+                        let _ = frames.send(frame);
+                    }
+                }
+                for reply in replies {
+                    let _ = master.write_all(&reply.encode());
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backend_against_pty_stand_in() {
+        let (master, slave_path) = open_pty();
+        let stop = Arc::new(AtomicBool::new(false));
+        let (frames_tx, frames_rx) = mpsc::channel();
+        let sim = {
+            let stop = stop.clone();
+            thread::spawn(move || simulate_mcu(master, stop, frames_tx))
+        };
+
+        let backend = McuBackend::new(&slave_path).unwrap();
+        backend.init().await.unwrap();
+        assert!(backend.runtime_snapshot().online);
+
+        backend
+            .send_keyboard(KeyboardEvent::key_down(
+                CanonicalKey::KeyA,
+                KeyboardModifiers::default(),
+            ))
+            .await
+            .unwrap();
+
+        // Delivered once, after the NAK forced a retransmit
+        let frame = frames_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(frame.frame_type, frame_type::KEYBOARD);
+        assert_eq!(frame.payload, vec![0, 0, 0x04, 0, 0, 0, 0, 0]);
+
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while !backend.runtime_snapshot().led_state.caps_lock {
+            assert!(Instant::now() < deadline, "LED report not applied");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        backend
+            .send_mouse(MouseEvent::move_rel(5, -3))
+            .await
+            .unwrap();
+        let frame = frames_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(frame.frame_type, frame_type::MOUSE_REL);
+        assert_eq!(frame.payload, vec![0, 5, (-3i8) as u8, 0, 0]);
+
+        stop.store(true, Ordering::Relaxed);
+        backend.shutdown().await.unwrap();
+        sim.join().unwrap();
+        assert!(!backend.runtime_snapshot().initialized);
+    }
+}
@@ -0,0 +1,385 @@
+//! Recording HID backend without hardware. Every report is kept, byte for
+//! byte in the OTG gadget layout, in a bounded log that end-to-end tests and
+//! demos can inspect. LED state and backend errors are simulated on request.
+
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use time::OffsetDateTime;
+use tokio::sync::watch;
+use tracing::info;
+
+use super::backend::{HidBackend, HidBackendRuntimeSnapshot};
+use super::types::{
+    ConsumerEvent, KeyEventType, KeyboardEvent, KeyboardReport, MouseEvent, MouseEventType,
+    SystemControlAction,
+};
+use crate::error::{AppError, Result};
+use crate::events::LedState;
+
+/// Reports kept before the oldest ones are dropped
+pub const MOCK_LOG_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockReportKind {
+    Keyboard,
+    MouseRelative,
+    MouseAbsolute,
+    Consumer,
+    SystemControl,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MockReport {
+    /// Increases by one per report and survives log trimming and clearing
+    pub seq: u64,
+    pub kind: MockReportKind,
+    pub data: Vec<u8>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub timestamp: OffsetDateTime,
+}
+
+/// Error every send returns until cleared
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MockFault {
+    pub reason: String,
+    pub error_code: String,
+}
+
+struct ReportLog {
+    reports: VecDeque<MockReport>,
+    next_seq: u64,
+}
+
+pub struct MockBackend {
+    log: Mutex<ReportLog>,
+    keyboard_state: Mutex<KeyboardReport>,
+    mouse_buttons: AtomicU8,
+    initialized: AtomicBool,
+    led_state: RwLock<LedState>,
+    fault: RwLock<Option<MockFault>>,
+    screen_resolution: RwLock<Option<(u32, u32)>>,
+    notify_tx: watch::Sender<()>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        let (notify_tx, _notify_rx) = watch::channel(());
+        Self {
+            log: Mutex::new(ReportLog {
+                reports: VecDeque::with_capacity(MOCK_LOG_CAPACITY),
+                next_seq: 1,
+            }),
+            keyboard_state: Mutex::new(KeyboardReport::default()),
+            mouse_buttons: AtomicU8::new(0),
+            initialized: AtomicBool::new(false),
+            led_state: RwLock::new(LedState::default()),
+            fault: RwLock::new(None),
+            screen_resolution: RwLock::new(None),
+            notify_tx,
+        }
+    }
+
+    /// Logged reports with `seq` greater than `since`, oldest first
+    pub fn reports(&self, since: u64) -> Vec<MockReport> {
+        self.log
+            .lock()
+            .reports
+            .iter()
+            .filter(|report| report.seq > since)
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_reports(&self) {
+        self.log.lock().reports.clear();
+    }
+
+    /// Pretend the target changed its keyboard LEDs
+    pub fn set_led_state(&self, led_state: LedState) {
+        *self.led_state.write() = led_state;
+        let _ = self.notify_tx.send(());
+    }
+
+    /// Make every send fail with `fault` until called with `None`
+    pub fn set_fault(&self, fault: Option<MockFault>) {
+        *self.fault.write() = fault;
+        let _ = self.notify_tx.send(());
+    }
+
+    fn record(&self, kind: MockReportKind, data: &[u8]) -> Result<()> {
+        if let Some(fault) = self.fault.read().clone() {
+            return Err(AppError::HidError {
+                backend: "mock".to_string(),
+                reason: fault.reason,
+                error_code: fault.error_code,
+            });
+        }
+
+        let mut log = self.log.lock();
+        if log.reports.len() >= MOCK_LOG_CAPACITY {
+            log.reports.pop_front();
+        }
+        let seq = log.next_seq;
+        log.next_seq += 1;
+        log.reports.push_back(MockReport {
+            seq,
+            kind,
+            data: data.to_vec(),
+            timestamp: OffsetDateTime::now_utc(),
+        });
+        Ok(())
+    }
+
+    fn send_mouse_relative(&self, buttons: u8, dx: i8, dy: i8, wheel: i8, pan: i8) -> Result<()> {
+        self.record(
+            MockReportKind::MouseRelative,
+            &[buttons, dx as u8, dy as u8, wheel as u8, pan as u8],
+        )
+    }
+
+    fn send_mouse_absolute(&self, x: u16, y: u16) -> Result<()> {
+        let [x_lo, x_hi] = x.to_le_bytes();
+        let [y_lo, y_hi] = y.to_le_bytes();
+        self.record(
+            MockReportKind::MouseAbsolute,
+            &[0, x_lo, x_hi, y_lo, y_hi, 0, 0],
+        )
+    }
+}
+
+impl Default for MockBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl HidBackend for MockBackend {
+    async fn init(&self) -> Result<()> {
+        self.initialized.store(true, Ordering::Relaxed);
+        let _ = self.notify_tx.send(());
+        info!("Mock HID backend initialized");
+        Ok(())
+    }
+
+    async fn send_keyboard(&self, event: KeyboardEvent) -> Result<()> {
+        let mut state = self.keyboard_state.lock();
+
+        if event.key.is_modifier() {
+            if let Some(bit) = event.key.modifier_bit() {
+                match event.event_type {
+                    KeyEventType::Down => state.modifiers |= bit,
+                    KeyEventType::Up => state.modifiers &= !bit,
+                }
+            }
+        } else {
+            state.modifiers = event.modifiers.to_hid_byte();
+            let usb_key = event.key.to_hid_usage();
+            match event.event_type {
+                KeyEventType::Down => {
+                    state.add_key(usb_key);
+                }
+                KeyEventType::Up => state.remove_key(usb_key),
+            }
+        }
+
+        let report = state.to_bytes();
+        drop(state);
+        self.record(MockReportKind::Keyboard, &report)
+    }
+
+    async fn send_mouse(&self, event: MouseEvent) -> Result<()> {
+        let buttons = self.mouse_buttons.load(Ordering::Relaxed);
+
+        // Same split as the OTG gadget: buttons and wheel travel on the relative report
+        match event.event_type {
+            MouseEventType::Move => {
+                let dx = event.x.clamp(-127, 127) as i8;
+                let dy = event.y.clamp(-127, 127) as i8;
+                self.send_mouse_relative(buttons, dx, dy, 0, 0)
+            }
+            MouseEventType::MoveAbs => self.send_mouse_absolute(
+                event.x.clamp(0, 32767) as u16,
+                event.y.clamp(0, 32767) as u16,
+            ),
+            MouseEventType::Down => match event.button {
+                Some(button) => {
+                    let bit = button.to_hid_bit();
+                    let buttons = self.mouse_buttons.fetch_or(bit, Ordering::Relaxed) | bit;
+                    self.send_mouse_relative(buttons, 0, 0, 0, 0)
+                }
+                None => Ok(()),
+            },
+            MouseEventType::Up => match event.button {
+                Some(button) => {
+                    let bit = button.to_hid_bit();
+                    let buttons = self.mouse_buttons.fetch_and(!bit, Ordering::Relaxed) & !bit;
+                    self.send_mouse_relative(buttons, 0, 0, 0, 0)
+                }
+                None => Ok(()),
+            },
+            MouseEventType::Scroll => {
+                self.send_mouse_relative(buttons, 0, 0, event.scroll, event.scroll_x)
+            }
+        }
+    }
+
+    async fn send_consumer(&self, event: ConsumerEvent) -> Result<()> {
+        self.record(MockReportKind::Consumer, &event.usage.to_le_bytes())?;
+        self.record(MockReportKind::Consumer, &[0, 0])
+    }
+
+    async fn send_system_control(&self, action: SystemControlAction) -> Result<()> {
+        self.record(MockReportKind::SystemControl, &[action.report_value()])?;
+        self.record(MockReportKind::SystemControl, &[0])
+    }
+
+    async fn reset(&self) -> Result<()> {
+        self.keyboard_state.lock().clear();
+        self.mouse_buttons.store(0, Ordering::Relaxed);
+        self.record(MockReportKind::Keyboard, &[0; 8])?;
+        self.send_mouse_relative(0, 0, 0, 0, 0)?;
+        self.send_mouse_absolute(0, 0)
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        let _ = self.reset().await;
+        self.initialized.store(false, Ordering::Relaxed);
+        let _ = self.notify_tx.send(());
+        info!("Mock HID backend shutdown");
+        Ok(())
+    }
+
+    fn runtime_snapshot(&self) -> HidBackendRuntimeSnapshot {
+        let initialized = self.initialized.load(Ordering::Relaxed);
+        let fault = self.fault.read().clone();
+
+        HidBackendRuntimeSnapshot {
+            initialized,
+            online: initialized && fault.is_none(),
+            supports_absolute_mouse: true,
+            keyboard_leds_enabled: true,
+            led_state: *self.led_state.read(),
+            screen_resolution: *self.screen_resolution.read(),
+            device: None,
+            error: fault.as_ref().map(|f| f.reason.clone()),
+            error_code: fault.map(|f| f.error_code),
+        }
+    }
+
+    fn subscribe_runtime(&self) -> watch::Receiver<()> {
+        self.notify_tx.subscribe()
+    }
+
+    fn set_screen_resolution(&self, width: u32, height: u32) {
+        *self.screen_resolution.write() = Some((width, height));
+        let _ = self.notify_tx.send(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::{CanonicalKey, KeyboardModifiers, MouseButton};
+
+    #[tokio::test]
+    async fn test_records_exact_reports() {
+        let backend = MockBackend::new();
+        backend.init().await.unwrap();
+
+        let shift = KeyboardModifiers {
+            left_shift: true,
+            ..Default::default()
+        };
+        backend
+            .send_keyboard(KeyboardEvent::key_down(CanonicalKey::KeyA, shift))
+            .await
+            .unwrap();
+        backend
+            .send_mouse(MouseEvent::button_down(MouseButton::Left))
+            .await
+            .unwrap();
+        backend
+            .send_mouse(MouseEvent::move_abs(0x1234, 32767))
+            .await
+            .unwrap();
+        backend
+            .send_consumer(ConsumerEvent { usage: 0x00E9 })
+            .await
+            .unwrap();
+
+        let reports = backend.reports(0);
+        let kinds: Vec<_> = reports.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                MockReportKind::Keyboard,
+                MockReportKind::MouseRelative,
+                MockReportKind::MouseAbsolute,
+                MockReportKind::Consumer,
+                MockReportKind::Consumer,
+            ]
+        );
+        assert_eq!(reports[0].data, vec![0x02, 0, 0x04, 0, 0, 0, 0, 0]);
+        assert_eq!(reports[1].data, vec![0x01, 0, 0, 0, 0]);
+        assert_eq!(reports[2].data, vec![0, 0x34, 0x12, 0xFF, 0x7F, 0, 0]);
+        assert_eq!(reports[3].data, vec![0xE9, 0x00]);
+        assert_eq!(reports[4].data, vec![0, 0]);
+
+        assert_eq!(backend.reports(reports[3].seq).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_log_is_bounded() {
+        let backend = MockBackend::new();
+        for _ in 0..MOCK_LOG_CAPACITY + 5 {
+            backend
+                .send_mouse(MouseEvent::move_rel(1, 0))
+                .await
+                .unwrap();
+        }
+
+        let reports = backend.reports(0);
+        assert_eq!(reports.len(), MOCK_LOG_CAPACITY);
+        assert_eq!(reports[0].seq, 6);
+
+        backend.clear_reports();
+        assert!(backend.reports(0).is_empty());
+        backend
+            .send_mouse(MouseEvent::move_rel(1, 0))
+            .await
+            .unwrap();
+        assert_eq!(backend.reports(0)[0].seq, MOCK_LOG_CAPACITY as u64 + 6);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_fault_and_leds() {
+        let backend = MockBackend::new();
+        backend.init().await.unwrap();
+
+        backend.set_fault(Some(MockFault {
+            reason: "Unplugged".to_string(),
+            error_code: "eshutdown".to_string(),
+        }));
+        assert!(backend
+            .send_mouse(MouseEvent::move_rel(1, 0))
+            .await
+            .is_err());
+        let snapshot = backend.runtime_snapshot();
+        assert!(!snapshot.online);
+        assert_eq!(snapshot.error_code.as_deref(), Some("eshutdown"));
+        assert!(backend.reports(0).is_empty());
+
+        backend.set_fault(None);
+        backend.set_led_state(LedState {
+            caps_lock: true,
+            ..Default::default()
+        });
+        let snapshot = backend.runtime_snapshot();
+        assert!(snapshot.online && snapshot.led_state.caps_lock);
+    }
+}
@@ -1,20 +1,38 @@
-//! HID path: browser (WebSocket or WebRTC DataChannel) → queue → OTG gadget or CH9329.
+//! HID path: browser (WebSocket or WebRTC DataChannel) → queue → OTG gadget, CH9329 or MCU bridge.
 
 pub mod backend;
+pub mod batch;
 pub mod ch9329;
 pub mod consumer;
+pub mod control;
 pub mod datachannel;
+pub mod jiggler;
 pub mod keyboard;
+pub mod layout;
+pub mod macros;
+pub mod mcu;
+pub mod mock;
 pub mod otg;
+pub mod presets;
+pub mod typer;
 pub mod types;
 pub mod websocket;
 
 pub use crate::events::LedState;
 pub use backend::{HidBackend, HidBackendRuntimeSnapshot, HidBackendType};
+pub use batch::BatchStep;
+pub use control::{ControlOwner, HidControl, InputSource, PresenceGuard};
+pub use datachannel::HidChannelEvent;
+pub use jiggler::JigglerStatus;
 pub use keyboard::CanonicalKey;
+pub use layout::KeyboardLayout;
+pub use macros::{HidMacro, HidMacroSummary, MacroEvent, MacroStep, MacroStore};
+pub use mock::{MockBackend, MockFault, MockReport};
+pub use presets::{KeyPreset, KeyPresetInfo};
+pub use typer::{HidJobState, TypeTextRequest};
 pub use types::{
     ConsumerEvent, KeyEventType, KeyboardEvent, KeyboardModifiers, MouseButton, MouseEvent,
-    MouseEventType,
+    MouseEventType, SystemControlAction,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -91,10 +109,11 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
+use crate::config::JigglerConfig;
 use crate::error::{AppError, Result};
-use crate::events::EventBus;
+use crate::events::{EventBus, SystemEvent};
 use crate::otg::OtgService;
 use tokio::sync::mpsc;
 use tokio::sync::Mutex;
@@ -124,6 +143,16 @@ pub struct HidController {
     hid_worker: Mutex<Option<JoinHandle<()>>>,
     runtime_worker: Mutex<Option<JoinHandle<()>>>,
     backend_available: Arc<AtomicBool>,
+    control: Arc<HidControl>,
+    typing: Arc<parking_lot::Mutex<Option<typer::RunningTypeJob>>>,
+    recording: parking_lot::Mutex<Option<macros::Recording>>,
+    playing: Arc<parking_lot::Mutex<Option<macros::RunningMacro>>>,
+    macros: std::sync::OnceLock<MacroStore>,
+    jiggler: jiggler::Jiggler,
+    jiggler_worker: Mutex<Option<JoinHandle<()>>>,
+    mock: RwLock<Option<Arc<mock::MockBackend>>>,
+    ch9329: RwLock<Option<Arc<ch9329::Ch9329Backend>>>,
+    key_presets: parking_lot::RwLock<Vec<KeyPreset>>,
 }
 
 impl HidController {
@@ -144,13 +173,34 @@ impl HidController {
             hid_worker: Mutex::new(None),
             runtime_worker: Mutex::new(None),
             backend_available: Arc::new(AtomicBool::new(false)),
+            control: Arc::new(HidControl::new()),
+            typing: Arc::new(parking_lot::Mutex::new(None)),
+            recording: parking_lot::Mutex::new(None),
+            playing: Arc::new(parking_lot::Mutex::new(None)),
+            macros: std::sync::OnceLock::new(),
+            jiggler: jiggler::Jiggler::new(),
+            jiggler_worker: Mutex::new(None),
+            mock: RwLock::new(None),
+            ch9329: RwLock::new(None),
+            key_presets: parking_lot::RwLock::new(Vec::new()),
         }
     }
 
     pub async fn set_event_bus(&self, events: Arc<EventBus>) {
+        self.control.set_event_bus(events.clone());
         *self.events.write().await = Some(events);
     }
 
+    /// Store used to resolve macros played by ID from a client channel
+    pub fn set_macro_store(&self, store: MacroStore) {
+        let _ = self.macros.set(store);
+    }
+
+    /// Presence list and exclusive control lock
+    pub fn control(&self) -> &Arc<HidControl> {
+        &self.control
+    }
+
     pub async fn init(&self) -> Result<()> {
         let backend_type = self.backend_type.read().await.clone();
         let backend: Arc<dyn HidBackend> = match backend_type {
@@ -175,7 +225,25 @@ impl HidController {
                     "Initializing CH9329 HID backend on {} @ {} baud",
                     port, baud_rate
                 );
-                Arc::new(ch9329::Ch9329Backend::with_baud_rate(port, baud_rate)?)
+                let backend = Arc::new(ch9329::Ch9329Backend::with_baud_rate(port, baud_rate)?);
+                *self.ch9329.write().await = Some(backend.clone());
+                backend
+            }
+            HidBackendType::Mcu {
+                ref port,
+                baud_rate,
+            } => {
+                info!(
+                    "Initializing MCU HID bridge backend on {} @ {} baud",
+                    port, baud_rate
+                );
+                Arc::new(mcu::McuBackend::with_baud_rate(port, baud_rate)?)
+            }
+            HidBackendType::Mock => {
+                info!("Initializing mock HID backend");
+                let backend = Arc::new(mock::MockBackend::new());
+                *self.mock.write().await = Some(backend.clone());
+                backend
             }
             HidBackendType::None => {
                 warn!("HID backend disabled");
@@ -218,6 +286,8 @@ impl HidController {
                 warn!("Error shutting down HID backend: {}", e);
             }
         }
+        *self.mock.write().await = None;
+        *self.ch9329.write().await = None;
         self.backend_available.store(false, Ordering::Release);
         let backend_type = self.backend_type.read().await.clone();
         let mut shutdown_state = HidRuntimeState::from_backend_type(&backend_type);
@@ -239,6 +309,8 @@ impl HidController {
                 "HID backend not available".to_string(),
             ));
         }
+        self.record(|| MacroEvent::Keyboard(event.clone()));
+        self.note_input(None).await;
         self.enqueue_event(QueuedHidEvent::Keyboard(event)).await
     }
 
@@ -248,6 +320,8 @@ impl HidController {
                 "HID backend not available".to_string(),
             ));
         }
+        self.record(|| MacroEvent::Mouse(event.clone()));
+        self.note_input(Some(&event)).await;
 
         if matches!(
             event.event_type,
@@ -265,9 +339,517 @@ impl HidController {
                 "HID backend not available".to_string(),
             ));
         }
+        self.record(|| MacroEvent::Consumer(event.clone()));
+        self.note_input(None).await;
         self.enqueue_event(QueuedHidEvent::Consumer(event)).await
     }
 
+    /// Press and release a power / sleep / wake usage
+    pub async fn send_system_control(&self, action: SystemControlAction) -> Result<()> {
+        if !self.backend_available.load(Ordering::Acquire) {
+            return Err(AppError::BadRequest(
+                "HID backend not available".to_string(),
+            ));
+        }
+        let backend = self
+            .backend
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| AppError::BadRequest("HID backend not available".to_string()))?;
+        info!("Sending system control: {:?}", action);
+        self.note_input(None).await;
+
+        // Sent directly rather than queued so an unsupported backend or a
+        // disabled gadget function is reported to the caller
+        backend.send_system_control(action).await
+    }
+
+    fn record(&self, event: impl FnOnce() -> MacroEvent) {
+        if let Some(recording) = self.recording.lock().as_mut() {
+            recording.push(event());
+        }
+    }
+
+    /// Real input resets the jiggler idle timer
+    async fn note_input(&self, mouse: Option<&MouseEvent>) {
+        if self.jiggler.note_input(mouse) {
+            self.mark_device_info_dirty().await;
+        }
+    }
+
+    async fn mark_device_info_dirty(&self) {
+        if let Some(events) = self.events.read().await.as_ref() {
+            events.mark_device_info_dirty();
+        }
+    }
+
+    pub async fn set_jiggler_config(&self, config: JigglerConfig) {
+        let was = self.jiggler.status();
+        self.jiggler.set_config(config);
+        let now = self.jiggler.status();
+        if was.config.enabled != now.config.enabled || was.active != now.active {
+            info!(
+                "Mouse jiggler {}",
+                if now.config.enabled {
+                    "enabled"
+                } else {
+                    "disabled"
+                }
+            );
+            self.mark_device_info_dirty().await;
+        }
+    }
+
+    pub fn jiggler_status(&self) -> JigglerStatus {
+        self.jiggler.status()
+    }
+
+    /// Start the background task that sends jiggles while input is idle.
+    pub async fn start_jiggler(self: &Arc<Self>) {
+        let weak = Arc::downgrade(self);
+        let handle = tokio::spawn(async move {
+            let mut tick = tokio::time::interval(jiggler::JIGGLER_TICK);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                tick.tick().await;
+                let Some(hid) = weak.upgrade() else {
+                    break;
+                };
+                let (moves, changed) = hid.jiggler.poll(std::time::Instant::now());
+                if changed {
+                    hid.mark_device_info_dirty().await;
+                }
+                let Some(moves) = moves else {
+                    continue;
+                };
+                if !hid.backend_available.load(Ordering::Acquire) {
+                    continue;
+                }
+                // Bypasses send_mouse so jiggles neither reset the idle timer
+                // nor end up in a macro recording
+                for event in moves {
+                    if let Err(e) = hid.enqueue_event(QueuedHidEvent::Mouse(event)).await {
+                        debug!("Jiggle failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        if let Some(old) = self.jiggler_worker.lock().await.replace(handle) {
+            old.abort();
+        }
+    }
+
+    /// Start capturing every keyboard, mouse and consumer event sent.
+    pub fn start_recording(&self) -> Result<()> {
+        let mut recording = self.recording.lock();
+        if recording.is_some() {
+            return Err(AppError::BadRequest(
+                "A macro is already being recorded".to_string(),
+            ));
+        }
+        *recording = Some(macros::Recording::new());
+        info!("Macro recording started");
+        Ok(())
+    }
+
+    /// Stop recording and return the captured steps.
+    pub fn stop_recording(&self) -> Result<Vec<MacroStep>> {
+        let recording = self
+            .recording
+            .lock()
+            .take()
+            .ok_or_else(|| AppError::BadRequest("No macro is being recorded".to_string()))?;
+        info!("Macro recording stopped after {} steps", recording.len());
+        Ok(recording.into_steps())
+    }
+
+    /// Number of steps captured so far, if recording
+    pub fn recording_steps(&self) -> Option<usize> {
+        self.recording.lock().as_ref().map(|r| r.len())
+    }
+
+    /// Look up a stored macro and play it.
+    pub async fn play_macro_by_id(
+        self: &Arc<Self>,
+        owner_id: &str,
+        macro_id: &str,
+        speed: f64,
+    ) -> Result<String> {
+        let store = self
+            .macros
+            .get()
+            .ok_or_else(|| AppError::Internal("Macro store not available".to_string()))?;
+        let hid_macro = store
+            .get(macro_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Macro not found".to_string()))?;
+        self.play_macro(owner_id, hid_macro, speed)
+    }
+
+    /// Replay `hid_macro` in the background with its delays divided by
+    /// `speed`, returning the run id. Progress is published as
+    /// `hid.macro_progress` events.
+    pub fn play_macro(
+        self: &Arc<Self>,
+        owner_id: &str,
+        hid_macro: HidMacro,
+        speed: f64,
+    ) -> Result<String> {
+        let speed = macros::validate_speed(speed)?;
+        if !self.backend_available.load(Ordering::Acquire) {
+            return Err(AppError::BadRequest(
+                "HID backend not available".to_string(),
+            ));
+        }
+
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let abort = tokio_util::sync::CancellationToken::new();
+        {
+            let mut playing = self.playing.lock();
+            if playing.is_some() {
+                return Err(AppError::BadRequest(
+                    "Another macro is already playing".to_string(),
+                ));
+            }
+            *playing = Some(macros::RunningMacro {
+                id: run_id.clone(),
+                abort: abort.clone(),
+            });
+        }
+
+        info!(
+            "Playing macro '{}' ({} steps, {}x speed)",
+            hid_macro.name,
+            hid_macro.steps.len(),
+            speed
+        );
+        let hid = self.clone();
+        let owner_id = owner_id.to_string();
+        let id = run_id.clone();
+        tokio::spawn(async move {
+            let total = hid_macro.steps.len();
+            let mut played = 0;
+            let mut outcome = Ok(HidJobState::Completed);
+            hid.publish_macro_progress(&id, &hid_macro, played, HidJobState::Running, None)
+                .await;
+
+            for step in &hid_macro.steps {
+                let delay = Duration::from_secs_f64(step.delay_ms as f64 / 1000.0 / speed);
+                if !delay.is_zero() {
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = abort.cancelled() => {}
+                    }
+                }
+                if abort.is_cancelled() {
+                    outcome = Ok(HidJobState::Cancelled);
+                    break;
+                }
+                if let Err(e) = hid.send_input(&owner_id, step.event.clone().into()).await {
+                    outcome = Err(e.to_string());
+                    break;
+                }
+                played += 1;
+            }
+
+            let (state, error) = match outcome {
+                Ok(state) => (state, None),
+                Err(e) => {
+                    warn!("Macro '{}' failed: {}", hid_macro.name, e);
+                    (HidJobState::Failed, Some(e))
+                }
+            };
+            if state != HidJobState::Completed {
+                // Don't leave keys or buttons held half-way through, unless
+                // another owner took control and the held state is theirs
+                if let Err(e) = hid.reset_as(&owner_id).await {
+                    warn!("Failed to reset HID after macro: {}", e);
+                }
+            }
+            hid.playing.lock().take_if(|run| run.id == id);
+            info!(
+                "Macro '{}' {} after {}/{} steps",
+                hid_macro.name, state, played, total
+            );
+            hid.publish_macro_progress(&id, &hid_macro, played, state, error)
+                .await;
+        });
+
+        Ok(run_id)
+    }
+
+    /// Run `steps` in order, waiting out their delays, and return the number
+    /// of events sent. The batch runs in its own task so a dropped request
+    /// does not stop it half-way; with `release_on_error` a failure resets
+    /// the HID state so nothing stays held, unless the failure is that
+    /// another owner holds control.
+    pub async fn run_batch(
+        self: &Arc<Self>,
+        owner_id: &str,
+        steps: &[BatchStep],
+        release_on_error: bool,
+    ) -> Result<usize> {
+        self.spawn_batch(owner_id, steps, release_on_error)?
+            .await
+            .map_err(|e| AppError::Internal(format!("HID batch task failed: {}", e)))?
+    }
+
+    fn spawn_batch(
+        self: &Arc<Self>,
+        owner_id: &str,
+        steps: &[BatchStep],
+        release_on_error: bool,
+    ) -> Result<JoinHandle<Result<usize>>> {
+        let plan = batch::plan(steps)?;
+        let hid = self.clone();
+        let owner_id = owner_id.to_string();
+        Ok(tokio::spawn(async move {
+            let total = plan.len();
+            for (index, step) in plan.into_iter().enumerate() {
+                if !step.delay.is_zero() {
+                    tokio::time::sleep(step.delay).await;
+                }
+                if let Err(e) = hid.send_input(&owner_id, step.event.into()).await {
+                    warn!("HID batch failed at event {}/{}: {}", index + 1, total, e);
+                    if release_on_error && hid.control.accepts(&owner_id) {
+                        if let Err(e) = hid.reset().await {
+                            warn!("Failed to reset HID after batch: {}", e);
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+            Ok(total)
+        }))
+    }
+
+    /// User presets from the HID config, on top of the built-ins
+    pub fn set_key_presets(&self, presets: Vec<KeyPreset>) {
+        *self.key_presets.write() = presets;
+    }
+
+    pub fn key_presets(&self) -> Vec<KeyPresetInfo> {
+        presets::merged_presets(&self.key_presets.read())
+    }
+
+    fn key_preset(&self, preset_id: &str) -> Result<KeyPreset> {
+        presets::find_preset(&self.key_presets.read(), preset_id)
+            .ok_or_else(|| AppError::NotFound(format!("Key preset '{}' not found", preset_id)))
+    }
+
+    /// Run the preset `preset_id` to completion, releasing everything if it fails.
+    pub async fn run_preset(self: &Arc<Self>, owner_id: &str, preset_id: &str) -> Result<usize> {
+        let preset = self.key_preset(preset_id)?;
+        info!("Running key preset '{}'", preset.name);
+        self.run_batch(owner_id, &preset.steps, true).await
+    }
+
+    /// Abort the playing macro. Returns whether one was playing.
+    pub fn abort_macro(&self) -> bool {
+        match self.playing.lock().as_ref() {
+            Some(run) => {
+                run.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn publish_macro_progress(
+        &self,
+        run_id: &str,
+        hid_macro: &HidMacro,
+        played: usize,
+        state: HidJobState,
+        error: Option<String>,
+    ) {
+        if let Some(events) = self.events.read().await.as_ref() {
+            events.publish(SystemEvent::HidMacroProgress {
+                run_id: run_id.to_string(),
+                macro_id: hid_macro.id.clone(),
+                name: hid_macro.name.clone(),
+                played,
+                total: hid_macro.steps.len(),
+                state: state.to_string(),
+                error,
+            });
+        }
+    }
+
+    /// Forward input from a remote client, dropping it while another owner
+    /// holds control.
+    pub async fn send_input(
+        self: &Arc<Self>,
+        owner_id: &str,
+        event: HidChannelEvent,
+    ) -> Result<()> {
+        self.ensure_control(owner_id)?;
+        match event {
+            HidChannelEvent::Keyboard(kb) => self.send_keyboard(kb).await,
+            HidChannelEvent::Mouse(ms) => self.send_mouse(ms).await,
+            HidChannelEvent::Consumer(cc) => self.send_consumer(cc).await,
+            HidChannelEvent::SystemControl(action) => self.send_system_control(action).await,
+            HidChannelEvent::TypeText(req) => self.type_text(owner_id, req).map(|_| ()),
+            HidChannelEvent::CancelType => {
+                self.cancel_typing();
+                Ok(())
+            }
+            HidChannelEvent::PlayMacro { macro_id, speed } => self
+                .play_macro_by_id(owner_id, &macro_id, speed)
+                .await
+                .map(|_| ()),
+            HidChannelEvent::AbortMacro => {
+                self.abort_macro();
+                Ok(())
+            }
+            HidChannelEvent::RunPreset { preset_id } => {
+                // Sequences like REISUB take seconds; don't hold up the channel
+                let preset = self.key_preset(&preset_id)?;
+                info!("Running key preset '{}'", preset.name);
+                self.spawn_batch(owner_id, &preset.steps, true).map(|_| ())
+            }
+        }
+    }
+
+    /// Start typing `req.text` in the background and return the job id.
+    /// Only one job runs at a time; progress is published as
+    /// `hid.type_progress` events.
+    pub fn type_text(self: &Arc<Self>, owner_id: &str, req: TypeTextRequest) -> Result<String> {
+        let delay = typer::validate_request(&req)?;
+        let plan = typer::plan_text(&req.text, req.layout)?;
+        if !self.backend_available.load(Ordering::Acquire) {
+            return Err(AppError::BadRequest(
+                "HID backend not available".to_string(),
+            ));
+        }
+
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let cancel = Arc::new(AtomicBool::new(false));
+        {
+            let mut typing = self.typing.lock();
+            if typing.is_some() {
+                return Err(AppError::BadRequest(
+                    "Another text is already being typed".to_string(),
+                ));
+            }
+            *typing = Some(typer::RunningTypeJob {
+                id: job_id.clone(),
+                cancel: cancel.clone(),
+            });
+        }
+
+        info!(
+            "Typing {} characters ({:?} layout, {} ms delay)",
+            plan.len(),
+            req.layout,
+            req.delay_ms
+        );
+        let hid = self.clone();
+        let owner_id = owner_id.to_string();
+        let id = job_id.clone();
+        tokio::spawn(async move {
+            let total = plan.len();
+            let mut typed = 0;
+            let mut outcome = Ok(HidJobState::Completed);
+            hid.publish_type_progress(&id, typed, total, HidJobState::Running, None)
+                .await;
+
+            'chars: for strokes in plan {
+                for stroke in strokes {
+                    if cancel.load(Ordering::Acquire) {
+                        outcome = Ok(HidJobState::Cancelled);
+                        break 'chars;
+                    }
+                    let press = hid
+                        .send_input(&owner_id, HidChannelEvent::Keyboard(stroke.press()))
+                        .await;
+                    let release = hid
+                        .send_input(&owner_id, HidChannelEvent::Keyboard(stroke.release()))
+                        .await;
+                    if let Err(e) = press.and(release) {
+                        outcome = Err(e.to_string());
+                        break 'chars;
+                    }
+                    if !delay.is_zero() {
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+                typed += 1;
+                if typed % typer::PROGRESS_INTERVAL == 0 && typed < total {
+                    hid.publish_type_progress(&id, typed, total, HidJobState::Running, None)
+                        .await;
+                }
+            }
+
+            let (state, error) = match outcome {
+                Ok(state) => (state, None),
+                Err(e) => {
+                    warn!("Type job {} failed: {}", id, e);
+                    (HidJobState::Failed, Some(e))
+                }
+            };
+            hid.typing.lock().take_if(|job| job.id == id);
+            info!(
+                "Type job {} {} after {}/{} characters",
+                id, state, typed, total
+            );
+            hid.publish_type_progress(&id, typed, total, state, error)
+                .await;
+        });
+
+        Ok(job_id)
+    }
+
+    /// Stop the running type job. Returns whether one was running.
+    pub fn cancel_typing(&self) -> bool {
+        match self.typing.lock().as_ref() {
+            Some(job) => {
+                job.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    async fn publish_type_progress(
+        &self,
+        job_id: &str,
+        typed: usize,
+        total: usize,
+        state: HidJobState,
+        error: Option<String>,
+    ) {
+        if let Some(events) = self.events.read().await.as_ref() {
+            events.publish(SystemEvent::HidTypeProgress {
+                job_id: job_id.to_string(),
+                typed,
+                total,
+                state: state.to_string(),
+                error,
+            });
+        }
+    }
+
+    fn ensure_control(&self, owner_id: &str) -> Result<()> {
+        if self.control.accepts(owner_id) {
+            Ok(())
+        } else {
+            Err(AppError::BadRequest(
+                "HID control is held by another user".to_string(),
+            ))
+        }
+    }
+
+    /// Release every key and button on behalf of `owner_id`, refused while
+    /// another owner holds control.
+    pub async fn reset_as(&self, owner_id: &str) -> Result<()> {
+        self.ensure_control(owner_id)?;
+        self.reset().await
+    }
+
     pub async fn reset(&self) -> Result<()> {
         if !self.backend_available.load(Ordering::Acquire) {
             return Ok(());
@@ -287,6 +869,30 @@ impl HidController {
         self.runtime_state.read().await.clone()
     }
 
+    /// The recording backend, while `HidBackendType::Mock` is active
+    pub async fn mock_backend(&self) -> Option<Arc<mock::MockBackend>> {
+        self.mock.read().await.clone()
+    }
+
+    /// The CH9329 backend, while `HidBackendType::Ch9329` is active
+    pub async fn ch9329_backend(&self) -> Option<Arc<ch9329::Ch9329Backend>> {
+        self.ch9329.read().await.clone()
+    }
+
+    /// Switch the CH9329 serial port rate. The backend type follows the rate
+    /// the chip answers at afterwards, also when the switch failed.
+    pub async fn switch_ch9329_baud_rate(&self, baud_rate: u32) -> Result<ch9329::ChipInfo> {
+        let backend = self
+            .ch9329_backend()
+            .await
+            .ok_or_else(|| AppError::BadRequest("CH9329 backend is not active".to_string()))?;
+        let result = backend.switch_baud_rate(baud_rate).await;
+        if let HidBackendType::Ch9329 { baud_rate, .. } = &mut *self.backend_type.write().await {
+            *baud_rate = backend.baud_rate();
+        }
+        result
+    }
+
     pub async fn reload(&self, new_backend_type: HidBackendType) -> Result<()> {
         info!("Reloading HID backend: {:?}", new_backend_type);
         self.backend_available.store(false, Ordering::Release);
@@ -297,6 +903,8 @@ impl HidController {
                 warn!("Error shutting down old HID backend: {}", e);
             }
         }
+        *self.mock.write().await = None;
+        *self.ch9329.write().await = None;
 
         let new_backend: Option<Arc<dyn HidBackend>> = match new_backend_type {
             HidBackendType::Otg => {
@@ -350,7 +958,10 @@ impl HidController {
                     Ok(b) => {
                         let backend = Arc::new(b);
                         match backend.init().await {
-                            Ok(_) => Some(backend),
+                            Ok(_) => {
+                                *self.ch9329.write().await = Some(backend.clone());
+                                Some(backend)
+                            }
                             Err(e) => {
                                 warn!("Failed to initialize CH9329 backend: {}", e);
                                 None
@@ -363,6 +974,45 @@ impl HidController {
                     }
                 }
             }
+            HidBackendType::Mcu {
+                ref port,
+                baud_rate,
+            } => {
+                info!(
+                    "Initializing MCU HID bridge backend on {} @ {} baud",
+                    port, baud_rate
+                );
+                match mcu::McuBackend::with_baud_rate(port, baud_rate) {
+                    Ok(b) => {
+                        let backend = Arc::new(b);
+                        match backend.init().await {
+                            Ok(_) => Some(backend),
+                            Err(e) => {
+                                warn!("Failed to initialize MCU backend: {}", e);
+                                None
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Failed to create MCU backend: {}", e);
+                        None
+                    }
+                }
+            }
+            HidBackendType::Mock => {
+                info!("Initializing mock HID backend");
+                let backend = Arc::new(mock::MockBackend::new());
+                match backend.init().await {
+                    Ok(_) => {
+                        *self.mock.write().await = Some(backend.clone());
+                        Some(backend)
+                    }
+                    Err(e) => {
+                        warn!("Failed to initialize mock backend: {}", e);
+                        None
+                    }
+                }
+            }
             HidBackendType::None => {
                 warn!("HID backend disabled");
                 None
@@ -595,7 +1245,9 @@ async fn process_hid_event(
 
 fn device_for_backend_type(backend_type: &HidBackendType) -> Option<String> {
     match backend_type {
-        HidBackendType::Ch9329 { port, .. } => Some(port.clone()),
+        HidBackendType::Ch9329 { port, .. } | HidBackendType::Mcu { port, .. } => {
+            Some(port.clone())
+        }
         _ => None,
     }
 }
@@ -623,3 +1275,131 @@ async fn apply_runtime_state(
         events.mark_device_info_dirty();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use batch::{BatchAction, BatchTarget};
+
+    fn owner(id: &str) -> ControlOwner {
+        ControlOwner {
+            id: id.to_string(),
+            name: id.to_string(),
+        }
+    }
+
+    async fn mock_controller() -> (Arc<HidController>, Arc<MockBackend>) {
+        let hid = Arc::new(HidController::new(HidBackendType::Mock, None));
+        hid.init().await.unwrap();
+        let mock = hid.mock_backend().await.unwrap();
+        (hid, mock)
+    }
+
+    /// Reports recorded once the event queue has drained
+    async fn settled_reports(mock: &MockBackend) -> Vec<MockReport> {
+        let mut count = usize::MAX;
+        loop {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let reports = mock.reports(0);
+            if reports.len() == count {
+                return reports;
+            }
+            count = reports.len();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reset_respects_control_lock() {
+        let (hid, mock) = mock_controller().await;
+        hid.control().acquire(owner("alice"), false).unwrap();
+
+        assert!(hid.reset_as("bob").await.is_err());
+        assert!(settled_reports(&mock).await.is_empty());
+
+        hid.reset_as("alice").await.unwrap();
+        let reports = settled_reports(&mock).await;
+        assert!(reports
+            .iter()
+            .any(|r| r.kind == mock::MockReportKind::Keyboard && r.data == [0; 8]));
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_keeps_other_owners_keys() {
+        let (hid, mock) = mock_controller().await;
+        hid.control().acquire(owner("alice"), false).unwrap();
+        hid.send_input(
+            "alice",
+            HidChannelEvent::Keyboard(KeyboardEvent::key_down(
+                CanonicalKey::KeyA,
+                KeyboardModifiers::default(),
+            )),
+        )
+        .await
+        .unwrap();
+        let held = settled_reports(&mock).await.len();
+
+        let steps = [BatchStep {
+            delay_ms: 0,
+            action: BatchAction::Press {
+                target: BatchTarget::Key {
+                    key: CanonicalKey::KeyB,
+                },
+            },
+        }];
+        assert!(hid.run_batch("bob", &steps, true).await.is_err());
+        assert_eq!(settled_reports(&mock).await.len(), held);
+    }
+
+    #[tokio::test]
+    async fn test_typed_text_leaves_no_modifier_held() {
+        let (hid, mock) = mock_controller().await;
+        hid.type_text(
+            "alice",
+            TypeTextRequest {
+                text: "Password!".to_string(),
+                layout: KeyboardLayout::Us,
+                delay_ms: 0,
+            },
+        )
+        .unwrap();
+
+        let reports = settled_reports(&mock).await;
+        let last = reports
+            .iter()
+            .rev()
+            .find(|r| r.kind == mock::MockReportKind::Keyboard)
+            .unwrap();
+        assert_eq!(last.data, [0; 8]);
+    }
+
+    #[tokio::test]
+    async fn test_abort_interrupts_macro_delay() {
+        let (hid, _mock) = mock_controller().await;
+        let slow = HidMacro {
+            id: "slow".to_string(),
+            name: "slow".to_string(),
+            steps: vec![macros::MacroStep {
+                delay_ms: macros::MAX_STEP_DELAY_MS,
+                event: MacroEvent::Keyboard(KeyboardEvent::key_down(
+                    CanonicalKey::KeyA,
+                    KeyboardModifiers::default(),
+                )),
+            }],
+            created_at: time::OffsetDateTime::now_utc(),
+        };
+
+        hid.play_macro("alice", slow.clone(), 1.0).unwrap();
+        assert!(hid.play_macro("alice", slow.clone(), 1.0).is_err());
+        assert!(hid.abort_macro());
+
+        // The aborted run stops sleeping right away and frees the slot
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while hid.play_macro("alice", slow.clone(), 1.0).is_err() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        hid.abort_macro();
+    }
+}
@@ -1,5 +1,6 @@
 //! Linux gadget HID: `/dev/hidg*` opened from [`crate::otg::OtgService`].
-//! Typical nodes: hidg0 keyboard, hidg1 relative mouse, hidg2 absolute, hidg3 consumer control.
+//! Typical nodes: hidg0 keyboard (optionally followed by its NKRO companion), then relative
+//! mouse, absolute mouse, consumer control and optionally system control.
 //!
 //! Polled timed writes (JetKVM-style). Treat `ESHUTDOWN` (108) by closing handles and reopening; keep fd on `EAGAIN` (11). Host/gadget teardown during MSD resembles PiKVM. <https://github.com/raspberrypi/linux/issues/4373>
 
@@ -21,6 +22,7 @@ use tracing::{debug, info, trace, warn};
 use super::backend::{HidBackend, HidBackendRuntimeSnapshot};
 use super::types::{
     ConsumerEvent, KeyEventType, KeyboardEvent, KeyboardReport, MouseEvent, MouseEventType,
+    SystemControlAction, NKRO_BITMAP_LEN,
 };
 use crate::error::{AppError, Result};
 use crate::events::LedState;
@@ -32,6 +34,7 @@ enum DeviceType {
     MouseRelative,
     MouseAbsolute,
     ConsumerControl,
+    SystemControl,
 }
 
 impl LedState {
@@ -69,13 +72,19 @@ impl LedState {
 /// Opens `/dev/hidg*` nodes provisioned by `OtgService`; gadget lifecycle is not handled here.
 pub struct OtgBackend {
     keyboard_path: Option<PathBuf>,
+    keyboard_nkro_path: Option<PathBuf>,
     mouse_rel_path: Option<PathBuf>,
     mouse_abs_path: Option<PathBuf>,
     consumer_path: Option<PathBuf>,
+    system_path: Option<PathBuf>,
     keyboard_dev: Mutex<Option<File>>,
+    keyboard_nkro_dev: Mutex<Option<File>>,
+    /// Last overflow bitmap the host accepted on the NKRO interface
+    keyboard_nkro_sent: Mutex<[u8; NKRO_BITMAP_LEN]>,
     mouse_rel_dev: Mutex<Option<File>>,
     mouse_abs_dev: Mutex<Option<File>>,
     consumer_dev: Mutex<Option<File>>,
+    system_dev: Mutex<Option<File>>,
     keyboard_leds_enabled: bool,
     keyboard_state: Mutex<KeyboardReport>,
     mouse_buttons: AtomicU8,
@@ -101,13 +110,18 @@ impl OtgBackend {
         let (runtime_notify_tx, _runtime_notify_rx) = watch::channel(());
         Ok(Self {
             keyboard_path: paths.keyboard,
+            keyboard_nkro_path: paths.keyboard_nkro,
             mouse_rel_path: paths.mouse_relative,
             mouse_abs_path: paths.mouse_absolute,
             consumer_path: paths.consumer,
+            system_path: paths.system_control,
             keyboard_dev: Mutex::new(None),
+            keyboard_nkro_dev: Mutex::new(None),
+            keyboard_nkro_sent: Mutex::new([0; NKRO_BITMAP_LEN]),
             mouse_rel_dev: Mutex::new(None),
             mouse_abs_dev: Mutex::new(None),
             consumer_dev: Mutex::new(None),
+            system_dev: Mutex::new(None),
             keyboard_leds_enabled: paths.keyboard_leds_enabled,
             keyboard_state: Mutex::new(KeyboardReport::default()),
             mouse_buttons: AtomicU8::new(0),
@@ -264,6 +278,7 @@ impl OtgBackend {
             DeviceType::MouseRelative => (&self.mouse_rel_path, &self.mouse_rel_dev),
             DeviceType::MouseAbsolute => (&self.mouse_abs_path, &self.mouse_abs_dev),
             DeviceType::ConsumerControl => (&self.consumer_path, &self.consumer_dev),
+            DeviceType::SystemControl => (&self.system_path, &self.system_dev),
         };
 
         let path = match path_opt {
@@ -359,9 +374,11 @@ impl OtgBackend {
 
     pub fn check_devices_exist(&self) -> bool {
         self.keyboard_path.as_ref().is_none_or(|p| p.exists())
+            && self.keyboard_nkro_path.as_ref().is_none_or(|p| p.exists())
             && self.mouse_rel_path.as_ref().is_none_or(|p| p.exists())
             && self.mouse_abs_path.as_ref().is_none_or(|p| p.exists())
             && self.consumer_path.as_ref().is_none_or(|p| p.exists())
+            && self.system_path.as_ref().is_none_or(|p| p.exists())
     }
 
     pub fn get_missing_devices(&self) -> Vec<String> {
@@ -384,6 +401,45 @@ impl OtgBackend {
         missing
     }
 
+    /// Keys beyond the six boot slots go to the NKRO interface. Hosts in boot
+    /// protocol (BIOS) never poll it, so dropped or failed writes here are not
+    /// keyboard errors; an unaccepted bitmap is retried with the next report.
+    fn send_keyboard_overflow(&self, overflow: &[u8; NKRO_BITMAP_LEN]) {
+        let Some(path) = self.keyboard_nkro_path.as_ref() else {
+            return;
+        };
+
+        let mut sent = self.keyboard_nkro_sent.lock();
+        if *sent == *overflow {
+            return;
+        }
+
+        let mut dev = self.keyboard_nkro_dev.lock();
+        if dev.is_none() {
+            match Self::open_device(path) {
+                Ok(file) => *dev = Some(file),
+                Err(e) => {
+                    debug!("NKRO keyboard device not available: {}", e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(ref mut file) = *dev {
+            match self.write_with_timeout(file, overflow) {
+                Ok(true) => {
+                    *sent = *overflow;
+                    debug!("Sent NKRO keyboard report: {:02X?}", overflow);
+                }
+                Ok(false) => trace!("NKRO keyboard write timeout, dropped"),
+                Err(e) => {
+                    debug!("NKRO keyboard write error: {}", e);
+                    *dev = None;
+                }
+            }
+        }
+    }
+
     fn send_keyboard_report(&self, report: &KeyboardReport) -> Result<()> {
         if self.keyboard_path.is_none() {
             return Ok(());
@@ -391,6 +447,8 @@ impl OtgBackend {
 
         self.ensure_device(DeviceType::Keyboard)?;
 
+        self.send_keyboard_overflow(&report.overflow);
+
         let mut dev = self.keyboard_dev.lock();
         if let Some(ref mut file) = *dev {
             let data = report.to_bytes();
@@ -450,7 +508,14 @@ impl OtgBackend {
         }
     }
 
-    fn send_mouse_report_relative(&self, buttons: u8, dx: i8, dy: i8, wheel: i8) -> Result<()> {
+    fn send_mouse_report_relative(
+        &self,
+        buttons: u8,
+        dx: i8,
+        dy: i8,
+        wheel: i8,
+        pan: i8,
+    ) -> Result<()> {
         if self.mouse_rel_path.is_none() {
             return Ok(());
         }
@@ -459,7 +524,7 @@ impl OtgBackend {
 
         let mut dev = self.mouse_rel_dev.lock();
         if let Some(ref mut file) = *dev {
-            let data = [buttons, dx as u8, dy as u8, wheel as u8];
+            let data = [buttons, dx as u8, dy as u8, wheel as u8, pan as u8];
             match self.write_with_timeout(file, &data) {
                 Ok(true) => {
                     self.mark_online();
@@ -510,7 +575,14 @@ impl OtgBackend {
         }
     }
 
-    fn send_mouse_report_absolute(&self, buttons: u8, x: u16, y: u16, wheel: i8) -> Result<()> {
+    fn send_mouse_report_absolute(
+        &self,
+        buttons: u8,
+        x: u16,
+        y: u16,
+        wheel: i8,
+        pan: i8,
+    ) -> Result<()> {
         if self.mouse_abs_path.is_none() {
             return Ok(());
         }
@@ -526,6 +598,7 @@ impl OtgBackend {
                 (y & 0xFF) as u8,
                 (y >> 8) as u8,
                 wheel as u8,
+                pan as u8,
             ];
             match self.write_with_timeout(file, &data) {
                 Ok(true) => {
@@ -636,6 +709,54 @@ impl OtgBackend {
         }
     }
 
+    /// Press and release a System Control usage
+    fn send_system_control_report(&self, action: SystemControlAction) -> Result<()> {
+        if self.system_path.is_none() {
+            return Err(AppError::BadRequest(
+                "System control HID function is not enabled".to_string(),
+            ));
+        }
+
+        self.ensure_device(DeviceType::SystemControl)?;
+
+        let mut dev = self.system_dev.lock();
+        let Some(ref mut file) = *dev else {
+            return Err(AppError::HidError {
+                backend: "otg".to_string(),
+                reason: "System control device not opened".to_string(),
+                error_code: "not_opened".to_string(),
+            });
+        };
+
+        match self.write_with_timeout(file, &[action.report_value()]) {
+            Ok(true) => {
+                let _ = self.write_with_timeout(file, &[0]);
+                self.mark_online();
+                self.reset_error_count();
+                Ok(())
+            }
+            Ok(false) => Err(AppError::HidError {
+                backend: "otg".to_string(),
+                reason: "System control write timed out".to_string(),
+                error_code: "timeout".to_string(),
+            }),
+            Err(e) => {
+                if e.raw_os_error() == Some(108) {
+                    *dev = None;
+                }
+                warn!("System control write error: {}", e);
+                self.record_error(
+                    format!("Failed to write system control report: {}", e),
+                    Self::io_error_code(&e),
+                );
+                Err(Self::io_error_to_hid_error(
+                    e,
+                    "Failed to write system control report",
+                ))
+            }
+        }
+    }
+
     pub fn send_consumer(&self, event: ConsumerEvent) -> Result<()> {
         self.send_consumer_report(event.usage)
     }
@@ -842,6 +963,9 @@ impl HidBackend for OtgBackend {
         if let Some(ref path) = self.keyboard_path {
             device_paths.push(path.clone());
         }
+        if let Some(ref path) = self.keyboard_nkro_path {
+            device_paths.push(path.clone());
+        }
         if let Some(ref path) = self.mouse_rel_path {
             device_paths.push(path.clone());
         }
@@ -851,6 +975,9 @@ impl HidBackend for OtgBackend {
         if let Some(ref path) = self.consumer_path {
             device_paths.push(path.clone());
         }
+        if let Some(ref path) = self.system_path {
+            device_paths.push(path.clone());
+        }
 
         if device_paths.is_empty() {
             return Err(AppError::Internal(
@@ -872,6 +999,16 @@ impl HidBackend for OtgBackend {
             }
         }
 
+        if let Some(ref path) = self.keyboard_nkro_path {
+            if path.exists() {
+                let file = Self::open_device(path)?;
+                *self.keyboard_nkro_dev.lock() = Some(file);
+                debug!("NKRO keyboard device opened: {}", path.display());
+            } else {
+                warn!("NKRO keyboard device not found: {}", path.display());
+            }
+        }
+
         if let Some(ref path) = self.mouse_rel_path {
             if path.exists() {
                 let file = Self::open_device(path)?;
@@ -902,6 +1039,16 @@ impl HidBackend for OtgBackend {
             }
         }
 
+        if let Some(ref path) = self.system_path {
+            if path.exists() {
+                let file = Self::open_device(path)?;
+                *self.system_dev.lock() = Some(file);
+                debug!("System control device opened: {}", path.display());
+            } else {
+                debug!("System control device not found: {}", path.display());
+            }
+        }
+
         self.initialized.store(true, Ordering::Relaxed);
         self.notify_runtime_changed();
         self.start_runtime_worker();
@@ -957,30 +1104,30 @@ impl HidBackend for OtgBackend {
             MouseEventType::Move => {
                 let dx = event.x.clamp(-127, 127) as i8;
                 let dy = event.y.clamp(-127, 127) as i8;
-                self.send_mouse_report_relative(buttons, dx, dy, 0)?;
+                self.send_mouse_report_relative(buttons, dx, dy, 0, 0)?;
             }
             MouseEventType::MoveAbs => {
                 // Coordinates 0–32767; buttons are sent only on the relative endpoint.
                 let x = event.x.clamp(0, 32767) as u16;
                 let y = event.y.clamp(0, 32767) as u16;
-                self.send_mouse_report_absolute(0, x, y, 0)?;
+                self.send_mouse_report_absolute(0, x, y, 0, 0)?;
             }
             MouseEventType::Down => {
                 if let Some(button) = event.button {
                     let bit = button.to_hid_bit();
                     let new_buttons = self.mouse_buttons.fetch_or(bit, Ordering::Relaxed) | bit;
-                    self.send_mouse_report_relative(new_buttons, 0, 0, 0)?;
+                    self.send_mouse_report_relative(new_buttons, 0, 0, 0, 0)?;
                 }
             }
             MouseEventType::Up => {
                 if let Some(button) = event.button {
                     let bit = button.to_hid_bit();
                     let new_buttons = self.mouse_buttons.fetch_and(!bit, Ordering::Relaxed) & !bit;
-                    self.send_mouse_report_relative(new_buttons, 0, 0, 0)?;
+                    self.send_mouse_report_relative(new_buttons, 0, 0, 0, 0)?;
                 }
             }
             MouseEventType::Scroll => {
-                self.send_mouse_report_relative(buttons, 0, 0, event.scroll)?;
+                self.send_mouse_report_relative(buttons, 0, 0, event.scroll, event.scroll_x)?;
             }
         }
 
@@ -997,8 +1144,8 @@ impl HidBackend for OtgBackend {
         }
 
         self.mouse_buttons.store(0, Ordering::Relaxed);
-        self.send_mouse_report_relative(0, 0, 0, 0)?;
-        self.send_mouse_report_absolute(0, 0, 0, 0)?;
+        self.send_mouse_report_relative(0, 0, 0, 0, 0)?;
+        self.send_mouse_report_absolute(0, 0, 0, 0, 0)?;
 
         info!("HID state reset");
         Ok(())
@@ -1010,9 +1157,11 @@ impl HidBackend for OtgBackend {
         self.reset().await?;
 
         *self.keyboard_dev.lock() = None;
+        *self.keyboard_nkro_dev.lock() = None;
         *self.mouse_rel_dev.lock() = None;
         *self.mouse_abs_dev.lock() = None;
         *self.consumer_dev.lock() = None;
+        *self.system_dev.lock() = None;
 
         self.initialized.store(false, Ordering::Relaxed);
         self.online.store(false, Ordering::Relaxed);
@@ -1035,6 +1184,10 @@ impl HidBackend for OtgBackend {
         self.send_consumer_report(event.usage)
     }
 
+    async fn send_system_control(&self, action: SystemControlAction) -> Result<()> {
+        self.send_system_control_report(action)
+    }
+
     fn set_screen_resolution(&self, width: u32, height: u32) {
         *self.screen_resolution.write() = Some((width, height));
         self.notify_runtime_changed();
@@ -1048,9 +1201,11 @@ impl Drop for OtgBackend {
             let _ = handle.join();
         }
         *self.keyboard_dev.lock() = None;
+        *self.keyboard_nkro_dev.lock() = None;
         *self.mouse_rel_dev.lock() = None;
         *self.mouse_abs_dev.lock() = None;
         *self.consumer_dev.lock() = None;
+        *self.system_dev.lock() = None;
         debug!("OtgBackend dropped, device files closed");
     }
 }
@@ -0,0 +1,247 @@
+//! Named key combos and sequences that browsers intercept before they reach
+//! the page (Ctrl+Alt+Del, Alt+SysRq+REISUB, Ctrl+Alt+Fn, Win+L). Built-ins
+//! are always available; user presets from the HID config are added to
+//! them and replace a built-in with the same ID.
+
+use serde::{Deserialize, Serialize};
+
+use super::batch::{self, BatchAction, BatchStep, BatchTarget};
+use super::keyboard::CanonicalKey;
+use crate::error::{AppError, Result};
+
+const MAX_PRESET_ID_LEN: usize = 64;
+/// Pause between the REISUB letters so each SysRq action can finish
+const SYSRQ_STEP_DELAY_MS: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyPreset {
+    /// Stable identifier used by the API and the datachannel, e.g. `ctrl_alt_del`
+    pub id: String,
+    pub name: String,
+    pub steps: Vec<BatchStep>,
+}
+
+/// Listing entry
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyPresetInfo {
+    #[serde(flatten)]
+    pub preset: KeyPreset,
+    pub builtin: bool,
+}
+
+fn step(delay_ms: u64, action: BatchAction) -> BatchStep {
+    BatchStep { delay_ms, action }
+}
+
+fn press(key: CanonicalKey) -> BatchStep {
+    step(
+        0,
+        BatchAction::Press {
+            target: BatchTarget::Key { key },
+        },
+    )
+}
+
+fn release(key: CanonicalKey) -> BatchStep {
+    step(
+        0,
+        BatchAction::Release {
+            target: BatchTarget::Key { key },
+        },
+    )
+}
+
+/// Press `keys` in order and release them in reverse
+fn combo(id: &str, name: &str, keys: &[CanonicalKey]) -> KeyPreset {
+    let steps = keys
+        .iter()
+        .map(|&key| press(key))
+        .chain(keys.iter().rev().map(|&key| release(key)))
+        .collect();
+    KeyPreset {
+        id: id.to_string(),
+        name: name.to_string(),
+        steps,
+    }
+}
+
+pub fn builtin_presets() -> Vec<KeyPreset> {
+    use CanonicalKey::*;
+
+    let mut presets = vec![
+        combo(
+            "ctrl_alt_del",
+            "Ctrl+Alt+Del",
+            &[ControlLeft, AltLeft, Delete],
+        ),
+        combo("win_l", "Win+L (lock)", &[MetaLeft, KeyL]),
+        combo(
+            "ctrl_alt_backspace",
+            "Ctrl+Alt+Backspace",
+            &[ControlLeft, AltLeft, Backspace],
+        ),
+    ];
+
+    let function_keys = [F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12];
+    for (n, key) in function_keys.into_iter().enumerate() {
+        presets.push(combo(
+            &format!("ctrl_alt_f{}", n + 1),
+            &format!("Ctrl+Alt+F{}", n + 1),
+            &[ControlLeft, AltLeft, key],
+        ));
+    }
+
+    let mut reisub = vec![press(AltLeft), press(PrintScreen)];
+    for key in [KeyR, KeyE, KeyI, KeyS, KeyU, KeyB] {
+        reisub.push(step(
+            SYSRQ_STEP_DELAY_MS,
+            BatchAction::Tap {
+                target: BatchTarget::Key { key },
+                hold_ms: 0,
+            },
+        ));
+    }
+    reisub.extend([release(PrintScreen), release(AltLeft)]);
+    presets.push(KeyPreset {
+        id: "alt_sysrq_reisub".to_string(),
+        name: "Alt+SysRq+REISUB (safe reboot)".to_string(),
+        steps: reisub,
+    });
+
+    presets
+}
+
+/// Built-ins overlaid with `user` presets, built-ins first
+pub fn merged_presets(user: &[KeyPreset]) -> Vec<KeyPresetInfo> {
+    let mut presets: Vec<KeyPresetInfo> = builtin_presets()
+        .into_iter()
+        .filter(|builtin| !user.iter().any(|p| p.id == builtin.id))
+        .map(|preset| KeyPresetInfo {
+            preset,
+            builtin: true,
+        })
+        .collect();
+    presets.extend(user.iter().cloned().map(|preset| KeyPresetInfo {
+        preset,
+        builtin: false,
+    }));
+    presets
+}
+
+pub fn find_preset(user: &[KeyPreset], id: &str) -> Option<KeyPreset> {
+    user.iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .or_else(|| builtin_presets().into_iter().find(|p| p.id == id))
+}
+
+pub fn validate_presets(presets: &[KeyPreset]) -> Result<()> {
+    for (index, preset) in presets.iter().enumerate() {
+        if preset.id.is_empty()
+            || preset.id.len() > MAX_PRESET_ID_LEN
+            || !preset
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        {
+            return Err(AppError::BadRequest(format!(
+                "Invalid key preset ID '{}': use 1-{} characters of a-z, 0-9, '_' or '-'",
+                preset.id, MAX_PRESET_ID_LEN
+            )));
+        }
+        if preset.name.trim().is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Key preset '{}' needs a name",
+                preset.id
+            )));
+        }
+        if presets[..index].iter().any(|p| p.id == preset.id) {
+            return Err(AppError::BadRequest(format!(
+                "Duplicate key preset ID '{}'",
+                preset.id
+            )));
+        }
+        batch::validate(&preset.steps)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::macros::MacroEvent;
+    use crate::hid::KeyEventType;
+
+    #[test]
+    fn test_builtins_are_valid() {
+        let builtins = builtin_presets();
+        assert!(validate_presets(&builtins).is_ok());
+        assert!(builtins.iter().any(|p| p.id == "ctrl_alt_f12"));
+
+        let plan = batch::plan(&find_preset(&[], "ctrl_alt_del").unwrap().steps).unwrap();
+        let keys: Vec<_> = plan
+            .iter()
+            .map(|e| match &e.event {
+                MacroEvent::Keyboard(kb) => (kb.key, kb.event_type),
+                _ => panic!("expected key events"),
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (CanonicalKey::ControlLeft, KeyEventType::Down),
+                (CanonicalKey::AltLeft, KeyEventType::Down),
+                (CanonicalKey::Delete, KeyEventType::Down),
+                (CanonicalKey::Delete, KeyEventType::Up),
+                (CanonicalKey::AltLeft, KeyEventType::Up),
+                (CanonicalKey::ControlLeft, KeyEventType::Up),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_user_presets_override_builtins() {
+        let user = vec![
+            combo(
+                "win_l",
+                "Lock",
+                &[CanonicalKey::MetaRight, CanonicalKey::KeyL],
+            ),
+            combo(
+                "paste",
+                "Shift+Insert",
+                &[CanonicalKey::ShiftLeft, CanonicalKey::Insert],
+            ),
+        ];
+        assert!(validate_presets(&user).is_ok());
+
+        let merged = merged_presets(&user);
+        assert_eq!(merged.iter().filter(|p| p.preset.id == "win_l").count(), 1);
+        assert!(
+            !merged
+                .iter()
+                .find(|p| p.preset.id == "win_l")
+                .unwrap()
+                .builtin
+        );
+        assert_eq!(find_preset(&user, "win_l").unwrap().name, "Lock");
+        assert!(find_preset(&user, "paste").is_some());
+        assert!(find_preset(&user, "missing").is_none());
+    }
+
+    #[test]
+    fn test_validate_presets() {
+        let bad_id = combo("Bad ID", "x", &[CanonicalKey::KeyA]);
+        assert!(validate_presets(&[bad_id]).is_err());
+
+        let dup = combo("a", "A", &[CanonicalKey::KeyA]);
+        assert!(validate_presets(&[dup.clone(), dup]).is_err());
+
+        let empty = KeyPreset {
+            id: "empty".to_string(),
+            name: "Empty".to_string(),
+            steps: Vec::new(),
+        };
+        assert!(validate_presets(&[empty]).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+//! Type a Unicode string into the target as key press/release sequences.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::layout::{KeyStroke, KeyboardLayout};
+use crate::error::{AppError, Result};
+
+/// Longest text accepted for a single type job
+pub const MAX_TYPE_TEXT_CHARS: usize = 65536;
+pub const DEFAULT_TYPE_DELAY_MS: u64 = 20;
+const MAX_TYPE_DELAY_MS: u64 = 5000;
+/// Progress is published every this many characters
+pub(crate) const PROGRESS_INTERVAL: usize = 32;
+
+fn default_delay_ms() -> u64 {
+    DEFAULT_TYPE_DELAY_MS
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TypeTextRequest {
+    pub text: String,
+    #[serde(default)]
+    pub layout: KeyboardLayout,
+    /// Pause after each key release, in milliseconds
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u64,
+}
+
+/// State of a background typing or macro playback job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HidJobState {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl std::fmt::Display for HidJobState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+            Self::Failed => "failed",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Key strokes for every character of `text`, in order. Fails listing the
+/// characters the layout cannot produce, so nothing is typed half-way.
+pub fn plan_text(text: &str, layout: KeyboardLayout) -> Result<Vec<Vec<KeyStroke>>> {
+    let text = text.replace("\r\n", "\n");
+    let mut unsupported = Vec::new();
+    let plan: Vec<_> = text
+        .chars()
+        .filter_map(|ch| {
+            let strokes = layout.strokes(ch);
+            if strokes.is_none() && !unsupported.contains(&ch) {
+                unsupported.push(ch);
+            }
+            strokes
+        })
+        .collect();
+
+    if !unsupported.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Characters not available on the {:?} layout: {:?}",
+            layout,
+            unsupported.into_iter().collect::<String>()
+        )));
+    }
+    Ok(plan)
+}
+
+pub(crate) fn validate_request(req: &TypeTextRequest) -> Result<Duration> {
+    if req.text.is_empty() {
+        return Err(AppError::BadRequest("text is empty".to_string()));
+    }
+    if req.text.chars().count() > MAX_TYPE_TEXT_CHARS {
+        return Err(AppError::BadRequest(format!(
+            "text is longer than {} characters",
+            MAX_TYPE_TEXT_CHARS
+        )));
+    }
+    if req.delay_ms > MAX_TYPE_DELAY_MS {
+        return Err(AppError::BadRequest(format!(
+            "delay_ms must be at most {}",
+            MAX_TYPE_DELAY_MS
+        )));
+    }
+    Ok(Duration::from_millis(req.delay_ms))
+}
+
+/// The job currently typing, if any
+pub(crate) struct RunningTypeJob {
+    pub id: String,
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl RunningTypeJob {
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hid::CanonicalKey;
+
+    #[test]
+    fn test_plan_text() {
+        let plan = plan_text("Hi!\r\n", KeyboardLayout::Us).unwrap();
+        let keys: Vec<_> = plan.iter().map(|s| (s[0].key, s[0].shift)).collect();
+        assert_eq!(
+            keys,
+            vec![
+                (CanonicalKey::KeyH, true),
+                (CanonicalKey::KeyI, false),
+                (CanonicalKey::Digit1, true),
+                (CanonicalKey::Enter, false),
+            ]
+        );
+
+        let err = plan_text("naïve ☃", KeyboardLayout::Us).unwrap_err();
+        assert!(err.to_string().contains("ï☃"));
+        assert_eq!(plan_text("naïve", KeyboardLayout::Fr).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let mut req = TypeTextRequest {
+            text: "root".to_string(),
+            layout: KeyboardLayout::Us,
+            delay_ms: DEFAULT_TYPE_DELAY_MS,
+        };
+        assert_eq!(validate_request(&req).unwrap(), Duration::from_millis(20));
+
+        req.delay_ms = MAX_TYPE_DELAY_MS + 1;
+        assert!(validate_request(&req).is_err());
+
+        req.delay_ms = 0;
+        req.text.clear();
+        assert!(validate_request(&req).is_err());
+    }
+}
@@ -86,7 +86,7 @@ impl KeyboardModifiers {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct KeyboardEvent {
     #[serde(rename = "type")]
     pub event_type: KeyEventType,
@@ -145,7 +145,7 @@ pub enum MouseEventType {
     Scroll,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MouseEvent {
     #[serde(rename = "type")]
     pub event_type: MouseEventType,
@@ -155,8 +155,12 @@ pub struct MouseEvent {
     pub y: i32,
     #[serde(default)]
     pub button: Option<MouseButton>,
+    /// Vertical wheel delta
     #[serde(default)]
     pub scroll: i8,
+    /// Horizontal wheel (AC Pan) delta, positive scrolls right
+    #[serde(default)]
+    pub scroll_x: i8,
 }
 
 impl MouseEvent {
@@ -167,6 +171,7 @@ impl MouseEvent {
             y: dy,
             button: None,
             scroll: 0,
+            scroll_x: 0,
         }
     }
 
@@ -177,6 +182,7 @@ impl MouseEvent {
             y,
             button: None,
             scroll: 0,
+            scroll_x: 0,
         }
     }
 
@@ -187,6 +193,7 @@ impl MouseEvent {
             y: 0,
             button: Some(button),
             scroll: 0,
+            scroll_x: 0,
         }
     }
 
@@ -197,6 +204,7 @@ impl MouseEvent {
             y: 0,
             button: Some(button),
             scroll: 0,
+            scroll_x: 0,
         }
     }
 
@@ -207,20 +215,70 @@ impl MouseEvent {
             y: 0,
             button: None,
             scroll: delta,
+            scroll_x: 0,
+        }
+    }
+
+    /// Scroll both wheels at once, e.g. for trackpad panning
+    pub fn scroll_xy(dx: i8, dy: i8) -> Self {
+        Self {
+            scroll_x: dx,
+            ..Self::scroll(dy)
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConsumerEvent {
     pub usage: u16,
 }
 
+/// Generic Desktop System Control usages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemControlAction {
+    PowerDown,
+    Sleep,
+    WakeUp,
+}
+
+impl SystemControlAction {
+    /// Value in the System Control report; 0 means released
+    pub fn report_value(self) -> u8 {
+        match self {
+            Self::PowerDown => 1,
+            Self::Sleep => 2,
+            Self::WakeUp => 3,
+        }
+    }
+
+    pub fn from_report_value(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::PowerDown),
+            2 => Some(Self::Sleep),
+            3 => Some(Self::WakeUp),
+            _ => None,
+        }
+    }
+
+    /// Usage ID on the Generic Desktop page
+    pub fn usage(self) -> u8 {
+        0x80 + self.report_value()
+    }
+}
+
+/// Bytes in the NKRO key bitmap, covering usages 0x00-0xDF
+pub const NKRO_BITMAP_LEN: usize = 28;
+
 #[derive(Debug, Clone, Default)]
 pub struct KeyboardReport {
     pub modifiers: u8,
     pub reserved: u8,
     pub keys: [u8; 6],
+    /// Keys pressed while all six boot slots were taken. A key stays where it
+    /// was first placed until released, so the host never sees it pressed on
+    /// both keyboard interfaces.
+    pub overflow: [u8; NKRO_BITMAP_LEN],
 }
 
 impl KeyboardReport {
@@ -237,13 +295,31 @@ impl KeyboardReport {
         ]
     }
 
+    fn set_overflow(&mut self, key: u8, pressed: bool) {
+        let Some(byte) = self.overflow.get_mut(key as usize / 8) else {
+            return;
+        };
+        let mask = 1 << (key % 8);
+        if pressed {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+    }
+
+    /// Returns false if the key did not fit in the six boot slots; it is
+    /// then kept in the overflow bitmap.
     pub fn add_key(&mut self, key: u8) -> bool {
+        if self.keys.contains(&key) {
+            return true;
+        }
         for slot in &mut self.keys {
             if *slot == 0 {
                 *slot = key;
                 return true;
             }
         }
+        self.set_overflow(key, true);
         false // All slots full
     }
 
@@ -253,12 +329,14 @@ impl KeyboardReport {
                 *slot = 0;
             }
         }
+        self.set_overflow(key, false);
         self.keys.sort_by(|a, b| b.cmp(a));
     }
 
     pub fn clear(&mut self) {
         self.modifiers = 0;
         self.keys = [0; 6];
+        self.overflow = [0; NKRO_BITMAP_LEN];
     }
 }
 
@@ -292,4 +370,44 @@ mod tests {
         report.remove_key(0x04);
         assert_eq!(report.keys[0], 0x05);
     }
+
+    #[test]
+    fn test_keyboard_report_overflow() {
+        let mut report = KeyboardReport::default();
+        for key in 0x04..=0x0A {
+            report.add_key(key);
+        }
+        // The seventh key only fits in the overflow bitmap
+        assert!(!report.keys.contains(&0x0A));
+        assert_eq!(report.overflow[1], 0x04); // 0x0A
+        assert_eq!(report.overflow.iter().filter(|b| **b != 0).count(), 1);
+
+        // Overflow keys are not moved into a freed boot slot
+        report.remove_key(0x04);
+        assert!(!report.keys.contains(&0x0A));
+        assert!(!report.keys.contains(&0x04));
+        assert_eq!(report.overflow[1], 0x04);
+
+        // A key pressed after that takes the free boot slot
+        assert!(report.add_key(0x0B));
+        report.remove_key(0x0A);
+        assert_eq!(report.overflow, [0; NKRO_BITMAP_LEN]);
+
+        report.clear();
+        assert_eq!(report.to_bytes(), [0; 8]);
+    }
+
+    #[test]
+    fn test_keyboard_report_international_keys() {
+        let mut report = KeyboardReport::default();
+        report.add_key(CanonicalKey::Lang1.to_hid_usage());
+        report.add_key(CanonicalKey::IntlYen.to_hid_usage());
+        assert_eq!(&report.to_bytes()[2..4], &[0x90, 0x89]);
+
+        for key in 0x04..=0x07 {
+            report.add_key(key);
+        }
+        assert!(!report.add_key(CanonicalKey::Lang2.to_hid_usage()));
+        assert_eq!(report.overflow[0x91 / 8], 0x02);
+    }
 }
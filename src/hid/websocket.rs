@@ -6,12 +6,15 @@ use axum::{
         State,
     },
     response::Response,
+    Extension,
 };
 use futures::{SinkExt, StreamExt};
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
-use super::datachannel::{parse_hid_message, HidChannelEvent};
+use super::control::{ControlOwner, InputSource};
+use super::datachannel::parse_hid_message;
+use crate::auth::Session;
 use crate::state::AppState;
 use crate::utils::LogThrottler;
 
@@ -19,13 +22,20 @@ const RESP_OK: u8 = 0x00;
 const RESP_ERR_HID_UNAVAILABLE: u8 = 0x01;
 const RESP_ERR_INVALID_MESSAGE: u8 = 0x02;
 
-pub async fn ws_hid_handler(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
-    ws.on_upgrade(move |socket| handle_hid_socket(socket, state))
+pub async fn ws_hid_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+) -> Response {
+    let owner = state.hid_control_owner(&session).await;
+    ws.on_upgrade(move |socket| handle_hid_socket(socket, state, owner))
 }
 
-async fn handle_hid_socket(socket: WebSocket, state: Arc<AppState>) {
+async fn handle_hid_socket(socket: WebSocket, state: Arc<AppState>, owner: ControlOwner) {
     let (mut sender, mut receiver) = socket.split();
     let log_throttler = LogThrottler::with_secs(5);
+    let owner_id = owner.id.clone();
+    let _presence = state.hid.control().connect(InputSource::WebSocket, owner);
 
     info!("WebSocket HID connection established (binary protocol)");
 
@@ -59,7 +69,7 @@ async fn handle_hid_socket(socket: WebSocket, state: Arc<AppState>) {
                     continue;
                 }
 
-                if let Err(e) = handle_binary_message(&data, &state).await {
+                if let Err(e) = handle_binary_message(&data, &state, &owner_id).await {
                     if log_throttler.should_log("binary_hid_error") {
                         warn!("Binary HID message error: {}", e);
                     }
@@ -98,34 +108,18 @@ async fn handle_hid_socket(socket: WebSocket, state: Arc<AppState>) {
     info!("WebSocket HID connection ended");
 }
 
-async fn handle_binary_message(data: &[u8], state: &AppState) -> Result<(), String> {
+async fn handle_binary_message(
+    data: &[u8],
+    state: &AppState,
+    owner_id: &str,
+) -> Result<(), String> {
     let event = parse_hid_message(data).ok_or("Invalid binary HID message")?;
 
-    match event {
-        HidChannelEvent::Keyboard(kb_event) => {
-            state
-                .hid
-                .send_keyboard(kb_event)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
-        HidChannelEvent::Mouse(ms_event) => {
-            state
-                .hid
-                .send_mouse(ms_event)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
-        HidChannelEvent::Consumer(consumer_event) => {
-            state
-                .hid
-                .send_consumer(consumer_event)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
-    }
-
-    Ok(())
+    state
+        .hid
+        .send_input(owner_id, event)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@
 
 pub mod atx;
 pub mod audio;
+pub mod audit;
 pub mod auth;
 pub mod config;
 pub mod db;
@@ -16,6 +17,7 @@ pub mod rustdesk;
 pub mod state;
 pub mod stream;
 pub mod stream_encoder;
+pub mod tls;
 pub mod update;
 pub mod utils;
 pub mod video;
@@ -5,7 +5,7 @@ use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use axum_server::tls_rustls::RustlsConfig;
+use axum_server::tls_rustls::RustlsAcceptor;
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use futures::{stream::FuturesUnordered, StreamExt};
 use rustls::crypto::{ring, CryptoProvider};
@@ -14,8 +14,9 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use one_kvm::atx::AtxController;
 use one_kvm::audio::{AudioController, AudioControllerConfig, AudioQuality};
-use one_kvm::auth::{SessionStore, UserStore};
-use one_kvm::config::{self, AppConfig, ConfigStore};
+use one_kvm::auth::client_cert::ClientCertAcceptor;
+use one_kvm::auth::{AccessControl, RecoveryCodeStore, SessionStore, UserRole, UserStore};
+use one_kvm::config::{self, AppConfig, ClientCertMode, ConfigStore};
 use one_kvm::db::DatabasePool;
 use one_kvm::events::EventBus;
 use one_kvm::extensions::ExtensionManager;
@@ -50,7 +51,7 @@ enum LogLevel {
 #[command(name = "one-kvm")]
 #[command(version, about = "A  open and lightweight IP-KVM solution", long_about = None)]
 struct CliArgs {
-    /// User management commands
+    /// User and config management commands
     #[command(subcommand)]
     command: Option<CliCommand>,
 
@@ -82,6 +83,10 @@ struct CliArgs {
     #[arg(short = 'd', long, value_name = "DIR")]
     data_dir: Option<PathBuf>,
 
+    /// Key file protecting config secrets at rest (default: <data dir>/config.key)
+    #[arg(long, value_name = "FILE")]
+    secret_key_file: Option<PathBuf>,
+
     /// Log level (error, warn, info, verbose, debug, trace)
     #[arg(short = 'l', long, value_name = "LEVEL", default_value = "info")]
     log_level: LogLevel,
@@ -95,6 +100,20 @@ struct CliArgs {
 enum CliCommand {
     /// Manage local users
     User(UserCommand),
+    /// Manage the stored configuration
+    Config(ConfigCommand),
+}
+
+#[derive(Args, Debug)]
+struct ConfigCommand {
+    #[command(subcommand)]
+    action: ConfigAction,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Replace the secret key file and re-wrap the config data key
+    RotateKey,
 }
 
 #[derive(Args, Debug)]
@@ -105,8 +124,12 @@ struct UserCommand {
 
 #[derive(Subcommand, Debug)]
 enum UserAction {
-    /// Set password for the single local user (interactive terminal prompt)
-    SetPassword,
+    /// Set password for a local user (interactive terminal prompt)
+    SetPassword {
+        /// Account to update; may be omitted when only one user exists
+        #[arg(short = 'u', long)]
+        username: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -123,16 +146,25 @@ async fn main() -> anyhow::Result<()> {
     let data_dir = args.data_dir.clone().unwrap_or_else(get_data_dir);
     tracing::info!("Data directory: {}", data_dir.display());
 
+    let secret_key_file = args
+        .secret_key_file
+        .clone()
+        .unwrap_or_else(|| data_dir.join("config.key"));
+
     if let Some(command) = args.command {
-        run_cli_command(command, data_dir).await?;
+        run_cli_command(command, data_dir, &secret_key_file).await?;
         return Ok(());
     }
 
     tokio::fs::create_dir_all(&data_dir).await?;
 
     let db = open_database_pool(&data_dir).await?;
-    let config_store = ConfigStore::new(db.clone_pool())?;
+    let config_store = ConfigStore::new(db.clone_pool())?
+        .with_secret_key(&secret_key_file)
+        .await?;
     config_store.load().await?;
+    let user_store = UserStore::new(db.clone_pool());
+    migrate_legacy_totp(&config_store, &user_store, &db).await?;
     let mut config = (*config_store.get()).clone();
 
     let mut msd_dir_updated = false;
@@ -198,15 +230,21 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("Server will listen on: {}://{}", scheme, addr);
     }
 
-    let session_store = SessionStore::new(config.auth.session_timeout_secs as i64);
-
-    let user_store = UserStore::new(db.clone_pool());
+    let session_ttl = config.auth.session_timeout_secs as i64;
+    let session_store = if config.auth.persist_sessions {
+        SessionStore::persistent(db.clone_pool(), session_ttl)
+    } else {
+        tracing::info!("Web sessions are kept in memory only");
+        SessionStore::new(session_ttl)
+    };
 
     let (shutdown_tx, _) = broadcast::channel::<()>(1);
 
     let events = Arc::new(EventBus::new());
     tracing::info!("Event bus initialized");
 
+    let access = Arc::new(AccessControl::new(config_store.clone(), events.clone()));
+
     let (video_format, video_resolution) = parse_video_config(&config);
     tracing::debug!(
         "Parsed video config: {} @ {}x{}",
@@ -324,6 +362,11 @@ async fn main() -> anyhow::Result<()> {
             port: config.hid.ch9329_port.clone(),
             baud_rate: config.hid.ch9329_baudrate,
         },
+        config::HidBackend::Mcu => HidBackendType::Mcu {
+            port: config.hid.mcu_port.clone(),
+            baud_rate: config.hid.mcu_baudrate,
+        },
+        config::HidBackend::Mock => HidBackendType::Mock,
         config::HidBackend::None => HidBackendType::None,
     };
     let hid = Arc::new(HidController::new(hid_backend, Some(otg_service.clone())));
@@ -331,6 +374,9 @@ async fn main() -> anyhow::Result<()> {
     if let Err(e) = hid.init().await {
         tracing::warn!("Failed to initialize HID backend: {}", e);
     }
+    hid.set_jiggler_config(config.hid.jiggler.clone()).await;
+    hid.set_key_presets(config.hid.key_presets.clone());
+    hid.start_jiggler().await;
 
     let msd = if config.msd.enabled {
         let ventoy_resource_dir = data_dir.join("ventoy");
@@ -510,6 +556,7 @@ async fn main() -> anyhow::Result<()> {
             stream_manager.clone(),
             hid.clone(),
             audio.clone(),
+            access.clone(),
         );
         Some(Arc::new(service))
     } else {
@@ -530,7 +577,7 @@ async fn main() -> anyhow::Result<()> {
             config.rtsp.port,
             config.rtsp.path
         );
-        let service = RtspService::new(config.rtsp.clone(), stream_manager.clone());
+        let service = RtspService::new(config.rtsp.clone(), stream_manager.clone(), access.clone());
         Some(Arc::new(service))
     } else {
         tracing::info!("RTSP disabled in configuration");
@@ -555,6 +602,7 @@ async fn main() -> anyhow::Result<()> {
         rtsp.clone(),
         extensions.clone(),
         events.clone(),
+        access,
         update_service,
         shutdown_tx.clone(),
         data_dir.clone(),
@@ -626,6 +674,38 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("Extension health check task started");
     }
 
+    {
+        let sessions = state.sessions.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+            loop {
+                interval.tick().await;
+                match sessions.cleanup_expired().await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::debug!("Removed {} expired sessions", n),
+                    Err(e) => tracing::warn!("Failed to clean up expired sessions: {}", e),
+                }
+            }
+        });
+    }
+
+    {
+        let audit = state.audit.clone();
+        let config_store_clone = config_store.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+            loop {
+                interval.tick().await;
+                let retention_days = config_store_clone.get().auth.audit_retention_days;
+                match audit.prune(retention_days).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::debug!("Pruned {} audit log entries", n),
+                    Err(e) => tracing::warn!("Failed to prune audit log: {}", e),
+                }
+            }
+        });
+    }
+
     state.publish_device_info().await;
 
     spawn_device_info_broadcaster(state.clone(), events);
@@ -643,35 +723,25 @@ async fn main() -> anyhow::Result<()> {
     };
 
     if config.web.https_enabled {
-        let tls_config = if let (Some(cert_path), Some(key_path)) =
-            (&config.web.ssl_cert_path, &config.web.ssl_key_path)
-        {
-            RustlsConfig::from_pem_file(cert_path, key_path).await?
-        } else {
-            let cert_dir = data_dir.join("certs");
-            let cert_path = cert_dir.join("server.crt");
-            let key_path = cert_dir.join("server.key");
-
-            if !cert_path.exists() || !key_path.exists() {
-                tracing::info!("Generating new self-signed TLS certificate");
-                let cert = generate_self_signed_cert()?;
-                tokio::fs::create_dir_all(&cert_dir).await?;
-                tokio::fs::write(&cert_path, cert.cert.pem()).await?;
-                tokio::fs::write(&key_path, cert.signing_key.serialize_pem()).await?;
-            } else {
-                tracing::info!("Using existing TLS certificate from {}", cert_dir.display());
-            }
-
-            RustlsConfig::from_pem_file(&cert_path, &key_path).await?
-        };
+        if config.web.client_cert_mode != ClientCertMode::Off {
+            tracing::info!(
+                "Client certificate authentication enabled ({:?})",
+                config.web.client_cert_mode
+            );
+        }
+        let tls_config = state.tls.load().await?;
+        state.tls.spawn_expiry_monitor();
 
         let servers = FuturesUnordered::new();
         for listener in listeners {
             let local_addr = listener.local_addr()?;
             tracing::info!("Starting HTTPS server on {}", local_addr);
 
-            let server = axum_server::from_tcp_rustls(listener, tls_config.clone())?
-                .serve(app.clone().into_make_service());
+            let acceptor = ClientCertAcceptor::new(RustlsAcceptor::new(tls_config.clone()));
+            let server = axum_server::from_tcp(listener)?.acceptor(acceptor).serve(
+                app.clone()
+                    .into_make_service_with_connect_info::<SocketAddr>(),
+            );
             servers.push(server);
         }
 
@@ -683,7 +753,11 @@ async fn main() -> anyhow::Result<()> {
             tracing::info!("Starting HTTP server on {}", local_addr);
 
             let listener = tokio::net::TcpListener::from_std(listener)?;
-            let server = axum::serve(listener, app.clone());
+            let server = axum::serve(
+                listener,
+                app.clone()
+                    .into_make_service_with_connect_info::<SocketAddr>(),
+            );
             servers.push(async move { server.await });
         }
 
@@ -738,6 +812,48 @@ async fn open_database_pool(data_dir: &Path) -> anyhow::Result<DatabasePool> {
     Ok(db)
 }
 
+/// Older builds kept a single TOTP secret in the config. Move it and its
+/// recovery codes to the first administrator, then drop the global fields.
+async fn migrate_legacy_totp(
+    config_store: &ConfigStore,
+    users: &UserStore,
+    db: &DatabasePool,
+) -> anyhow::Result<()> {
+    let auth = config_store.get().auth.clone();
+    if !auth.totp_enabled && auth.totp_secret.is_none() {
+        return Ok(());
+    }
+
+    let admin = users
+        .list()
+        .await?
+        .into_iter()
+        .find(|user| user.role == UserRole::Admin);
+    if let (Some(admin), Some(secret), true) = (admin, auth.totp_secret, auth.totp_enabled) {
+        if users.totp(&admin.id).await?.secret.is_none() {
+            users
+                .set_totp_secret(&admin.id, Some(&config_store.seal_secret(&secret)?))
+                .await?;
+            users.enable_totp(&admin.id).await?;
+            RecoveryCodeStore::new(db.clone_pool())
+                .adopt_unowned(&admin.id)
+                .await?;
+            tracing::info!(
+                "Moved two-factor authentication to user '{}'",
+                admin.username
+            );
+        }
+    }
+
+    config_store
+        .update(|config| {
+            config.auth.totp_enabled = false;
+            config.auth.totp_secret = None;
+        })
+        .await?;
+    Ok(())
+}
+
 async fn run_servers_until_shutdown<F, E>(
     mut servers: FuturesUnordered<F>,
     shutdown_signal: impl Future<Output = ()>,
@@ -760,14 +876,26 @@ async fn run_servers_until_shutdown<F, E>(
     }
 }
 
-async fn run_cli_command(command: CliCommand, data_dir: PathBuf) -> anyhow::Result<()> {
+async fn run_cli_command(
+    command: CliCommand,
+    data_dir: PathBuf,
+    secret_key_file: &Path,
+) -> anyhow::Result<()> {
     tokio::fs::create_dir_all(&data_dir).await?;
     let db = open_database_pool(&data_dir).await?;
     let users = UserStore::new(db.clone_pool());
-    let sessions = SessionStore::new(0);
+    // Revocations must reach sessions persisted by the running server
+    let sessions = SessionStore::persistent(db.clone_pool(), 0);
 
     match command {
         CliCommand::User(user) => run_user_action(user.action, &users, &sessions).await,
+        CliCommand::Config(config) => match config.action {
+            ConfigAction::RotateKey => {
+                config::rotate_key_file(&db.clone_pool(), secret_key_file).await?;
+                println!("Rotated secret key file {}", secret_key_file.display());
+                Ok(())
+            }
+        },
     }
 }
 
@@ -777,14 +905,31 @@ async fn run_user_action(
     sessions: &SessionStore,
 ) -> anyhow::Result<()> {
     match action {
-        UserAction::SetPassword => set_user_password(users, sessions).await,
+        UserAction::SetPassword { username } => {
+            set_user_password(users, sessions, username.as_deref()).await
+        }
     }
 }
 
-async fn set_user_password(users: &UserStore, sessions: &SessionStore) -> anyhow::Result<()> {
-    let user = users.single_user().await?.ok_or_else(|| {
-        anyhow::anyhow!("No local user exists yet; complete setup in the web UI first.")
-    })?;
+async fn set_user_password(
+    users: &UserStore,
+    sessions: &SessionStore,
+    username: Option<&str>,
+) -> anyhow::Result<()> {
+    let user = match username {
+        Some(name) => users
+            .get_by_username(name)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User '{}' does not exist", name))?,
+        None => {
+            let mut all = users.list().await?;
+            match all.len() {
+                0 => anyhow::bail!("No local user exists yet; complete setup in the web UI first."),
+                1 => all.remove(0),
+                _ => anyhow::bail!("Multiple users exist; pass --username to pick one"),
+            }
+        }
+    };
 
     let new_password = read_new_password_interactive()?;
     if new_password.len() < 4 {
@@ -792,7 +937,7 @@ async fn set_user_password(users: &UserStore, sessions: &SessionStore) -> anyhow
     }
 
     users.update_password(&user.id, &new_password).await?;
-    let revoked = sessions.delete_all().await?;
+    let revoked = sessions.delete_for_user(&user.id).await?.len();
 
     tracing::info!(
         "Password updated for user '{}' and {} sessions revoked",
@@ -879,19 +1024,6 @@ fn parse_video_config(config: &AppConfig) -> (PixelFormat, Resolution) {
     (format, resolution)
 }
 
-fn generate_self_signed_cert() -> anyhow::Result<rcgen::CertifiedKey<rcgen::KeyPair>> {
-    use rcgen::generate_simple_self_signed;
-
-    let subject_alt_names = vec![
-        "localhost".to_string(),
-        "127.0.0.1".to_string(),
-        "::1".to_string(),
-    ];
-
-    let certified_key = generate_simple_self_signed(subject_alt_names)?;
-    Ok(certified_key)
-}
-
 fn spawn_device_info_broadcaster(state: Arc<AppState>, events: Arc<EventBus>) {
     use std::time::{Duration, Instant};
 
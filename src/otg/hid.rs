@@ -6,52 +6,63 @@ use super::configfs::{
 };
 use super::function::GadgetFunction;
 use super::report_desc::{
-    CONSUMER_CONTROL, KEYBOARD, KEYBOARD_WITH_LED, MOUSE_ABSOLUTE, MOUSE_RELATIVE,
+    CONSUMER_CONTROL, KEYBOARD, KEYBOARD_NKRO, KEYBOARD_WITH_LED, MOUSE_ABSOLUTE, MOUSE_RELATIVE,
+    SYSTEM_CONTROL,
 };
 use crate::error::Result;
 
 #[derive(Debug, Clone)]
 pub enum HidFunctionType {
     Keyboard,
+    KeyboardNkro,
     MouseRelative,
     MouseAbsolute,
     ConsumerControl,
+    SystemControl,
 }
 
 impl HidFunctionType {
     pub fn endpoints(&self) -> u8 {
         match self {
             HidFunctionType::Keyboard => 1,
+            HidFunctionType::KeyboardNkro => 1,
             HidFunctionType::MouseRelative => 1,
             HidFunctionType::MouseAbsolute => 1,
             HidFunctionType::ConsumerControl => 1,
+            HidFunctionType::SystemControl => 1,
         }
     }
 
     pub fn protocol(&self) -> u8 {
         match self {
             HidFunctionType::Keyboard => 1,
+            HidFunctionType::KeyboardNkro => 0,
             HidFunctionType::MouseRelative => 2,
             HidFunctionType::MouseAbsolute => 2,
             HidFunctionType::ConsumerControl => 0,
+            HidFunctionType::SystemControl => 0,
         }
     }
 
     pub fn subclass(&self) -> u8 {
         match self {
             HidFunctionType::Keyboard => 1,
+            HidFunctionType::KeyboardNkro => 0,
             HidFunctionType::MouseRelative => 1,
             HidFunctionType::MouseAbsolute => 0,
             HidFunctionType::ConsumerControl => 0,
+            HidFunctionType::SystemControl => 0,
         }
     }
 
     pub fn report_length(&self, _keyboard_leds: bool) -> u8 {
         match self {
             HidFunctionType::Keyboard => 8,
-            HidFunctionType::MouseRelative => 4,
-            HidFunctionType::MouseAbsolute => 6,
+            HidFunctionType::KeyboardNkro => 28,
+            HidFunctionType::MouseRelative => 5,
+            HidFunctionType::MouseAbsolute => 7,
             HidFunctionType::ConsumerControl => 2,
+            HidFunctionType::SystemControl => 1,
         }
     }
 
@@ -64,9 +75,11 @@ impl HidFunctionType {
                     KEYBOARD
                 }
             }
+            HidFunctionType::KeyboardNkro => KEYBOARD_NKRO,
             HidFunctionType::MouseRelative => MOUSE_RELATIVE,
             HidFunctionType::MouseAbsolute => MOUSE_ABSOLUTE,
             HidFunctionType::ConsumerControl => CONSUMER_CONTROL,
+            HidFunctionType::SystemControl => SYSTEM_CONTROL,
         }
     }
 }
@@ -89,6 +102,15 @@ impl HidFunction {
         }
     }
 
+    pub fn keyboard_nkro(instance: u8) -> Self {
+        Self {
+            instance,
+            func_type: HidFunctionType::KeyboardNkro,
+            name: format!("hid.usb{}", instance),
+            keyboard_leds: false,
+        }
+    }
+
     pub fn mouse_relative(instance: u8) -> Self {
         Self {
             instance,
@@ -116,6 +138,15 @@ impl HidFunction {
         }
     }
 
+    pub fn system_control(instance: u8) -> Self {
+        Self {
+            instance,
+            func_type: HidFunctionType::SystemControl,
+            name: format!("hid.usb{}", instance),
+            keyboard_leds: false,
+        }
+    }
+
     fn function_path(&self, gadget_path: &Path) -> PathBuf {
         gadget_path.join("functions").join(self.name())
     }
@@ -203,8 +234,10 @@ mod tests {
 
         assert_eq!(HidFunctionType::Keyboard.report_length(false), 8);
         assert_eq!(HidFunctionType::Keyboard.report_length(true), 8);
-        assert_eq!(HidFunctionType::MouseRelative.report_length(false), 4);
-        assert_eq!(HidFunctionType::MouseAbsolute.report_length(false), 6);
+        assert_eq!(HidFunctionType::KeyboardNkro.report_length(false), 28);
+        assert_eq!(HidFunctionType::KeyboardNkro.subclass(), 0);
+        assert_eq!(HidFunctionType::MouseRelative.report_length(false), 5);
+        assert_eq!(HidFunctionType::MouseAbsolute.report_length(false), 7);
     }
 
     #[test]
@@ -111,6 +111,14 @@ impl OtgGadgetManager {
         Ok(device_path)
     }
 
+    pub fn add_keyboard_nkro(&mut self) -> Result<PathBuf> {
+        let func = HidFunction::keyboard_nkro(self.hid_instance);
+        let device_path = func.device_path();
+        self.add_function(Box::new(func))?;
+        self.hid_instance += 1;
+        Ok(device_path)
+    }
+
     pub fn add_mouse_relative(&mut self) -> Result<PathBuf> {
         let func = HidFunction::mouse_relative(self.hid_instance);
         let device_path = func.device_path();
@@ -135,6 +143,14 @@ impl OtgGadgetManager {
         Ok(device_path)
     }
 
+    pub fn add_system_control(&mut self) -> Result<PathBuf> {
+        let func = HidFunction::system_control(self.hid_instance);
+        let device_path = func.device_path();
+        self.add_function(Box::new(func))?;
+        self.hid_instance += 1;
+        Ok(device_path)
+    }
+
     pub fn add_msd(&mut self) -> Result<MsdFunction> {
         let func = MsdFunction::new(self.msd_instance);
         let func_clone = func.clone();
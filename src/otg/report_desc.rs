@@ -67,6 +67,30 @@ pub const KEYBOARD_WITH_LED: &[u8] = &[
     0xC0, // End Collection
 ];
 
+/// N-key rollover companion to the boot keyboard. It is a separate,
+/// non-boot interface that only reports keys pressed while the six boot
+/// slots are full, so BIOS hosts keep using the 8-byte boot keyboard and OS
+/// hosts see the union of both.
+pub const KEYBOARD_NKRO: &[u8] = &[
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x06, // Usage (Keyboard)
+    0xA1, 0x01, // Collection (Application)
+    // Key bitmap (224 bits)
+    0x05, 0x07, //   Usage Page (Key Codes)
+    0x19, 0x00, //   Usage Minimum (0)
+    0x29, 0xDF, //   Usage Maximum (223)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x25, 0x01, //   Logical Maximum (1)
+    0x95, 0xE0, //   Report Count (224)
+    0x75, 0x01, //   Report Size (1)
+    0x81, 0x02, //   Input (Data, Variable, Absolute) - Key bitmap
+    0xC0, // End Collection
+];
+
+/// Wheel and AC Pan report whole detents. There is deliberately no
+/// Resolution Multiplier feature report: the f_hid gadget cannot answer
+/// GET/SET_REPORT for features, so hosts would keep the default multiplier
+/// anyway and high-resolution scrolling is not supported.
 pub const MOUSE_RELATIVE: &[u8] = &[
     0x05, 0x01, // Usage Page (Generic Desktop)
     0x09, 0x02, // Usage (Mouse)
@@ -102,10 +126,19 @@ pub const MOUSE_RELATIVE: &[u8] = &[
     0x75, 0x08, //     Report Size (8)
     0x95, 0x01, //     Report Count (1)
     0x81, 0x06, //     Input (Data, Variable, Relative) - Wheel
+    // Horizontal wheel
+    0x05, 0x0C, //     Usage Page (Consumer)
+    0x0A, 0x38, 0x02, // Usage (AC Pan)
+    0x15, 0x81, //     Logical Minimum (-127)
+    0x25, 0x7F, //     Logical Maximum (127)
+    0x75, 0x08, //     Report Size (8)
+    0x95, 0x01, //     Report Count (1)
+    0x81, 0x06, //     Input (Data, Variable, Relative) - AC Pan
     0xC0, //   End Collection
     0xC0, // End Collection
 ];
 
+/// Same wheel layout as [`MOUSE_RELATIVE`], detents only
 pub const MOUSE_ABSOLUTE: &[u8] = &[
     0x05, 0x01, // Usage Page (Generic Desktop)
     0x09, 0x02, // Usage (Mouse)
@@ -147,6 +180,14 @@ pub const MOUSE_ABSOLUTE: &[u8] = &[
     0x75, 0x08, //     Report Size (8)
     0x95, 0x01, //     Report Count (1)
     0x81, 0x06, //     Input (Data, Variable, Relative) - Wheel
+    // Horizontal wheel
+    0x05, 0x0C, //     Usage Page (Consumer)
+    0x0A, 0x38, 0x02, // Usage (AC Pan)
+    0x15, 0x81, //     Logical Minimum (-127)
+    0x25, 0x7F, //     Logical Maximum (127)
+    0x75, 0x08, //     Report Size (8)
+    0x95, 0x01, //     Report Count (1)
+    0x81, 0x06, //     Input (Data, Variable, Relative) - AC Pan
     0xC0, //   End Collection
     0xC0, // End Collection
 ];
@@ -165,16 +206,111 @@ pub const CONSUMER_CONTROL: &[u8] = &[
     0xC0, // End Collection
 ];
 
+pub const SYSTEM_CONTROL: &[u8] = &[
+    0x05, 0x01, // Usage Page (Generic Desktop)
+    0x09, 0x80, // Usage (System Control)
+    0xA1, 0x01, // Collection (Application)
+    0x19, 0x81, //   Usage Minimum (System Power Down)
+    0x29, 0x83, //   Usage Maximum (System Wake Up)
+    0x15, 0x01, //   Logical Minimum (1)
+    0x25, 0x03, //   Logical Maximum (3)
+    0x75, 0x02, //   Report Size (2)
+    0x95, 0x01, //   Report Count (1)
+    0x81, 0x00, //   Input (Data, Array) - 0 = released
+    // Padding (6 bits)
+    0x75, 0x06, //   Report Size (6)
+    0x81, 0x01, //   Input (Constant)
+    0xC0, // End Collection
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hid::types::SystemControlAction;
+
+    const INPUT: u8 = 0x80;
+    const OUTPUT: u8 = 0x90;
+    const USAGE_PAGE: u8 = 0x04;
+    const LOGICAL_MIN: u8 = 0x14;
+    const LOGICAL_MAX: u8 = 0x24;
+    const USAGE: u8 = 0x08;
+    const USAGE_MIN: u8 = 0x18;
+    const USAGE_MAX: u8 = 0x28;
+
+    /// Short items as (tag and type, unsigned data)
+    fn items(desc: &[u8]) -> Vec<(u8, usize)> {
+        let mut items = Vec::new();
+        let mut i = 0;
+        while i < desc.len() {
+            let prefix = desc[i];
+            let len = [0, 1, 2, 4][(prefix & 0x03) as usize];
+            let data = desc[i + 1..i + 1 + len]
+                .iter()
+                .rev()
+                .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            items.push((prefix & 0xFC, data));
+            i += 1 + len;
+        }
+        items
+    }
+
+    fn item_values(desc: &[u8], tag: u8) -> Vec<usize> {
+        items(desc)
+            .into_iter()
+            .filter(|(t, _)| *t == tag)
+            .map(|(_, data)| data)
+            .collect()
+    }
+
+    /// Total bits of all `main` items (Input or Output) in a descriptor
+    fn report_bits(desc: &[u8], main: u8) -> usize {
+        let (mut size, mut count, mut bits) = (0, 0, 0);
+        for (tag, data) in items(desc) {
+            match tag {
+                0x74 => size = data,
+                0x94 => count = data,
+                tag if tag == main => bits += size * count,
+                _ => {}
+            }
+        }
+        bits
+    }
 
     #[test]
     fn test_report_descriptor_sizes() {
-        assert!(!KEYBOARD.is_empty());
-        assert!(!KEYBOARD_WITH_LED.is_empty());
-        assert!(!MOUSE_RELATIVE.is_empty());
-        assert!(!MOUSE_ABSOLUTE.is_empty());
-        assert!(!CONSUMER_CONTROL.is_empty());
+        assert_eq!(report_bits(KEYBOARD, INPUT), 8 * 8);
+        assert_eq!(report_bits(KEYBOARD, OUTPUT), 0);
+        assert_eq!(report_bits(KEYBOARD_WITH_LED, INPUT), 8 * 8);
+        assert_eq!(report_bits(KEYBOARD_WITH_LED, OUTPUT), 8);
+        assert_eq!(report_bits(KEYBOARD_NKRO, INPUT), 28 * 8);
+        assert_eq!(report_bits(MOUSE_RELATIVE, INPUT), 5 * 8);
+        assert_eq!(report_bits(MOUSE_ABSOLUTE, INPUT), 7 * 8);
+        assert_eq!(report_bits(CONSUMER_CONTROL, INPUT), 2 * 8);
+        assert_eq!(report_bits(SYSTEM_CONTROL, INPUT), 8);
+    }
+
+    #[test]
+    fn test_system_control_usages() {
+        assert_eq!(item_values(SYSTEM_CONTROL, USAGE_PAGE), vec![0x01]);
+        assert_eq!(item_values(SYSTEM_CONTROL, USAGE), vec![0x80]);
+
+        let (first, last) = (SystemControlAction::PowerDown, SystemControlAction::WakeUp);
+        assert_eq!(
+            item_values(SYSTEM_CONTROL, USAGE_MIN),
+            vec![first.usage() as usize]
+        );
+        assert_eq!(
+            item_values(SYSTEM_CONTROL, USAGE_MAX),
+            vec![last.usage() as usize]
+        );
+        assert_eq!(
+            item_values(SYSTEM_CONTROL, LOGICAL_MIN),
+            vec![first.report_value() as usize]
+        );
+        assert_eq!(
+            item_values(SYSTEM_CONTROL, LOGICAL_MAX),
+            vec![last.report_value() as usize]
+        );
+        assert_eq!(SystemControlAction::Sleep.usage(), 0x82);
     }
 }
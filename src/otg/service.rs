@@ -10,9 +10,12 @@ use crate::error::{AppError, Result};
 #[derive(Debug, Clone, Default)]
 pub struct HidDevicePaths {
     pub keyboard: Option<PathBuf>,
+    /// Companion N-key rollover interface for the boot keyboard
+    pub keyboard_nkro: Option<PathBuf>,
     pub mouse_relative: Option<PathBuf>,
     pub mouse_absolute: Option<PathBuf>,
     pub consumer: Option<PathBuf>,
+    pub system_control: Option<PathBuf>,
     pub udc: Option<String>,
     pub keyboard_leds_enabled: bool,
 }
@@ -21,9 +24,11 @@ impl HidDevicePaths {
     pub fn existing_paths(&self) -> Vec<PathBuf> {
         [
             &self.keyboard,
+            &self.keyboard_nkro,
             &self.mouse_relative,
             &self.mouse_absolute,
             &self.consumer,
+            &self.system_control,
         ]
         .into_iter()
         .filter_map(|p| p.as_ref().cloned())
@@ -37,6 +42,7 @@ pub(crate) struct OtgDesiredState {
     pub descriptor: GadgetDescriptor,
     pub hid_functions: Option<OtgHidFunctions>,
     pub keyboard_leds: bool,
+    pub keyboard_nkro: bool,
     pub msd_enabled: bool,
     pub max_endpoints: u8,
 }
@@ -48,6 +54,7 @@ impl Default for OtgDesiredState {
             descriptor: GadgetDescriptor::default(),
             hid_functions: None,
             keyboard_leds: false,
+            keyboard_nkro: false,
             msd_enabled: false,
             max_endpoints: super::endpoint::DEFAULT_MAX_ENDPOINTS,
         }
@@ -70,6 +77,7 @@ impl OtgDesiredState {
             descriptor: GadgetDescriptor::from(&hid.otg_descriptor),
             hid_functions,
             keyboard_leds: hid.effective_otg_keyboard_leds(),
+            keyboard_nkro: hid.effective_otg_keyboard_nkro(),
             msd_enabled: msd.enabled,
             max_endpoints: hid
                 .resolved_otg_endpoint_limit()
@@ -92,6 +100,7 @@ struct OtgServiceState {
     pub hid_paths: Option<HidDevicePaths>,
     pub hid_functions: Option<OtgHidFunctions>,
     pub keyboard_leds_enabled: bool,
+    pub keyboard_nkro_enabled: bool,
     pub max_endpoints: u8,
     pub descriptor: Option<GadgetDescriptor>,
     pub error: Option<String>,
@@ -163,6 +172,7 @@ impl OtgService {
                 && state.configured_udc == desired.udc
                 && state.hid_functions == desired.hid_functions
                 && state.keyboard_leds_enabled == desired.keyboard_leds
+                && state.keyboard_nkro_enabled == desired.keyboard_nkro
                 && state.max_endpoints == desired.max_endpoints
                 && state.descriptor.as_ref() == Some(&desired.descriptor)
             {
@@ -192,6 +202,7 @@ impl OtgService {
             state.hid_paths = None;
             state.hid_functions = None;
             state.keyboard_leds_enabled = false;
+            state.keyboard_nkro_enabled = false;
             state.max_endpoints = super::endpoint::DEFAULT_MAX_ENDPOINTS;
             state.descriptor = None;
             state.error = None;
@@ -240,6 +251,17 @@ impl OtgService {
                         return Err(AppError::Internal(error));
                     }
                 }
+
+                if desired.keyboard_nkro {
+                    match manager.add_keyboard_nkro() {
+                        Ok(nkro) => paths.keyboard_nkro = Some(nkro),
+                        Err(e) => {
+                            let error = format!("Failed to add NKRO keyboard HID function: {}", e);
+                            self.state.write().await.error = Some(error.clone());
+                            return Err(AppError::Internal(error));
+                        }
+                    }
+                }
             }
 
             if hid_functions.mouse_relative {
@@ -275,6 +297,17 @@ impl OtgService {
                 }
             }
 
+            if hid_functions.system_control {
+                match manager.add_system_control() {
+                    Ok(system) => paths.system_control = Some(system),
+                    Err(e) => {
+                        let error = format!("Failed to add system control HID function: {}", e);
+                        self.state.write().await.error = Some(error.clone());
+                        return Err(AppError::Internal(error));
+                    }
+                }
+            }
+
             hid_paths = Some(paths);
             debug!("HID functions added to gadget");
         }
@@ -327,6 +360,7 @@ impl OtgService {
             state.hid_paths = hid_paths;
             state.hid_functions = desired.hid_functions;
             state.keyboard_leds_enabled = desired.keyboard_leds;
+            state.keyboard_nkro_enabled = desired.keyboard_nkro;
             state.max_endpoints = desired.max_endpoints;
             state.descriptor = Some(desired.descriptor);
             state.error = None;
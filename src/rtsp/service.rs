@@ -6,6 +6,7 @@ use tokio::io::AsyncReadExt;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{broadcast, Mutex, RwLock};
 
+use crate::auth::{AccessControl, AccessService};
 use crate::config::RtspConfig;
 use crate::error::{AppError, Result};
 use crate::video::VideoStreamManager;
@@ -32,10 +33,15 @@ pub struct RtspService {
     server_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
     client_handles: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>,
     shared_state: SharedRtspState,
+    access: Arc<AccessControl>,
 }
 
 impl RtspService {
-    pub fn new(config: RtspConfig, video_manager: Arc<VideoStreamManager>) -> Self {
+    pub fn new(
+        config: RtspConfig,
+        video_manager: Arc<VideoStreamManager>,
+        access: Arc<AccessControl>,
+    ) -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
         Self {
             config: Arc::new(RwLock::new(config)),
@@ -45,6 +51,7 @@ impl RtspService {
             server_handle: Arc::new(Mutex::new(None)),
             client_handles: Arc::new(Mutex::new(Vec::new())),
             shared_state: SharedRtspState::new(),
+            access,
         }
     }
 
@@ -87,6 +94,7 @@ impl RtspService {
         let mut shutdown_rx = self.shutdown_tx.subscribe();
         let status = self.status.clone();
         let client_handles = self.client_handles.clone();
+        let access = self.access.clone();
 
         let handle = tokio::spawn(async move {
             tracing::info!("RTSP service listening on {}", bind_addr);
@@ -101,6 +109,10 @@ impl RtspService {
                     result = listener.accept() => {
                         match result {
                             Ok((stream, addr)) => {
+                                if !access.admit(AccessService::Rtsp, addr.ip()) {
+                                    drop(stream);
+                                    continue;
+                                }
                                 let cfg = service_config.clone();
                                 let vm = video_manager.clone();
                                 let shared = shared_state.clone();
@@ -14,7 +14,10 @@ use tokio::sync::{broadcast, mpsc, Mutex};
 use tracing::{debug, error, info, warn};
 
 use crate::audio::AudioController;
-use crate::hid::{CanonicalKey, HidController, KeyEventType, KeyboardEvent, KeyboardModifiers};
+use crate::hid::{
+    CanonicalKey, ControlOwner, HidController, InputSource, KeyEventType, KeyboardEvent,
+    KeyboardModifiers, PresenceGuard,
+};
 use crate::utils::hostname_from_etc;
 use crate::video::codec_constraints::{
     encoder_codec_to_id, encoder_codec_to_video_codec, video_codec_to_encoder_codec,
@@ -123,6 +126,8 @@ pub struct Connection {
     password: String,
     /// HID controller for keyboard/mouse events
     hid: Option<Arc<HidController>>,
+    /// Presence list entry, held while the peer is logged in
+    presence: Option<PresenceGuard>,
     /// Audio controller for audio streaming
     audio: Option<Arc<AudioController>>,
     /// Video stream manager for frame subscription
@@ -219,6 +224,7 @@ impl Connection {
             temp_keypair,
             password: config.device_password.clone(),
             hid,
+            presence: None,
             audio,
             video_manager,
             screen_width: DEFAULT_SCREEN_WIDTH,
@@ -499,6 +505,12 @@ impl Connection {
 
                 // Handle login and start video/audio streaming if successful
                 if self.handle_login_request_arc(&lr, writer).await? {
+                    if let (Some(hid), None) = (&self.hid, &self.presence) {
+                        self.presence = Some(hid.control().connect(
+                            InputSource::RustDesk,
+                            ControlOwner::rustdesk(&self.peer_id, &self.peer_name),
+                        ));
+                    }
                     // Store video_tx for potential codec switching
                     self.video_frame_tx = Some(video_tx.clone());
                     // Start video streaming
@@ -1290,6 +1302,14 @@ impl Connection {
         Ok(())
     }
 
+    /// Whether the HID control lock lets this peer's input through
+    fn has_hid_control(&self) -> bool {
+        let owner = ControlOwner::rustdesk(&self.peer_id, &self.peer_name);
+        self.hid
+            .as_ref()
+            .is_none_or(|hid| hid.control().accepts(&owner.id))
+    }
+
     /// Handle key event
     async fn handle_key_event(&mut self, ke: &KeyEvent) -> anyhow::Result<()> {
         debug!(
@@ -1297,6 +1317,11 @@ impl Connection {
             ke.down, ke.press, ke.union, ke.modifiers
         );
 
+        if !self.has_hid_control() {
+            debug!("HID control held by another user, dropping key event");
+            return Ok(());
+        }
+
         // Check for CapsLock state change in modifiers
         // RustDesk doesn't send CapsLock key events, only includes it in modifiers
         let caps_lock_in_modifiers = ke.modifiers.iter().any(|m| {
@@ -1369,6 +1394,10 @@ impl Connection {
             self.relative_mouse_active = false;
         }
 
+        if !self.has_hid_control() {
+            return Ok(());
+        }
+
         // Check if this is a pure move event (no button/scroll)
         let is_pure_move = event_type == mouse_type::MOVE || is_relative_move;
 
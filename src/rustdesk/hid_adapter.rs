@@ -1,4 +1,5 @@
 use super::protocol::hbb::message::key_event as ke_union;
+use super::protocol::hbb::message::KeyboardMode;
 use super::protocol::{ControlKey, KeyEvent, MouseEvent};
 use crate::hid::{
     CanonicalKey, KeyEventType, KeyboardEvent, KeyboardModifiers, MouseButton,
@@ -49,6 +50,7 @@ pub fn convert_mouse_event(
                 y: abs_y,
                 button: None,
                 scroll: 0,
+                scroll_x: 0,
             });
         }
         mouse_type::MOVE_RELATIVE => {
@@ -58,6 +60,7 @@ pub fn convert_mouse_event(
                 y: event.y,
                 button: None,
                 scroll: 0,
+                scroll_x: 0,
             });
         }
         mouse_type::DOWN => {
@@ -72,6 +75,7 @@ pub fn convert_mouse_event(
                     y: abs_y,
                     button: None,
                     scroll: 0,
+                    scroll_x: 0,
                 });
             }
 
@@ -82,6 +86,7 @@ pub fn convert_mouse_event(
                     y: 0,
                     button: Some(button),
                     scroll: 0,
+                    scroll_x: 0,
                 });
             }
         }
@@ -97,6 +102,7 @@ pub fn convert_mouse_event(
                     y: abs_y,
                     button: None,
                     scroll: 0,
+                    scroll_x: 0,
                 });
             }
 
@@ -107,6 +113,7 @@ pub fn convert_mouse_event(
                     y: 0,
                     button: Some(button),
                     scroll: 0,
+                    scroll_x: 0,
                 });
             }
         }
@@ -122,17 +129,18 @@ pub fn convert_mouse_event(
                     y: abs_y,
                     button: None,
                     scroll: 0,
+                    scroll_x: 0,
                 });
             }
 
-            let scroll = if event.y > 0 { 1i8 } else { -1i8 };
-            events.push(OneKvmMouseEvent {
-                event_type: MouseEventType::Scroll,
-                x: 0,
-                y: 0,
-                button: None,
-                scroll,
-            });
+            let scroll_x = event.x.signum() as i8;
+            // A horizontal-only wheel event must not also scroll vertically
+            let scroll = match event.y {
+                0 if scroll_x != 0 => 0,
+                y if y > 0 => 1,
+                _ => -1,
+            };
+            events.push(OneKvmMouseEvent::scroll_xy(scroll_x, scroll));
         }
         _ => {
             if include_abs_move {
@@ -146,6 +154,7 @@ pub fn convert_mouse_event(
                     y: abs_y,
                     button: None,
                     scroll: 0,
+                    scroll_x: 0,
                 });
             }
         }
@@ -188,7 +197,8 @@ pub fn convert_key_event(event: &KeyEvent) -> Option<KeyboardEvent> {
     }
 
     if let Some(ke_union::Union::Chr(chr)) = &event.union {
-        if let Some(key) = keycode_to_hid(*chr) {
+        let positional = event.mode.enum_value() == Ok(KeyboardMode::Map);
+        if let Some(key) = keycode_to_hid(*chr, positional) {
             let key = CanonicalKey::from_hid_usage(key)?;
             return Some(KeyboardEvent {
                 event_type,
@@ -291,11 +301,23 @@ fn control_key_to_hid(key: i32) -> Option<u8> {
         x if x == ControlKey::Decimal as i32 => Some(0x63),
         x if x == ControlKey::Divide as i32 => Some(0x54),
         x if x == ControlKey::NumpadEnter as i32 => Some(0x58),
+        x if x == ControlKey::Kana as i32 => Some(0x88), // International2
+        x if x == ControlKey::Convert as i32 => Some(0x8A), // International4
+        x if x == ControlKey::Hangul as i32 => Some(0x90), // Lang1
+        x if x == ControlKey::Hanja as i32 => Some(0x91), // Lang2
         _ => None,
     }
 }
 
-fn keycode_to_hid(keycode: u32) -> Option<u8> {
+/// Map a `chr` code to a HID usage. In map mode `chr` is a position code
+/// (an X11 keycode from Linux peers), which overlaps the ASCII and VK ranges
+/// for keys like the JIS Henkan (100) or Ro (97), so try X11 first there.
+fn keycode_to_hid(keycode: u32, positional: bool) -> Option<u8> {
+    if positional {
+        if let Some(hid) = x11_keycode_to_hid(keycode) {
+            return Some(hid);
+        }
+    }
     if let Some(hid) = ascii_to_hid(keycode) {
         return Some(hid);
     }
@@ -454,6 +476,16 @@ fn x11_keycode_to_hid(keycode: u32) -> Option<u8> {
         60 => Some(0x37),                             // .
         61 => Some(0x38),                             // /
         65 => Some(0x2C),
+        93 => Some(0x94),  // Zenkaku/Hankaku
+        97 => Some(0x87),  // Ro
+        98 => Some(0x92),  // Katakana
+        99 => Some(0x93),  // Hiragana
+        100 => Some(0x8A), // Henkan
+        101 => Some(0x88), // Katakana/Hiragana
+        102 => Some(0x8B), // Muhenkan
+        130 => Some(0x90), // Hangul
+        131 => Some(0x91), // Hanja
+        132 => Some(0x89), // Yen
         _ => None,
     }
 }
@@ -509,6 +541,18 @@ mod tests {
         assert_eq!(events[0].y, 8);
     }
 
+    #[test]
+    fn test_convert_horizontal_wheel() {
+        let mut event = MouseEvent::new();
+        event.x = -3;
+        event.mask = mouse_type::WHEEL;
+
+        let events = convert_mouse_event(&event, 1920, 1080, true);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, MouseEventType::Scroll);
+        assert_eq!((events[0].scroll_x, events[0].scroll), (-1, 0));
+    }
+
     #[test]
     fn test_convert_key_event() {
         use protobuf::EnumOrUnknown;
@@ -526,4 +570,53 @@ mod tests {
         assert_eq!(kb_event.event_type, KeyEventType::Down);
         assert_eq!(kb_event.key, CanonicalKey::Enter);
     }
+
+    #[test]
+    fn test_convert_international_keys() {
+        use protobuf::EnumOrUnknown;
+        let mut key_event = KeyEvent::new();
+        key_event.down = true;
+        key_event.union = Some(ke_union::Union::ControlKey(EnumOrUnknown::new(
+            ControlKey::Hangul,
+        )));
+        assert_eq!(
+            convert_key_event(&key_event).unwrap().key,
+            CanonicalKey::Lang1
+        );
+
+        key_event.union = Some(ke_union::Union::ControlKey(EnumOrUnknown::new(
+            ControlKey::Convert,
+        )));
+        assert_eq!(
+            convert_key_event(&key_event).unwrap().key,
+            CanonicalKey::Convert
+        );
+
+        key_event.union = Some(ke_union::Union::Chr(132));
+        assert_eq!(
+            convert_key_event(&key_event).unwrap().key,
+            CanonicalKey::IntlYen
+        );
+
+        // JIS keycodes that are also ASCII letters or VK codes
+        key_event.mode = EnumOrUnknown::new(KeyboardMode::Map);
+        for (keycode, key) in [
+            (93, CanonicalKey::Lang5),
+            (97, CanonicalKey::IntlRo),
+            (100, CanonicalKey::Convert),
+            (101, CanonicalKey::KanaMode),
+            (102, CanonicalKey::NonConvert),
+            (38, CanonicalKey::KeyA),
+        ] {
+            key_event.union = Some(ke_union::Union::Chr(keycode));
+            assert_eq!(convert_key_event(&key_event).unwrap().key, key);
+        }
+
+        key_event.mode = EnumOrUnknown::new(KeyboardMode::Legacy);
+        key_event.union = Some(ke_union::Union::Chr(97));
+        assert_eq!(
+            convert_key_event(&key_event).unwrap().key,
+            CanonicalKey::KeyA
+        );
+    }
 }
@@ -22,6 +22,7 @@ use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
 use crate::audio::AudioController;
+use crate::auth::{AccessControl, AccessService};
 use crate::hid::HidController;
 use crate::utils::bind_tcp_listener;
 use crate::video::stream_manager::VideoStreamManager;
@@ -65,6 +66,7 @@ pub struct RustDeskService {
     video_manager: Arc<VideoStreamManager>,
     hid: Arc<HidController>,
     audio: Arc<AudioController>,
+    access: Arc<AccessControl>,
     shutdown_tx: broadcast::Sender<()>,
 }
 
@@ -74,6 +76,7 @@ impl RustDeskService {
         video_manager: Arc<VideoStreamManager>,
         hid: Arc<HidController>,
         audio: Arc<AudioController>,
+        access: Arc<AccessControl>,
     ) -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
         let connection_manager = Arc::new(ConnectionManager::new(config.clone()));
@@ -89,6 +92,7 @@ impl RustDeskService {
             video_manager,
             hid,
             audio,
+            access,
             shutdown_tx,
         }
     }
@@ -312,6 +316,7 @@ impl RustDeskService {
             info!("RustDesk TCP listener started on {}", local_addr);
 
             let conn_mgr = connection_manager.clone();
+            let access = self.access.clone();
             let mut shutdown_rx = self.shutdown_tx.subscribe();
             let handle = tokio::spawn(async move {
                 loop {
@@ -319,6 +324,10 @@ impl RustDeskService {
                         result = listener.accept() => {
                             match result {
                                 Ok((stream, peer_addr)) => {
+                                    if !access.admit(AccessService::RustDesk, peer_addr.ip()) {
+                                        drop(stream);
+                                        continue;
+                                    }
                                     info!("Accepted direct connection from {}", peer_addr);
                                     let conn_mgr = conn_mgr.clone();
                                     tokio::spawn(async move {
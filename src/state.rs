@@ -3,7 +3,11 @@ use tokio::sync::{broadcast, watch, Mutex, RwLock};
 
 use crate::atx::AtxController;
 use crate::audio::AudioController;
-use crate::auth::{SessionStore, UserStore};
+use crate::audit::AuditLog;
+use crate::auth::{
+    session_key, AccessControl, ApiTokenStore, LoginLimiter, RecoveryCodeStore, Session,
+    SessionStore, ShareLinkStore, UserStore,
+};
 use crate::config::ConfigStore;
 use crate::db::DatabasePool;
 use crate::events::{
@@ -11,11 +15,12 @@ use crate::events::{
     TtydDeviceInfo, VideoDeviceInfo,
 };
 use crate::extensions::{ExtensionId, ExtensionManager};
-use crate::hid::HidController;
+use crate::hid::{ControlOwner, HidController, MacroStore};
 use crate::msd::MsdController;
 use crate::otg::OtgService;
 use crate::rtsp::RtspService;
 use crate::rustdesk::RustDeskService;
+use crate::tls::TlsManager;
 use crate::update::UpdateService;
 use crate::video::VideoStreamManager;
 use crate::webrtc::WebRtcStreamer;
@@ -51,6 +56,17 @@ pub struct AppState {
     pub config: ConfigStore,
     pub sessions: SessionStore,
     pub users: UserStore,
+    pub recovery_codes: RecoveryCodeStore,
+    pub api_tokens: ApiTokenStore,
+    pub login_limiter: LoginLimiter,
+    pub audit: AuditLog,
+    pub share_links: ShareLinkStore,
+    /// Recorded HID macros
+    pub macros: MacroStore,
+    /// IP allow/deny policy shared by the web, RTSP and RustDesk listeners
+    pub access: Arc<AccessControl>,
+    /// HTTPS certificate files and hot reload of the running listener
+    pub tls: Arc<TlsManager>,
     pub otg_service: Arc<OtgService>,
     pub stream_manager: Arc<VideoStreamManager>,
     pub webrtc: Arc<WebRtcStreamer>,
@@ -88,17 +104,38 @@ impl AppState {
         rtsp: Option<Arc<RtspService>>,
         extensions: Arc<ExtensionManager>,
         events: Arc<EventBus>,
+        access: Arc<AccessControl>,
         update: Arc<UpdateService>,
         shutdown_tx: broadcast::Sender<()>,
         data_dir: std::path::PathBuf,
     ) -> Arc<Self> {
         let (device_info_tx, _device_info_rx) = watch::channel(None);
 
+        let recovery_codes = RecoveryCodeStore::new(db.clone_pool());
+        let api_tokens = ApiTokenStore::new(db.clone_pool());
+        let audit = AuditLog::new(db.clone_pool());
+        let share_links = ShareLinkStore::new(db.clone_pool());
+        let macros = MacroStore::new(db.clone_pool());
+        hid.set_macro_store(macros.clone());
+        let tls = Arc::new(TlsManager::new(
+            config.clone(),
+            events.clone(),
+            data_dir.clone(),
+        ));
+
         Arc::new(Self {
             db,
             config,
             sessions,
             users,
+            recovery_codes,
+            api_tokens,
+            login_limiter: LoginLimiter::new(),
+            audit,
+            share_links,
+            macros,
+            access,
+            tls,
             otg_service,
             stream_manager,
             webrtc,
@@ -119,6 +156,56 @@ impl AppState {
         })
     }
 
+    /// State with a fresh database in `data_dir` and no devices, for router tests
+    #[cfg(test)]
+    pub(crate) async fn for_tests(data_dir: &std::path::Path) -> Arc<Self> {
+        use crate::audio::AudioControllerConfig;
+        use crate::hid::HidBackendType;
+        use crate::video::streamer::Streamer;
+
+        let db = DatabasePool::new(&data_dir.join("one-kvm.db"))
+            .await
+            .expect("open test database");
+        db.init_schema().await.expect("init test schema");
+        let config = ConfigStore::new(db.clone_pool()).expect("config store");
+        let sessions = SessionStore::persistent(db.clone_pool(), 3600);
+        let users = UserStore::new(db.clone_pool());
+        let events = Arc::new(EventBus::new());
+        let access = Arc::new(AccessControl::new(config.clone(), events.clone()));
+        let otg_service = Arc::new(OtgService::new());
+        let webrtc = WebRtcStreamer::new();
+        let stream_manager = VideoStreamManager::with_webrtc_streamer(
+            Streamer::new(),
+            webrtc.clone() as Arc<dyn crate::video::traits::VideoOutput>,
+        );
+        let hid = Arc::new(HidController::new(HidBackendType::None, None));
+        let audio = Arc::new(AudioController::new(AudioControllerConfig::default()));
+        let update = Arc::new(UpdateService::new(data_dir.join("updates")));
+        let (shutdown_tx, _) = broadcast::channel(1);
+
+        Self::new(
+            db,
+            config,
+            sessions,
+            users,
+            otg_service,
+            stream_manager,
+            webrtc,
+            hid,
+            None,
+            None,
+            audio,
+            None,
+            None,
+            Arc::new(ExtensionManager::new()),
+            events,
+            access,
+            update,
+            shutdown_tx,
+            data_dir.to_path_buf(),
+        )
+    }
+
     pub fn data_dir(&self) -> &std::path::PathBuf {
         &self.data_dir
     }
@@ -131,13 +218,14 @@ impl AppState {
         self.device_info_tx.subscribe()
     }
 
-    pub async fn remember_revoked_sessions(&self, session_ids: Vec<String>) {
-        if session_ids.is_empty() {
+    /// Remember session keys (see [`session_key`]) revoked by a newer login or account change.
+    pub async fn remember_revoked_sessions(&self, session_keys: Vec<String>) {
+        if session_keys.is_empty() {
             return;
         }
         let mut guard = self.revoked_sessions.write().await;
-        for id in session_ids {
-            guard.push_back(id);
+        for key in session_keys {
+            guard.push_back(key);
         }
         while guard.len() > 32 {
             guard.pop_front();
@@ -145,8 +233,21 @@ impl AppState {
     }
 
     pub async fn is_session_revoked(&self, session_id: &str) -> bool {
+        let key = session_key(session_id);
         let guard = self.revoked_sessions.read().await;
-        guard.iter().any(|id| id == session_id)
+        guard.iter().any(|k| *k == key)
+    }
+
+    /// HID control identity of a web session, named after its user.
+    pub async fn hid_control_owner(&self, session: &Session) -> ControlOwner {
+        let name = match self.users.get(&session.user_id).await {
+            Ok(Some(user)) => user.username,
+            _ => session.user_id.clone(),
+        };
+        ControlOwner {
+            id: session_key(&session.id),
+            name,
+        }
     }
 
     pub async fn get_device_info(&self) -> SystemEvent {
@@ -180,6 +281,7 @@ impl AppState {
 
     async fn collect_hid_info(&self) -> HidDeviceInfo {
         let state = self.hid.snapshot().await;
+        let jiggler = self.hid.jiggler_status();
 
         HidDeviceInfo {
             available: state.available,
@@ -198,6 +300,8 @@ impl AppState {
             device: state.device,
             error: state.error,
             error_code: state.error_code,
+            jiggler_enabled: jiggler.config.enabled,
+            jiggler_active: jiggler.active,
         }
     }
 
@@ -1,7 +1,7 @@
 use arc_swap::ArcSwap;
 use parking_lot::Mutex as ParkingMutex;
 use parking_lot::RwLock as ParkingRwLock;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -22,6 +22,8 @@ pub type ClientId = String;
 pub struct ClientSession {
     pub id: ClientId,
     pub generation: ClientGeneration,
+    /// Control owner of the viewer, if known
+    pub owner: Option<String>,
     pub connected_at: Instant,
     pub last_activity: Instant,
     pub frames_sent: u64,
@@ -29,11 +31,12 @@ pub struct ClientSession {
 }
 
 impl ClientSession {
-    pub fn new(id: ClientId, generation: ClientGeneration) -> Self {
+    pub fn new(id: ClientId, generation: ClientGeneration, owner: Option<String>) -> Self {
         let now = Instant::now();
         Self {
             id,
             generation,
+            owner,
             connected_at: now,
             last_activity: now,
             frames_sent: 0,
@@ -297,9 +300,9 @@ impl MjpegStreamHandler {
     }
 
     /// Connects `client_id`; return value must be passed to [`unregister_client`].
-    pub fn register_client(&self, client_id: ClientId) -> ClientGeneration {
+    pub fn register_client(&self, client_id: ClientId, owner: Option<String>) -> ClientGeneration {
         let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
-        let session = ClientSession::new(client_id.clone(), generation);
+        let session = ClientSession::new(client_id.clone(), generation, owner);
         self.clients.write().insert(client_id.clone(), session);
         info!(
             "Client {} connected (total: {})",
@@ -338,6 +341,15 @@ impl MjpegStreamHandler {
         }
     }
 
+    /// Control owners of the connected clients
+    pub fn client_owners(&self) -> HashSet<String> {
+        self.clients
+            .read()
+            .values()
+            .filter_map(|session| session.owner.clone())
+            .collect()
+    }
+
     pub fn get_clients_stat(&self) -> HashMap<String, crate::events::types::ClientStats> {
         // write() because `current_fps()` mutates the underlying VecDeque
         // to prune stale samples. Held for ~microseconds, called once per
@@ -400,7 +412,16 @@ pub struct ClientGuard {
 
 impl ClientGuard {
     pub fn new(client_id: ClientId, handler: Arc<MjpegStreamHandler>) -> Self {
-        let generation = handler.register_client(client_id.clone());
+        Self::with_owner(client_id, None, handler)
+    }
+
+    /// Register a client attributed to a control owner
+    pub fn with_owner(
+        client_id: ClientId,
+        owner: Option<String>,
+        handler: Arc<MjpegStreamHandler>,
+    ) -> Self {
+        let generation = handler.register_client(client_id.clone(), owner);
         Self {
             client_id,
             generation,
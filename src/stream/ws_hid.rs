@@ -214,6 +214,9 @@ impl WsHidHandler {
                     .await
                     .map_err(|e| e.to_string())?;
             }
+            _ => {
+                return Err("Message type not supported on this endpoint".to_string());
+            }
         }
 
         client.events_processed.fetch_add(1, Ordering::Relaxed);
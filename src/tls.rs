@@ -0,0 +1,401 @@
+//! HTTPS certificate management: loading, CSR generation, installation and
+//! hot reload of the certificate served by the web listener.
+
+use axum_server::tls_rustls::RustlsConfig;
+use rcgen::{CertificateParams, DnType, KeyPair};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use x509_parser::extensions::GeneralName;
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::auth::client_cert;
+use crate::config::ConfigStore;
+use crate::error::{AppError, Result};
+use crate::events::{EventBus, SystemEvent};
+
+/// Days before expiry at which `tls.cert_expiring` events start
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+/// How often the expiry monitor re-checks the active certificate
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 3600);
+
+/// SANs of the generated self-signed certificate when none are given
+const DEFAULT_SELF_SIGNED_SANS: &[&str] = &["localhost", "127.0.0.1", "::1"];
+
+/// Details of the certificate served over HTTPS
+#[derive(Debug, Clone, Serialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// DNS names and IP addresses from the subjectAltName extension
+    pub sans: Vec<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub not_before: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub not_after: OffsetDateTime,
+    /// Whole days until `not_after`; negative once expired
+    pub days_left: i64,
+    /// Lowercase hex SHA-256 of the DER leaf certificate
+    pub fingerprint: String,
+    pub self_signed: bool,
+    /// Whether a custom (uploaded) certificate is active
+    pub custom: bool,
+}
+
+impl CertificateInfo {
+    /// Describe the leaf (first) certificate of a PEM chain.
+    pub fn from_pem(pem: &[u8], custom: bool) -> Result<Self> {
+        let (_, block) = x509_parser::pem::parse_x509_pem(pem)
+            .map_err(|e| AppError::BadRequest(format!("Invalid certificate PEM: {}", e)))?;
+        let (_, cert) = X509Certificate::from_der(&block.contents)
+            .map_err(|e| AppError::BadRequest(format!("Invalid certificate: {}", e)))?;
+
+        let mut sans = Vec::new();
+        if let Ok(Some(ext)) = cert.subject_alternative_name() {
+            for name in &ext.value.general_names {
+                match name {
+                    GeneralName::DNSName(dns) => sans.push(dns.to_string()),
+                    GeneralName::IPAddress(bytes) => {
+                        let ip = match bytes.len() {
+                            4 => <[u8; 4]>::try_from(*bytes).ok().map(IpAddr::from),
+                            16 => <[u8; 16]>::try_from(*bytes).ok().map(IpAddr::from),
+                            _ => None,
+                        };
+                        sans.extend(ip.map(|ip| ip.to_string()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let to_time =
+            |ts: i64| OffsetDateTime::from_unix_timestamp(ts).unwrap_or(OffsetDateTime::UNIX_EPOCH);
+        let not_before = to_time(cert.validity().not_before.timestamp());
+        let not_after = to_time(cert.validity().not_after.timestamp());
+
+        Ok(Self {
+            subject: cert.subject().to_string(),
+            issuer: cert.issuer().to_string(),
+            sans,
+            not_before,
+            not_after,
+            days_left: (not_after - OffsetDateTime::now_utc()).whole_days(),
+            fingerprint: Sha256::digest(&block.contents)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+            self_signed: cert.subject() == cert.issuer(),
+            custom,
+        })
+    }
+
+    pub fn expires_soon(&self) -> bool {
+        self.days_left <= EXPIRY_WARNING_DAYS
+    }
+}
+
+/// Generate a self-signed certificate for `sans` (defaults when empty).
+pub fn generate_self_signed(sans: &[String]) -> Result<rcgen::CertifiedKey<KeyPair>> {
+    let sans: Vec<String> = if sans.is_empty() {
+        DEFAULT_SELF_SIGNED_SANS
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    } else {
+        sans.to_vec()
+    };
+    rcgen::generate_simple_self_signed(sans)
+        .map_err(|e| AppError::Internal(format!("Failed to generate certificate: {}", e)))
+}
+
+/// Generate a private key and a PEM certificate signing request.
+pub fn generate_csr(common_name: &str, sans: &[String]) -> Result<(KeyPair, String)> {
+    let mut params = CertificateParams::new(sans.to_vec())
+        .map_err(|e| AppError::BadRequest(format!("Invalid subject alternative name: {}", e)))?;
+    params
+        .distinguished_name
+        .push(DnType::CommonName, common_name);
+    let key = KeyPair::generate()
+        .map_err(|e| AppError::Internal(format!("Failed to generate key: {}", e)))?;
+    let csr = params
+        .serialize_request(&key)
+        .and_then(|csr| csr.pem())
+        .map_err(|e| AppError::Internal(format!("Failed to generate CSR: {}", e)))?;
+    Ok((key, csr))
+}
+
+/// Owns the certificate files under `<data_dir>/certs` and the rustls config
+/// of the running HTTPS listener, which is swapped in place on changes so new
+/// connections use the new certificate without a restart.
+pub struct TlsManager {
+    config: ConfigStore,
+    events: Arc<EventBus>,
+    data_dir: PathBuf,
+    listener: Mutex<Option<RustlsConfig>>,
+}
+
+impl TlsManager {
+    pub fn new(config: ConfigStore, events: Arc<EventBus>, data_dir: PathBuf) -> Self {
+        Self {
+            config,
+            events,
+            data_dir,
+            listener: Mutex::new(None),
+        }
+    }
+
+    pub fn cert_dir(&self) -> PathBuf {
+        self.data_dir.join("certs")
+    }
+
+    fn pending_key_path(&self) -> PathBuf {
+        self.cert_dir().join("pending.key")
+    }
+
+    /// Certificate and key paths in use: the custom pair if configured,
+    /// otherwise the self-signed pair. The flag tells which.
+    fn active_paths(&self) -> (PathBuf, PathBuf, bool) {
+        let web = self.config.get().web.clone();
+        match (web.ssl_cert_path, web.ssl_key_path) {
+            (Some(cert), Some(key)) if !cert.is_empty() && !key.is_empty() => {
+                (cert.into(), key.into(), true)
+            }
+            _ => (
+                self.cert_dir().join("server.crt"),
+                self.cert_dir().join("server.key"),
+                false,
+            ),
+        }
+    }
+
+    async fn write_pair(
+        &self,
+        name: &str,
+        cert_pem: &str,
+        key_pem: &str,
+    ) -> Result<(String, String)> {
+        let cert_dir = self.cert_dir();
+        tokio::fs::create_dir_all(&cert_dir).await?;
+        let cert_path = cert_dir.join(format!("{}.crt", name));
+        let key_path = cert_dir.join(format!("{}.key", name));
+        tokio::fs::write(&cert_path, cert_pem).await?;
+        tokio::fs::write(&key_path, key_pem).await?;
+        Ok((
+            cert_path.to_string_lossy().into_owned(),
+            key_path.to_string_lossy().into_owned(),
+        ))
+    }
+
+    async fn server_config(&self) -> Result<Arc<rustls::ServerConfig>> {
+        let (cert_path, key_path, custom) = self.active_paths();
+        if !custom && (!cert_path.exists() || !key_path.exists()) {
+            info!("Generating new self-signed TLS certificate");
+            let cert = generate_self_signed(&[])?;
+            self.write_pair(
+                "server",
+                &cert.cert.pem(),
+                &cert.signing_key.serialize_pem(),
+            )
+            .await?;
+        } else {
+            info!("Using TLS certificate {}", cert_path.display());
+        }
+
+        let base = RustlsConfig::from_pem_file(&cert_path, &key_path).await?;
+        client_cert::with_client_auth(base.get_inner(), &self.config.get().web)
+    }
+
+    /// Build the listener's rustls config. Later calls to [`Self::reload`]
+    /// update the returned config in place.
+    pub async fn load(&self) -> Result<RustlsConfig> {
+        let rustls = RustlsConfig::from_config(self.server_config().await?);
+        *self.listener.lock().unwrap() = Some(rustls.clone());
+        Ok(rustls)
+    }
+
+    /// Re-read certificate, key and client CA into the running listener.
+    /// A no-op when HTTPS is disabled.
+    pub async fn reload(&self) -> Result<()> {
+        let Some(rustls) = self.listener.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        rustls.reload_from_config(self.server_config().await?);
+        info!("TLS configuration reloaded");
+        Ok(())
+    }
+
+    /// Details of the active certificate
+    pub async fn certificate_info(&self) -> Result<CertificateInfo> {
+        let (cert_path, _, custom) = self.active_paths();
+        let pem = tokio::fs::read(&cert_path).await.map_err(|e| {
+            AppError::NotFound(format!("Certificate {}: {}", cert_path.display(), e))
+        })?;
+        CertificateInfo::from_pem(&pem, custom)
+    }
+
+    /// Generate a key and CSR. The key is kept until a certificate is installed.
+    pub async fn create_csr(&self, common_name: &str, sans: &[String]) -> Result<String> {
+        let (key, csr) = generate_csr(common_name, sans)?;
+        tokio::fs::create_dir_all(self.cert_dir()).await?;
+        tokio::fs::write(self.pending_key_path(), key.serialize_pem()).await?;
+        Ok(csr)
+    }
+
+    /// Install a signed certificate chain as the custom certificate. Without
+    /// `key_pem`, the key of the last generated CSR is used.
+    pub async fn install_certificate(
+        &self,
+        chain_pem: &str,
+        key_pem: Option<&str>,
+    ) -> Result<CertificateInfo> {
+        let key_pem = match key_pem {
+            Some(key) => key.to_string(),
+            None => tokio::fs::read_to_string(self.pending_key_path())
+                .await
+                .map_err(|_| {
+                    AppError::BadRequest(
+                        "No private key: generate a CSR first or provide key_pem".to_string(),
+                    )
+                })?,
+        };
+        let info = CertificateInfo::from_pem(chain_pem.as_bytes(), true)?;
+        RustlsConfig::from_pem(chain_pem.as_bytes().to_vec(), key_pem.as_bytes().to_vec())
+            .await
+            .map_err(|e| {
+                AppError::BadRequest(format!("Certificate does not match the private key: {}", e))
+            })?;
+
+        let (cert_path, key_path) = self.write_pair("custom", chain_pem, &key_pem).await?;
+        self.config
+            .update(|config| {
+                config.web.ssl_cert_path = Some(cert_path);
+                config.web.ssl_key_path = Some(key_path);
+            })
+            .await?;
+        let _ = tokio::fs::remove_file(self.pending_key_path()).await;
+        self.reload().await?;
+        Ok(info)
+    }
+
+    /// Replace the self-signed certificate and make it the active one.
+    pub async fn regenerate_self_signed(&self, sans: &[String]) -> Result<CertificateInfo> {
+        let cert = generate_self_signed(sans)?;
+        let cert_pem = cert.cert.pem();
+        self.write_pair("server", &cert_pem, &cert.signing_key.serialize_pem())
+            .await?;
+        self.config
+            .update(|config| {
+                config.web.ssl_cert_path = None;
+                config.web.ssl_key_path = None;
+            })
+            .await?;
+        self.reload().await?;
+        CertificateInfo::from_pem(cert_pem.as_bytes(), false)
+    }
+
+    /// Publish `tls.cert_expiring` if the active certificate expires within
+    /// [`EXPIRY_WARNING_DAYS`].
+    pub async fn check_expiry(&self) {
+        let info = match self.certificate_info().await {
+            Ok(info) => info,
+            Err(e) => {
+                warn!("Failed to inspect TLS certificate: {}", e);
+                return;
+            }
+        };
+        if !info.expires_soon() {
+            return;
+        }
+
+        warn!(
+            "TLS certificate '{}' expires in {} days",
+            info.subject, info.days_left
+        );
+        self.events.publish(SystemEvent::TlsCertExpiring {
+            subject: info.subject,
+            not_after: info
+                .not_after
+                .format(&time::format_description::well_known::Rfc3339)
+                .unwrap_or_default(),
+            days_left: info.days_left,
+        });
+    }
+
+    /// Check expiry now and then periodically for the life of the process.
+    pub fn spawn_expiry_monitor(self: &Arc<Self>) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(EXPIRY_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                manager.check_expiry().await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_signed_info() {
+        let cert =
+            generate_self_signed(&["kvm.example.com".to_string(), "10.0.0.5".to_string()]).unwrap();
+        let info = CertificateInfo::from_pem(cert.cert.pem().as_bytes(), false).unwrap();
+
+        assert_eq!(info.sans, vec!["kvm.example.com", "10.0.0.5"]);
+        assert!(info.self_signed);
+        assert!(!info.custom);
+        assert_eq!(info.fingerprint.len(), 64);
+        // rcgen defaults to a far-future expiry
+        assert!(!info.expires_soon());
+
+        let defaults = generate_self_signed(&[]).unwrap();
+        let info = CertificateInfo::from_pem(defaults.cert.pem().as_bytes(), false).unwrap();
+        assert_eq!(info.sans, vec!["localhost", "127.0.0.1", "::1"]);
+    }
+
+    #[test]
+    fn test_csr_generation() {
+        let (key, csr) = generate_csr("kvm.example.com", &["kvm.example.com".to_string()]).unwrap();
+        assert!(csr.starts_with("-----BEGIN CERTIFICATE REQUEST-----"));
+        assert!(key.serialize_pem().contains("PRIVATE KEY"));
+
+        assert!(CertificateInfo::from_pem(b"garbage", true).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_expiring_certificate_publishes_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = crate::db::DatabasePool::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        db.init_schema().await.unwrap();
+        let config = ConfigStore::new(db.clone_pool()).unwrap();
+        let events = Arc::new(EventBus::new());
+        let manager = TlsManager::new(config, events.clone(), dir.path().to_path_buf());
+
+        let mut params = CertificateParams::new(vec!["kvm.local".to_string()]).unwrap();
+        params.not_after = OffsetDateTime::now_utc() + time::Duration::days(5);
+        let key = KeyPair::generate().unwrap();
+        let cert = params.self_signed(&key).unwrap();
+        manager
+            .write_pair("server", &cert.pem(), &key.serialize_pem())
+            .await
+            .unwrap();
+
+        let mut rx = events.subscribe();
+        manager.check_expiry().await;
+        match rx.try_recv().unwrap() {
+            SystemEvent::TlsCertExpiring { days_left, .. } => assert!(days_left <= 5),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::error::AppError;
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -25,6 +25,16 @@ impl IntoResponse for AppError {
             "Request failed"
         );
 
+        // Rate limiting must be visible to HTTP clients and proxies
+        if let AppError::RateLimited { retry_after_secs } = self {
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                Json(body),
+            )
+                .into_response();
+        }
+
         // Always return 200 OK - success/failure is indicated by the success field
         (StatusCode::OK, Json(body)).into_response()
     }
@@ -0,0 +1,82 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tracing::info;
+
+use super::LoginResponse;
+use crate::auth::ApiToken;
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    /// Lifetime in seconds; omit for a token that never expires
+    pub expires_in_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct CreateApiTokenResponse {
+    #[serde(flatten)]
+    pub token: ApiToken,
+    /// Plain-text token; only returned at creation time
+    pub secret: String,
+}
+
+pub async fn list_api_tokens(State(state): State<Arc<AppState>>) -> Result<Json<Vec<ApiToken>>> {
+    Ok(Json(state.api_tokens.list().await?))
+}
+
+pub async fn create_api_token(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateApiTokenRequest>,
+) -> Result<Json<CreateApiTokenResponse>> {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("Token name is required".to_string()));
+    }
+    if req.scopes.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one scope is required".to_string(),
+        ));
+    }
+
+    let expires_at = req
+        .expires_in_secs
+        .map(|secs| {
+            i64::try_from(secs)
+                .ok()
+                .and_then(|secs| OffsetDateTime::now_utc().checked_add(Duration::seconds(secs)))
+                .ok_or_else(|| AppError::BadRequest("Token expiry is too far away".to_string()))
+        })
+        .transpose()?;
+
+    let (token, secret) = state
+        .api_tokens
+        .create(name, req.scopes, expires_at)
+        .await?;
+    info!(
+        "API token '{}' created with scopes {:?}",
+        token.name, token.scopes
+    );
+
+    Ok(Json(CreateApiTokenResponse { token, secret }))
+}
+
+pub async fn delete_api_token(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<LoginResponse>> {
+    state.api_tokens.delete(&id).await?;
+    info!("API token {} revoked", id);
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("API token revoked".to_string()),
+    }))
+}
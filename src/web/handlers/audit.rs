@@ -0,0 +1,114 @@
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Query, State},
+    http::request::Parts,
+    Json,
+};
+use serde_json::Value;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::audit::{AuditOutcome, AuditPage, AuditQuery, AuditRecord};
+use crate::auth::{ApiToken, Session};
+use crate::config::ConfigFieldChange;
+use crate::error::Result;
+use crate::state::AppState;
+
+/// Who is performing a request and from where, for audit entries
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl FromRequestParts<Arc<AppState>> for AuditContext {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0.ip().to_string());
+
+        let actor = if let Some(token) = parts.extensions.get::<ApiToken>() {
+            Some(format!("token:{}", token.name))
+        } else if let Some(session) = parts.extensions.get::<Session>() {
+            match state.users.get(&session.user_id).await {
+                Ok(Some(user)) => Some(user.username),
+                _ => Some(session.user_id.clone()),
+            }
+        } else {
+            None
+        };
+
+        Ok(Self { actor, ip })
+    }
+}
+
+impl AuditContext {
+    /// Record the outcome of `result`. Audit failures are logged, never returned.
+    pub async fn record<T>(
+        &self,
+        state: &AppState,
+        action: &str,
+        details: Option<Value>,
+        result: &Result<T>,
+    ) {
+        let (outcome, message) = match result {
+            Ok(_) => (AuditOutcome::Success, None),
+            Err(e) => (AuditOutcome::Failure, Some(e.to_string())),
+        };
+        self.record_outcome(state, action, details, outcome, message)
+            .await;
+    }
+
+    /// Record a config section update with its field-level diff. Updates that
+    /// changed nothing are not recorded.
+    pub async fn record_config_update(
+        &self,
+        state: &AppState,
+        section: &str,
+        result: &Result<Vec<ConfigFieldChange>>,
+    ) {
+        if matches!(result, Ok(changes) if changes.is_empty()) {
+            return;
+        }
+        let changes = result.as_ref().ok();
+        let details = serde_json::json!({ "section": section, "changes": changes });
+        self.record(state, "config.update", Some(details), result)
+            .await;
+    }
+
+    pub async fn record_outcome(
+        &self,
+        state: &AppState,
+        action: &str,
+        details: Option<Value>,
+        outcome: AuditOutcome,
+        message: Option<String>,
+    ) {
+        let record = AuditRecord {
+            actor: self.actor.clone(),
+            ip: self.ip.clone(),
+            action: action.to_string(),
+            details,
+            outcome,
+            message,
+        };
+        if let Err(e) = state.audit.record(record).await {
+            warn!("Failed to write audit entry for {}: {}", action, e);
+        }
+    }
+}
+
+/// Paginated, filterable audit log (newest first)
+pub async fn list_audit_entries(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditPage>> {
+    Ok(Json(state.audit.query(&query).await?))
+}
@@ -0,0 +1,82 @@
+use axum::{extract::State, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::audit::AuditContext;
+use crate::error::{AppError, Result};
+use crate::hid::ch9329::{Ch9329Backend, Ch9329Settings, Ch9329SettingsUpdate, ChipInfo};
+use crate::hid::HidBackendType;
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct BaudRateRequest {
+    pub baud_rate: u32,
+}
+
+async fn ch9329_backend(state: &AppState) -> Result<Arc<Ch9329Backend>> {
+    state
+        .hid
+        .ch9329_backend()
+        .await
+        .ok_or_else(|| AppError::BadRequest("CH9329 backend is not active".to_string()))
+}
+
+/// Chip parameters and custom USB strings, read from the chip
+pub async fn get_settings(State(state): State<Arc<AppState>>) -> Result<Json<Ch9329Settings>> {
+    Ok(Json(ch9329_backend(&state).await?.settings().await?))
+}
+
+/// Edit the chip parameters; the chip is reset and re-enumerates on the target
+pub async fn update_settings(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(update): Json<Ch9329SettingsUpdate>,
+) -> Result<Json<Ch9329Settings>> {
+    let details = serde_json::json!({
+        "work_mode": update.work_mode,
+        "vid": update.vid,
+        "pid": update.pid,
+        "manufacturer": update.manufacturer,
+        "product": update.product,
+        "serial_number": update.serial_number,
+    });
+    let result = match ch9329_backend(&state).await {
+        Ok(backend) => backend.update_settings(&update).await,
+        Err(e) => Err(e),
+    };
+    audit
+        .record(&state, "hid.ch9329_settings", Some(details), &result)
+        .await;
+
+    Ok(Json(result?))
+}
+
+/// Switch the chip's serial rate and keep the HID config in step with it
+pub async fn switch_baud_rate(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<BaudRateRequest>,
+) -> Result<Json<ChipInfo>> {
+    let result = state.hid.switch_ch9329_baud_rate(req.baud_rate).await;
+
+    audit
+        .record(
+            &state,
+            "hid.ch9329_baud_rate",
+            Some(serde_json::json!({ "baud_rate": req.baud_rate })),
+            &result,
+        )
+        .await;
+
+    // Persist the rate the chip answers at now, even after a failed switch
+    if let HidBackendType::Ch9329 { baud_rate, .. } = state.hid.backend_type().await {
+        if state.config.get().hid.ch9329_baudrate != baud_rate {
+            state
+                .config
+                .update(|config| config.hid.ch9329_baudrate = baud_rate)
+                .await?;
+        }
+    }
+
+    Ok(Json(result?))
+}
@@ -0,0 +1,50 @@
+use axum::{
+    extract::{ConnectInfo, State},
+    Extension, Json,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::auth::access::{AccessPolicy, AccessService};
+use crate::config::AccessConfig;
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
+
+use super::types::AccessConfigUpdate;
+
+pub async fn get_access_config(State(state): State<Arc<AppState>>) -> Json<AccessConfig> {
+    Json(state.config.get().access.clone())
+}
+
+pub async fn update_access_config(
+    State(state): State<Arc<AppState>>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    audit: AuditContext,
+    Json(req): Json<AccessConfigUpdate>,
+) -> Result<Json<AccessConfig>> {
+    req.validate()?;
+
+    // Refuse rules that would lock out the client making the change
+    let mut merged = state.config.get().access.clone();
+    req.apply_to(&mut merged);
+    if let Some(Extension(ConnectInfo(peer))) = connect_info {
+        if !AccessPolicy::new(&merged, AccessService::Web).allows(peer.ip()) {
+            return Err(AppError::BadRequest(format!(
+                "Access rules would block your own address {}",
+                peer.ip()
+            )));
+        }
+    }
+
+    let result = state
+        .config
+        .update(|config| {
+            req.apply_to(&mut config.access);
+        })
+        .await;
+    audit.record_config_update(&state, "access", &result).await;
+    result?;
+
+    Ok(Json(state.config.get().access.clone()))
+}
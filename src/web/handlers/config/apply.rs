@@ -34,6 +34,11 @@ fn hid_backend_type(config: &HidConfig) -> crate::hid::HidBackendType {
             port: config.ch9329_port.clone(),
             baud_rate: config.ch9329_baudrate,
         },
+        HidBackend::Mcu => crate::hid::HidBackendType::Mcu {
+            port: config.mcu_port.clone(),
+            baud_rate: config.mcu_baudrate,
+        },
+        HidBackend::Mock => crate::hid::HidBackendType::Mock,
         HidBackend::None => crate::hid::HidBackendType::None,
     }
 }
@@ -149,6 +154,12 @@ pub async fn apply_hid_config(
     new_config: &HidConfig,
     options: ConfigApplyOptions,
 ) -> Result<()> {
+    state
+        .hid
+        .set_jiggler_config(new_config.jiggler.clone())
+        .await;
+    state.hid.set_key_presets(new_config.key_presets.clone());
+
     let current_msd_enabled = state.config.get().msd.enabled;
     new_config.validate_otg_endpoint_budget(current_msd_enabled)?;
 
@@ -158,16 +169,21 @@ pub async fn apply_hid_config(
     let hid_functions_changed = old_hid_functions != new_hid_functions;
     let keyboard_leds_changed =
         old_config.effective_otg_keyboard_leds() != new_config.effective_otg_keyboard_leds();
+    let keyboard_nkro_changed =
+        old_config.effective_otg_keyboard_nkro() != new_config.effective_otg_keyboard_nkro();
     let endpoint_budget_changed =
         old_config.resolved_otg_endpoint_limit() != new_config.resolved_otg_endpoint_limit();
 
     if old_config.backend == new_config.backend
         && old_config.ch9329_port == new_config.ch9329_port
         && old_config.ch9329_baudrate == new_config.ch9329_baudrate
+        && old_config.mcu_port == new_config.mcu_port
+        && old_config.mcu_baudrate == new_config.mcu_baudrate
         && old_config.otg_udc == new_config.otg_udc
         && !descriptor_changed
         && !hid_functions_changed
         && !keyboard_leds_changed
+        && !keyboard_nkro_changed
         && !endpoint_budget_changed
         && !options.force
     {
@@ -417,6 +433,7 @@ pub async fn apply_rustdesk_config(
                 state.stream_manager.clone(),
                 state.hid.clone(),
                 state.audio.clone(),
+                state.access.clone(),
             );
             service.start().await.map_err(|e| {
                 AppError::Config(format!("Failed to start RustDesk service: {}", e))
@@ -492,7 +509,11 @@ pub async fn apply_rtsp_config(
             || old_config.allow_one_client != new_config.allow_one_client;
 
         if rtsp_guard.is_none() {
-            let service = RtspService::new(new_config.clone(), state.stream_manager.clone());
+            let service = RtspService::new(
+                new_config.clone(),
+                state.stream_manager.clone(),
+                state.access.clone(),
+            );
             service.start().await?;
             tracing::info!("RTSP service started");
             *rtsp_guard = Some(Arc::new(service));
@@ -5,6 +5,7 @@ use crate::atx::AtxDriverType;
 use crate::config::{AtxConfig, HidBackend, HidConfig};
 use crate::error::{AppError, Result};
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_atx_config, try_apply_lock};
 use super::types::AtxConfigUpdate;
@@ -15,6 +16,7 @@ pub async fn get_atx_config(State(state): State<Arc<AppState>>) -> Json<AtxConfi
 
 pub async fn update_atx_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<AtxConfigUpdate>,
 ) -> Result<Json<AtxConfig>> {
     let current_config = state.config.get();
@@ -27,12 +29,14 @@ pub async fn update_atx_config(
     req.apply_to(&mut merged_atx_config);
     validate_serial_device_conflict(&merged_atx_config, &current_config.hid)?;
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.atx);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "atx", &result).await;
+    result?;
 
     let new_atx_config = state.config.get().atx.clone();
 
@@ -42,10 +46,11 @@ pub async fn update_atx_config(
 }
 
 fn validate_serial_device_conflict(atx: &AtxConfig, hid: &HidConfig) -> Result<()> {
-    if hid.backend != HidBackend::Ch9329 {
-        return Ok(());
-    }
-    let reserved = hid.ch9329_port.trim();
+    let (reserved, backend_name) = match hid.backend {
+        HidBackend::Ch9329 => (hid.ch9329_port.trim(), "CH9329"),
+        HidBackend::Mcu => (hid.mcu_port.trim(), "MCU bridge"),
+        _ => return Ok(()),
+    };
     if reserved.is_empty() {
         return Ok(());
     }
@@ -53,8 +58,8 @@ fn validate_serial_device_conflict(atx: &AtxConfig, hid: &HidConfig) -> Result<(
     for (name, key) in [("power", &atx.power), ("reset", &atx.reset)] {
         if key.driver == AtxDriverType::Serial && key.device.trim() == reserved {
             return Err(AppError::BadRequest(format!(
-                "ATX {} serial device '{}' conflicts with HID CH9329 serial device",
-                name, reserved
+                "ATX {} serial device '{}' conflicts with HID {} serial device",
+                name, reserved, backend_name
             )));
         }
     }
@@ -79,6 +84,19 @@ mod tests {
         assert!(validate_serial_device_conflict(&atx, &hid).is_err());
     }
 
+    #[test]
+    fn test_validate_serial_device_conflict_rejects_mcu_overlap() {
+        let mut atx = AtxConfig::default();
+        atx.reset.driver = AtxDriverType::Serial;
+        atx.reset.device = "/dev/ttyACM0".to_string();
+
+        let mut hid = HidConfig::default();
+        hid.backend = HidBackend::Mcu;
+        hid.mcu_port = "/dev/ttyACM0".to_string();
+
+        assert!(validate_serial_device_conflict(&atx, &hid).is_err());
+    }
+
     #[test]
     fn test_validate_serial_device_conflict_allows_non_ch9329_backend() {
         let mut atx = AtxConfig::default();
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use crate::config::AudioConfig;
 use crate::error::Result;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_audio_config, try_apply_lock};
 use super::types::AudioConfigUpdate;
@@ -14,6 +15,7 @@ pub async fn get_audio_config(State(state): State<Arc<AppState>>) -> Json<AudioC
 
 pub async fn update_audio_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<AudioConfigUpdate>,
 ) -> Result<Json<AudioConfig>> {
     req.validate()?;
@@ -21,12 +23,14 @@ pub async fn update_audio_config(
     let _apply_guard = try_apply_lock(&state.config_apply_locks.audio, "audio")?;
     let old_audio_config = state.config.get().audio.clone();
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.audio);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "audio", &result).await;
+    result?;
 
     let new_audio_config = state.config.get().audio.clone();
 
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use crate::config::AuthConfig;
 use crate::error::Result;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::types::AuthConfigUpdate;
 
@@ -16,15 +17,18 @@ pub async fn get_auth_config(State(state): State<Arc<AppState>>) -> Json<AuthCon
 
 pub async fn update_auth_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(update): Json<AuthConfigUpdate>,
 ) -> Result<Json<AuthConfig>> {
     update.validate()?;
-    state
+    let result = state
         .config
         .update(|config| {
             update.apply_to(&mut config.auth);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "auth", &result).await;
+    result?;
 
     let mut auth = state.config.get().auth.clone();
     auth.totp_secret = None;
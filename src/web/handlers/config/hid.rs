@@ -3,10 +3,12 @@ use std::sync::Arc;
 
 use crate::config::HidConfig;
 use crate::error::Result;
+use crate::hid::JigglerStatus;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_hid_config, try_apply_lock, ConfigApplyOptions};
-use super::types::HidConfigUpdate;
+use super::types::{HidConfigUpdate, JigglerConfigUpdate};
 
 pub async fn get_hid_config(State(state): State<Arc<AppState>>) -> Json<HidConfig> {
     Json(state.config.get().hid.clone())
@@ -14,6 +16,7 @@ pub async fn get_hid_config(State(state): State<Arc<AppState>>) -> Json<HidConfi
 
 pub async fn update_hid_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<HidConfigUpdate>,
 ) -> Result<Json<HidConfig>> {
     req.validate()?;
@@ -21,12 +24,14 @@ pub async fn update_hid_config(
     let _apply_guard = try_apply_lock(&state.config_apply_locks.otg, "otg")?;
     let old_hid_config = state.config.get().hid.clone();
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.hid);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "hid", &result).await;
+    result?;
 
     let new_hid_config = state.config.get().hid.clone();
 
@@ -40,3 +45,30 @@ pub async fn update_hid_config(
 
     Ok(Json(new_hid_config))
 }
+
+/// Mouse jiggler settings and whether it is currently moving the mouse
+pub async fn get_jiggler(State(state): State<Arc<AppState>>) -> Json<JigglerStatus> {
+    Json(state.hid.jiggler_status())
+}
+
+/// Change only the jiggler settings; no HID backend reload is needed.
+pub async fn update_jiggler(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<JigglerConfigUpdate>,
+) -> Result<Json<JigglerStatus>> {
+    req.validate()?;
+
+    let result = state
+        .config
+        .update(|config| req.apply_to(&mut config.hid.jiggler))
+        .await;
+    audit
+        .record_config_update(&state, "hid.jiggler", &result)
+        .await;
+    result?;
+
+    let jiggler = state.config.get().hid.jiggler.clone();
+    state.hid.set_jiggler_config(jiggler).await;
+    Ok(Json(state.hid.jiggler_status()))
+}
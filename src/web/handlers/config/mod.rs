@@ -1,6 +1,7 @@
 pub(crate) mod apply;
 mod types;
 
+mod access;
 mod atx;
 mod audio;
 mod auth;
@@ -12,10 +13,11 @@ mod stream;
 pub(crate) mod video;
 mod web;
 
+pub use access::{get_access_config, update_access_config};
 pub use atx::{get_atx_config, update_atx_config};
 pub use audio::{get_audio_config, update_audio_config};
 pub use auth::{get_auth_config, update_auth_config};
-pub use hid::{get_hid_config, update_hid_config};
+pub use hid::{get_hid_config, get_jiggler, update_hid_config, update_jiggler};
 pub use msd::{get_msd_config, update_msd_config};
 pub use rtsp::{get_rtsp_config, get_rtsp_status, update_rtsp_config};
 pub use rustdesk::{
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use crate::config::MsdConfig;
 use crate::error::Result;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_msd_config, try_apply_lock, ConfigApplyOptions};
 use super::types::MsdConfigUpdate;
@@ -14,6 +15,7 @@ pub async fn get_msd_config(State(state): State<Arc<AppState>>) -> Json<MsdConfi
 
 pub async fn update_msd_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<MsdConfigUpdate>,
 ) -> Result<Json<MsdConfig>> {
     req.validate()?;
@@ -21,12 +23,14 @@ pub async fn update_msd_config(
     let _apply_guard = try_apply_lock(&state.config_apply_locks.otg, "otg")?;
     let old_msd_config = state.config.get().msd.clone();
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.msd);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "msd", &result).await;
+    result?;
 
     let new_msd_config = state.config.get().msd.clone();
 
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use crate::error::Result;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_rtsp_config, try_apply_lock, ConfigApplyOptions};
 use super::types::{RtspConfigResponse, RtspConfigUpdate, RtspStatusResponse};
@@ -28,6 +29,7 @@ pub async fn get_rtsp_status(State(state): State<Arc<AppState>>) -> Json<RtspSta
 
 pub async fn update_rtsp_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<RtspConfigUpdate>,
 ) -> Result<Json<RtspConfigResponse>> {
     req.validate()?;
@@ -35,12 +37,14 @@ pub async fn update_rtsp_config(
     let _apply_guard = try_apply_lock(&state.config_apply_locks.rtsp, "rtsp")?;
     let old_config = state.config.get().rtsp.clone();
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.rtsp);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "rtsp", &result).await;
+    result?;
 
     let new_config = state.config.get().rtsp.clone();
     apply_rtsp_config(
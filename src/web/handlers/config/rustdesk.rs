@@ -4,6 +4,7 @@ use std::sync::Arc;
 use crate::error::Result;
 use crate::rustdesk::config::RustDeskConfig;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_rustdesk_config, try_apply_lock, ConfigApplyOptions};
 use super::types::RustDeskConfigUpdate;
@@ -71,6 +72,7 @@ pub async fn get_rustdesk_status(
 
 pub async fn update_rustdesk_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<RustDeskConfigUpdate>,
 ) -> Result<Json<RustDeskConfigResponse>> {
     req.validate()?;
@@ -78,12 +80,16 @@ pub async fn update_rustdesk_config(
     let _apply_guard = try_apply_lock(&state.config_apply_locks.rustdesk, "rustdesk")?;
     let old_config = state.config.get().rustdesk.clone();
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.rustdesk);
         })
-        .await?;
+        .await;
+    audit
+        .record_config_update(&state, "rustdesk", &result)
+        .await;
+    result?;
 
     let new_config = state.config.get().rustdesk.clone();
 
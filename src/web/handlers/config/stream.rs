@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use crate::error::Result;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_stream_config, try_apply_lock, ConfigApplyOptions};
 use super::types::{StreamConfigResponse, StreamConfigUpdate};
@@ -14,6 +15,7 @@ pub async fn get_stream_config(State(state): State<Arc<AppState>>) -> Json<Strea
 
 pub async fn update_stream_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<StreamConfigUpdate>,
 ) -> Result<Json<StreamConfigResponse>> {
     req.validate()?;
@@ -21,12 +23,14 @@ pub async fn update_stream_config(
     let _apply_guard = try_apply_lock(&state.config_apply_locks.stream, "stream")?;
     let old_stream_config = state.config.get().stream.clone();
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.stream);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "stream", &result).await;
+    result?;
 
     let new_stream_config = state.config.get().stream.clone();
 
@@ -11,10 +11,52 @@ use typeshare::typeshare;
 #[derive(Debug, Deserialize)]
 pub struct AuthConfigUpdate {
     pub single_user_allow_multiple_sessions: Option<bool>,
+    pub persist_sessions: Option<bool>,
+    pub login_max_failures: Option<u32>,
+    pub login_lockout_secs: Option<u32>,
+    pub login_backoff_secs: Option<u32>,
+    pub audit_retention_days: Option<u32>,
+    pub proxy_auth_enabled: Option<bool>,
+    pub proxy_auth_header: Option<String>,
+    pub proxy_auth_trusted_cidrs: Option<Vec<String>>,
+    /// Empty string clears the default role
+    pub proxy_auth_default_role: Option<String>,
 }
 
 impl AuthConfigUpdate {
     pub fn validate(&self) -> crate::error::Result<()> {
+        if let Some(secs) = self.login_lockout_secs {
+            if !(1..=86400).contains(&secs) {
+                return Err(AppError::BadRequest(
+                    "Invalid login_lockout_secs: must be 1-86400".into(),
+                ));
+            }
+        }
+        if let Some(secs) = self.login_backoff_secs {
+            if secs > 60 {
+                return Err(AppError::BadRequest(
+                    "Invalid login_backoff_secs: must be 0-60".into(),
+                ));
+            }
+        }
+        if let Some(ref header) = self.proxy_auth_header {
+            if axum::http::HeaderName::from_bytes(header.trim().as_bytes()).is_err() {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid proxy_auth_header: {}",
+                    header
+                )));
+            }
+        }
+        if let Some(ref cidrs) = self.proxy_auth_trusted_cidrs {
+            for cidr in cidrs {
+                crate::auth::access::parse_cidr(cidr)?;
+            }
+        }
+        if let Some(ref role) = self.proxy_auth_default_role {
+            if !role.is_empty() {
+                role.parse::<crate::auth::UserRole>()?;
+            }
+        }
         Ok(())
     }
 
@@ -22,6 +64,73 @@ impl AuthConfigUpdate {
         if let Some(allow_multiple) = self.single_user_allow_multiple_sessions {
             config.single_user_allow_multiple_sessions = allow_multiple;
         }
+        if let Some(persist) = self.persist_sessions {
+            config.persist_sessions = persist;
+        }
+        if let Some(max_failures) = self.login_max_failures {
+            config.login_max_failures = max_failures;
+        }
+        if let Some(secs) = self.login_lockout_secs {
+            config.login_lockout_secs = secs;
+        }
+        if let Some(secs) = self.login_backoff_secs {
+            config.login_backoff_secs = secs;
+        }
+        if let Some(days) = self.audit_retention_days {
+            config.audit_retention_days = days;
+        }
+        if let Some(enabled) = self.proxy_auth_enabled {
+            config.proxy_auth_enabled = enabled;
+        }
+        if let Some(ref header) = self.proxy_auth_header {
+            config.proxy_auth_header = header.trim().to_string();
+        }
+        if let Some(ref cidrs) = self.proxy_auth_trusted_cidrs {
+            config.proxy_auth_trusted_cidrs = cidrs.iter().map(|c| c.trim().to_string()).collect();
+        }
+        if let Some(ref role) = self.proxy_auth_default_role {
+            config.proxy_auth_default_role = (!role.is_empty()).then(|| role.clone());
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Debug, Deserialize)]
+pub struct AccessConfigUpdate {
+    pub global: Option<AccessRules>,
+    pub web: Option<AccessRules>,
+    pub rtsp: Option<AccessRules>,
+    pub rustdesk: Option<AccessRules>,
+}
+
+impl AccessConfigUpdate {
+    pub fn validate(&self) -> crate::error::Result<()> {
+        for rules in [&self.global, &self.web, &self.rtsp, &self.rustdesk]
+            .into_iter()
+            .flatten()
+        {
+            crate::auth::access::validate_rules(rules)?;
+        }
+        Ok(())
+    }
+
+    pub fn apply_to(&self, config: &mut AccessConfig) {
+        let trimmed = |rules: &AccessRules| AccessRules {
+            allow: rules.allow.iter().map(|c| c.trim().to_string()).collect(),
+            deny: rules.deny.iter().map(|c| c.trim().to_string()).collect(),
+        };
+        if let Some(ref rules) = self.global {
+            config.global = trimmed(rules);
+        }
+        if let Some(ref rules) = self.web {
+            config.web = trimmed(rules);
+        }
+        if let Some(ref rules) = self.rtsp {
+            config.rtsp = trimmed(rules);
+        }
+        if let Some(ref rules) = self.rustdesk {
+            config.rustdesk = trimmed(rules);
+        }
     }
 }
 
@@ -273,6 +382,7 @@ pub struct OtgHidFunctionsUpdate {
     pub mouse_relative: Option<bool>,
     pub mouse_absolute: Option<bool>,
     pub consumer: Option<bool>,
+    pub system_control: Option<bool>,
 }
 
 impl OtgHidFunctionsUpdate {
@@ -289,6 +399,41 @@ impl OtgHidFunctionsUpdate {
         if let Some(enabled) = self.consumer {
             config.consumer = enabled;
         }
+        if let Some(enabled) = self.system_control {
+            config.system_control = enabled;
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Debug, Deserialize)]
+pub struct JigglerConfigUpdate {
+    pub enabled: Option<bool>,
+    pub mode: Option<JigglerMode>,
+    pub interval_secs: Option<u32>,
+    pub idle_secs: Option<u32>,
+}
+
+impl JigglerConfigUpdate {
+    pub fn validate(&self) -> crate::error::Result<()> {
+        let mut config = JigglerConfig::default();
+        self.apply_to(&mut config);
+        crate::hid::jiggler::validate_config(&config)
+    }
+
+    pub fn apply_to(&self, config: &mut JigglerConfig) {
+        if let Some(enabled) = self.enabled {
+            config.enabled = enabled;
+        }
+        if let Some(mode) = self.mode {
+            config.mode = mode;
+        }
+        if let Some(interval) = self.interval_secs {
+            config.interval_secs = interval;
+        }
+        if let Some(idle) = self.idle_secs {
+            config.idle_secs = idle;
+        }
     }
 }
 
@@ -298,19 +443,28 @@ pub struct HidConfigUpdate {
     pub backend: Option<HidBackend>,
     pub ch9329_port: Option<String>,
     pub ch9329_baudrate: Option<u32>,
+    pub mcu_port: Option<String>,
+    pub mcu_baudrate: Option<u32>,
     pub otg_udc: Option<String>,
     pub otg_descriptor: Option<OtgDescriptorConfigUpdate>,
     pub otg_profile: Option<OtgHidProfile>,
     pub otg_endpoint_budget: Option<OtgEndpointBudget>,
     pub otg_functions: Option<OtgHidFunctionsUpdate>,
     pub otg_keyboard_leds: Option<bool>,
+    pub otg_keyboard_nkro: Option<bool>,
     pub mouse_absolute: Option<bool>,
+    pub jiggler: Option<JigglerConfigUpdate>,
+    #[typeshare(skip)]
+    pub key_presets: Option<Vec<KeyPreset>>,
 }
 
 impl HidConfigUpdate {
     pub fn validate(&self) -> crate::error::Result<()> {
-        if let Some(baudrate) = self.ch9329_baudrate {
-            let valid_rates = [9600, 19200, 38400, 57600, 115200];
+        let valid_rates = [9600, 19200, 38400, 57600, 115200];
+        for baudrate in [self.ch9329_baudrate, self.mcu_baudrate]
+            .into_iter()
+            .flatten()
+        {
             if !valid_rates.contains(&baudrate) {
                 return Err(AppError::BadRequest(
                     "Invalid baudrate: must be 9600, 19200, 38400, 57600, or 115200".into(),
@@ -320,6 +474,12 @@ impl HidConfigUpdate {
         if let Some(ref desc) = self.otg_descriptor {
             desc.validate()?;
         }
+        if let Some(ref jiggler) = self.jiggler {
+            jiggler.validate()?;
+        }
+        if let Some(ref presets) = self.key_presets {
+            crate::hid::presets::validate_presets(presets)?;
+        }
         Ok(())
     }
 
@@ -333,6 +493,12 @@ impl HidConfigUpdate {
         if let Some(baudrate) = self.ch9329_baudrate {
             config.ch9329_baudrate = baudrate;
         }
+        if let Some(ref port) = self.mcu_port {
+            config.mcu_port = port.clone();
+        }
+        if let Some(baudrate) = self.mcu_baudrate {
+            config.mcu_baudrate = baudrate;
+        }
         if let Some(ref udc) = self.otg_udc {
             config.otg_udc = Some(udc.clone());
         }
@@ -351,9 +517,18 @@ impl HidConfigUpdate {
         if let Some(enabled) = self.otg_keyboard_leds {
             config.otg_keyboard_leds = enabled;
         }
+        if let Some(enabled) = self.otg_keyboard_nkro {
+            config.otg_keyboard_nkro = enabled;
+        }
         if let Some(absolute) = self.mouse_absolute {
             config.mouse_absolute = absolute;
         }
+        if let Some(ref jiggler) = self.jiggler {
+            jiggler.apply_to(&mut config.jiggler);
+        }
+        if let Some(ref presets) = self.key_presets {
+            config.key_presets = presets.clone();
+        }
     }
 }
 
@@ -899,6 +1074,10 @@ pub struct WebConfigResponse {
     pub https_enabled: bool,
     /// Whether a custom TLS certificate is active (non-empty cert + key paths in stored config).
     pub has_custom_cert: bool,
+    /// Whether a client CA bundle is installed
+    pub has_client_ca: bool,
+    pub client_cert_mode: ClientCertMode,
+    pub client_cert_default_role: Option<String>,
 }
 
 impl WebConfigResponse {
@@ -920,6 +1099,9 @@ impl WebConfigResponse {
             bind_address: web.bind_address.clone(),
             https_enabled: web.https_enabled,
             has_custom_cert,
+            has_client_ca: web.client_ca_path.as_deref().is_some_and(|p| !p.is_empty()),
+            client_cert_mode: web.client_cert_mode,
+            client_cert_default_role: web.client_cert_default_role.clone(),
         }
     }
 }
@@ -938,6 +1120,13 @@ pub struct WebConfigUpdate {
     pub ssl_key_pem: Option<String>,
     /// Set to true to remove the custom certificate and revert to self-signed
     pub clear_custom_cert: Option<bool>,
+    /// PEM bundle of CAs trusted to issue client certificates
+    pub client_ca_pem: Option<String>,
+    /// Set to true to remove the client CA; also turns client certificates off
+    pub clear_client_ca: Option<bool>,
+    pub client_cert_mode: Option<ClientCertMode>,
+    /// Empty string clears the default role
+    pub client_cert_default_role: Option<String>,
 }
 
 impl WebConfigUpdate {
@@ -980,6 +1169,14 @@ impl WebConfigUpdate {
             }
             (None, None) => {}
         }
+        if let Some(ref pem) = self.client_ca_pem {
+            crate::auth::client_cert::parse_client_ca(pem.as_bytes())?;
+        }
+        if let Some(ref role) = self.client_cert_default_role {
+            if !role.is_empty() {
+                role.parse::<crate::auth::UserRole>()?;
+            }
+        }
         Ok(())
     }
 
@@ -1004,8 +1201,14 @@ impl WebConfigUpdate {
         if let Some(enabled) = self.https_enabled {
             config.https_enabled = enabled;
         }
-        // ssl_cert_pem, ssl_key_pem, clear_custom_cert are handled at the handler level
-        // (they require async file I/O before updating config paths)
+        if let Some(mode) = self.client_cert_mode {
+            config.client_cert_mode = mode;
+        }
+        if let Some(ref role) = self.client_cert_default_role {
+            config.client_cert_default_role = (!role.is_empty()).then(|| role.clone());
+        }
+        // ssl_cert_pem, ssl_key_pem, clear_custom_cert, client_ca_pem and clear_client_ca are
+        // handled at the handler level (they require async file I/O before updating config paths)
     }
 }
 
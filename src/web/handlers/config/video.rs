@@ -4,6 +4,7 @@ use std::sync::Arc;
 use crate::config::VideoConfig;
 use crate::error::Result;
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::apply::{apply_video_config, try_apply_lock, ConfigApplyOptions};
 use super::types::VideoConfigUpdate;
@@ -14,6 +15,7 @@ pub async fn get_video_config(State(state): State<Arc<AppState>>) -> Json<VideoC
 
 pub async fn update_video_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<VideoConfigUpdate>,
 ) -> Result<Json<VideoConfig>> {
     req.validate()?;
@@ -21,12 +23,14 @@ pub async fn update_video_config(
     let _apply_guard = try_apply_lock(&state.config_apply_locks.video, "video")?;
     let old_video_config = state.config.get().video.clone();
 
-    state
+    let result = state
         .config
         .update(|config| {
             req.apply_to(&mut config.video);
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "video", &result).await;
+    result?;
 
     let new_video_config = state.config.get().video.clone();
 
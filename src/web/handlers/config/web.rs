@@ -1,9 +1,12 @@
-use axum::{extract::State, Json};
+use axum::{extract::State, Extension, Json};
 use axum_server::tls_rustls::RustlsConfig;
 use std::sync::Arc;
 
+use crate::auth::client_cert::ClientCertificate;
+use crate::config::ClientCertMode;
 use crate::error::{AppError, Result};
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 use super::types::{WebConfigResponse, WebConfigUpdate};
 
@@ -13,6 +16,8 @@ pub async fn get_web_config(State(state): State<Arc<AppState>>) -> Json<WebConfi
 
 pub async fn update_web_config(
     State(state): State<Arc<AppState>>,
+    client_cert: Option<Extension<Option<ClientCertificate>>>,
+    audit: AuditContext,
     Json(req): Json<WebConfigUpdate>,
 ) -> Result<Json<WebConfigResponse>> {
     req.validate()?;
@@ -62,10 +67,73 @@ pub async fn update_web_config(
         None
     };
 
-    state
+    // Same shape as `cert_path_update`, for the client CA bundle. `validate()` has
+    // already parsed the PEM; it is only put in place once the config update succeeds.
+    let cert_dir = state.data_dir().join("certs");
+    let ca_path = cert_dir.join("client-ca.pem");
+    let client_ca_update: Option<Option<String>> = if req.client_ca_pem.is_some() {
+        Some(Some(ca_path.to_string_lossy().into_owned()))
+    } else if req.clear_client_ca.unwrap_or(false) {
+        Some(None)
+    } else {
+        None
+    };
+
+    let current = state.config.get().web.clone();
+    let has_client_ca = match &client_ca_update {
+        Some(update) => update.is_some(),
+        None => current.client_ca_path.is_some(),
+    };
+    let mode = req.client_cert_mode.unwrap_or(current.client_cert_mode);
+    if mode != ClientCertMode::Off && !has_client_ca && req.clear_client_ca != Some(true) {
+        return Err(AppError::BadRequest(
+            "Upload a client CA before enabling client certificates".into(),
+        ));
+    }
+
+    // Refuse settings that would lock out the client making the change: requiring
+    // certificates needs one already presented, verified against the CA in use
+    let client_auth_changed = req.client_cert_mode.is_some() || req.client_ca_pem.is_some();
+    if mode == ClientCertMode::Required && has_client_ca && client_auth_changed {
+        if req.client_ca_pem.is_some() {
+            return Err(AppError::BadRequest(
+                "Set client certificates to optional before replacing the client CA".into(),
+            ));
+        }
+        if client_cert.and_then(|Extension(cert)| cert).is_none() {
+            return Err(AppError::BadRequest(
+                "Connect with a valid client certificate before requiring one".into(),
+            ));
+        }
+    }
+
+    let pending_ca_path = cert_dir.join("client-ca.pem.tmp");
+    if let Some(ref ca_pem) = req.client_ca_pem {
+        tokio::fs::create_dir_all(&cert_dir)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to create cert dir: {e}")))?;
+        tokio::fs::write(&pending_ca_path, ca_pem.as_bytes())
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to write client CA: {e}")))?;
+    }
+
+    let tls_changed =
+        cert_path_update.is_some() || client_ca_update.is_some() || req.client_cert_mode.is_some();
+    let installs_ca = matches!(client_ca_update, Some(Some(_)));
+    let clears_ca = matches!(client_ca_update, Some(None));
+
+    let result = state
         .config
         .update(move |config| {
             req.apply_to(&mut config.web);
+            match client_ca_update {
+                Some(Some(ca_path)) => config.web.client_ca_path = Some(ca_path),
+                Some(None) => {
+                    config.web.client_ca_path = None;
+                    config.web.client_cert_mode = ClientCertMode::Off;
+                }
+                None => {}
+            }
             match cert_path_update {
                 Some(Some((cert_path, key_path))) => {
                     config.web.ssl_cert_path = Some(cert_path);
@@ -78,7 +146,27 @@ pub async fn update_web_config(
                 None => {}
             }
         })
-        .await?;
+        .await;
+    audit.record_config_update(&state, "web", &result).await;
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&pending_ca_path).await;
+        return Err(e);
+    }
+
+    if installs_ca {
+        tokio::fs::rename(&pending_ca_path, &ca_path)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to install client CA: {e}")))?;
+    } else if clears_ca {
+        let _ = tokio::fs::remove_file(&ca_path).await;
+    }
+
+    // New certificate or client CA settings apply to new connections right away
+    if tls_changed {
+        if let Err(e) = state.tls.reload().await {
+            tracing::warn!("Failed to reload TLS configuration: {}", e);
+        }
+    }
 
     Ok(Json(WebConfigResponse::from_stored(
         &state.config.get().web,
@@ -100,4 +188,70 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn requiring_client_certs_needs_a_presented_certificate() {
+        let _ = CryptoProvider::install_default(ring::default_provider());
+        let dir = tempfile::tempdir().unwrap();
+        let state = AppState::for_tests(dir.path()).await;
+        let ca_file = dir.path().join("certs").join("client-ca.pem");
+        let ca_pem = rcgen::generate_simple_self_signed(vec!["ca".into()])
+            .unwrap()
+            .cert
+            .pem();
+        let update = |value: serde_json::Value| {
+            Json(serde_json::from_value::<WebConfigUpdate>(value).unwrap())
+        };
+
+        // Uploading a CA and requiring certificates at once is refused before anything is written
+        let result = update_web_config(
+            State(state.clone()),
+            None,
+            AuditContext::default(),
+            update(serde_json::json!({ "client_ca_pem": ca_pem, "client_cert_mode": "required" })),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!ca_file.exists());
+
+        let Json(response) = update_web_config(
+            State(state.clone()),
+            None,
+            AuditContext::default(),
+            update(serde_json::json!({ "client_ca_pem": ca_pem, "client_cert_mode": "optional" })),
+        )
+        .await
+        .unwrap();
+        assert!(response.has_client_ca);
+        assert!(ca_file.exists());
+
+        let required = serde_json::json!({ "client_cert_mode": "required" });
+        let result = update_web_config(
+            State(state.clone()),
+            None,
+            AuditContext::default(),
+            update(required.clone()),
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(
+            state.config.get().web.client_cert_mode,
+            ClientCertMode::Optional
+        );
+
+        let cert = ClientCertificate {
+            subject: "CN=admin".to_string(),
+            common_name: Some("admin".to_string()),
+            fingerprint: String::new(),
+        };
+        let Json(response) = update_web_config(
+            State(state.clone()),
+            Some(Extension(Some(cert))),
+            AuditContext::default(),
+            update(required),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.client_cert_mode, ClientCertMode::Required);
+    }
 }
@@ -12,6 +12,7 @@ use crate::extensions::{
     GostcConfig, GostcInfo, TtydConfig, TtydInfo,
 };
 use crate::state::AppState;
+use crate::web::handlers::audit::AuditContext;
 
 pub async fn list_extensions(State(state): State<Arc<AppState>>) -> Json<ExtensionsStatus> {
     let config = state.config.get();
@@ -140,11 +141,12 @@ pub struct EasytierConfigUpdate {
 
 pub async fn update_ttyd_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<TtydConfigUpdate>,
 ) -> Result<Json<TtydConfig>> {
     let was_enabled = state.config.get().extensions.ttyd.enabled;
 
-    state
+    let result = state
         .config
         .update(|config| {
             let ttyd = &mut config.extensions.ttyd;
@@ -155,7 +157,11 @@ pub async fn update_ttyd_config(
                 ttyd.shell = shell.clone();
             }
         })
-        .await?;
+        .await;
+    audit
+        .record_config_update(&state, "extensions.ttyd", &result)
+        .await;
+    result?;
 
     let new_config = state.config.get();
     let is_enabled = new_config.extensions.ttyd.enabled;
@@ -177,11 +183,12 @@ pub async fn update_ttyd_config(
 
 pub async fn update_gostc_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<GostcConfigUpdate>,
 ) -> Result<Json<GostcConfig>> {
     let was_enabled = state.config.get().extensions.gostc.enabled;
 
-    state
+    let result = state
         .config
         .update(|config| {
             let gostc = &mut config.extensions.gostc;
@@ -198,7 +205,11 @@ pub async fn update_gostc_config(
                 gostc.tls = tls;
             }
         })
-        .await?;
+        .await;
+    audit
+        .record_config_update(&state, "extensions.gostc", &result)
+        .await;
+    result?;
 
     let new_config = state.config.get();
     let is_enabled = new_config.extensions.gostc.enabled;
@@ -225,11 +236,12 @@ pub async fn update_gostc_config(
 
 pub async fn update_easytier_config(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<EasytierConfigUpdate>,
 ) -> Result<Json<EasytierConfig>> {
     let was_enabled = state.config.get().extensions.easytier.enabled;
 
-    state
+    let result = state
         .config
         .update(|config| {
             let et = &mut config.extensions.easytier;
@@ -249,7 +261,11 @@ pub async fn update_easytier_config(
                 et.virtual_ip = req.virtual_ip.clone();
             }
         })
-        .await?;
+        .await;
+    audit
+        .record_config_update(&state, "extensions.easytier", &result)
+        .await;
+    result?;
 
     let new_config = state.config.get();
     let is_enabled = new_config.extensions.easytier.enabled;
@@ -0,0 +1,69 @@
+use axum::{extract::State, Extension, Json};
+use std::sync::Arc;
+
+use super::audit::AuditContext;
+use crate::auth::Session;
+use crate::error::Result;
+use crate::hid::control::{ControlHolder, ControlStatus};
+use crate::state::AppState;
+
+/// Current control holder and every connected input client
+pub async fn get_control(State(state): State<Arc<AppState>>) -> Json<ControlStatus> {
+    Json(state.hid.control().status())
+}
+
+async fn take_control(
+    state: &AppState,
+    session: &Session,
+    audit: AuditContext,
+    force: bool,
+) -> Result<Json<ControlHolder>> {
+    let owner = state.hid_control_owner(session).await;
+    let previous = state.hid.control().holder();
+    let result = state.hid.control().acquire(owner, force);
+    let action = if force {
+        "hid.control_force_take"
+    } else {
+        "hid.control_take"
+    };
+    let details = previous
+        .filter(|_| force)
+        .map(|holder| serde_json::json!({ "previous_holder": holder.owner.name }));
+    audit.record(state, action, details, &result).await;
+
+    Ok(Json(result?))
+}
+
+/// Take exclusive control; fails while another user holds it.
+pub async fn request_control(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    audit: AuditContext,
+) -> Result<Json<ControlHolder>> {
+    take_control(&state, &session, audit, false).await
+}
+
+/// Take control away from the current holder.
+pub async fn force_take_control(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    audit: AuditContext,
+) -> Result<Json<ControlHolder>> {
+    take_control(&state, &session, audit, true).await
+}
+
+/// Give up control so every connected client may send input again.
+pub async fn release_control(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    audit: AuditContext,
+) -> Result<Json<ControlStatus>> {
+    let owner = state.hid_control_owner(&session).await;
+    let result = state.hid.control().release(&owner.id, false);
+    audit
+        .record(&state, "hid.control_release", None, &result)
+        .await;
+    result?;
+
+    Ok(Json(state.hid.control().status()))
+}
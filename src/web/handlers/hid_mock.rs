@@ -0,0 +1,97 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::audit::AuditContext;
+use super::LoginResponse;
+use crate::error::{AppError, Result};
+use crate::hid::{LedState, MockBackend, MockFault, MockReport};
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct MockReportsQuery {
+    /// Only reports with a larger sequence number
+    #[serde(default)]
+    pub since: u64,
+}
+
+#[derive(Serialize)]
+pub struct MockReportsResponse {
+    pub reports: Vec<MockReport>,
+}
+
+#[derive(Deserialize)]
+pub struct MockControlRequest {
+    /// LED state the simulated target reports
+    pub led_state: Option<LedState>,
+    /// Fail every send with this error until cleared
+    pub fault: Option<MockFault>,
+    #[serde(default)]
+    pub clear_fault: bool,
+}
+
+async fn mock_backend(state: &AppState) -> Result<Arc<MockBackend>> {
+    state
+        .hid
+        .mock_backend()
+        .await
+        .ok_or_else(|| AppError::BadRequest("Mock HID backend is not active".to_string()))
+}
+
+/// Reports the mock backend recorded, oldest first
+pub async fn list_reports(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<MockReportsQuery>,
+) -> Result<Json<MockReportsResponse>> {
+    let backend = mock_backend(&state).await?;
+    Ok(Json(MockReportsResponse {
+        reports: backend.reports(query.since),
+    }))
+}
+
+pub async fn clear_reports(State(state): State<Arc<AppState>>) -> Result<Json<LoginResponse>> {
+    mock_backend(&state).await?.clear_reports();
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("Mock HID report log cleared".to_string()),
+    }))
+}
+
+/// Simulate LED changes and backend errors
+pub async fn update_mock(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<MockControlRequest>,
+) -> Result<Json<LoginResponse>> {
+    let result = mock_backend(&state).await.map(|backend| {
+        if let Some(led_state) = req.led_state {
+            backend.set_led_state(led_state);
+        }
+        if req.clear_fault {
+            backend.set_fault(None);
+        } else if let Some(fault) = req.fault.clone() {
+            backend.set_fault(Some(fault));
+        }
+    });
+    audit
+        .record(
+            &state,
+            "hid.mock_update",
+            Some(serde_json::json!({
+                "led_state": req.led_state,
+                "fault": req.fault,
+                "clear_fault": req.clear_fault,
+            })),
+            &result,
+        )
+        .await;
+    result?;
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("Mock HID backend updated".to_string()),
+    }))
+}
@@ -0,0 +1,198 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+use super::audit::AuditContext;
+use super::LoginResponse;
+use crate::auth::Session;
+use crate::error::{AppError, Result};
+use crate::hid::{HidMacro, HidMacroSummary, MacroStep};
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+pub struct ImportMacroRequest {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+#[derive(Deserialize)]
+pub struct StopRecordingRequest {
+    /// Name to save the recording under; required unless discarding
+    pub name: Option<String>,
+    #[serde(default)]
+    pub discard: bool,
+}
+
+#[derive(Serialize)]
+pub struct RecordingStatus {
+    pub recording: bool,
+    pub steps: usize,
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize)]
+pub struct PlayMacroRequest {
+    /// Playback speed multiplier (0.1-10)
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+}
+
+impl Default for PlayMacroRequest {
+    fn default() -> Self {
+        Self {
+            speed: default_speed(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PlayMacroResponse {
+    pub run_id: String,
+}
+
+pub async fn list_macros(State(state): State<Arc<AppState>>) -> Result<Json<Vec<HidMacroSummary>>> {
+    let macros = state.macros.list().await?;
+    Ok(Json(macros.iter().map(HidMacroSummary::from).collect()))
+}
+
+/// Full macro with its steps; the JSON can be imported again as is.
+pub async fn export_macro(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<HidMacro>> {
+    state
+        .macros
+        .get(&id)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("Macro not found".to_string()))
+}
+
+pub async fn import_macro(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<ImportMacroRequest>,
+) -> Result<Json<HidMacroSummary>> {
+    let result = state.macros.create(&req.name, req.steps).await;
+    let details = result
+        .as_ref()
+        .ok()
+        .map(|m| serde_json::json!({ "id": m.id, "name": m.name, "steps": m.steps.len() }));
+    audit
+        .record(&state, "hid.macro_import", details, &result)
+        .await;
+
+    Ok(Json(HidMacroSummary::from(&result?)))
+}
+
+pub async fn delete_macro(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<String>,
+) -> Result<Json<LoginResponse>> {
+    let result = state.macros.delete(&id).await;
+    audit
+        .record(
+            &state,
+            "hid.macro_delete",
+            Some(serde_json::json!({ "id": id })),
+            &result,
+        )
+        .await;
+    result?;
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("Macro deleted".to_string()),
+    }))
+}
+
+pub async fn recording_status(State(state): State<Arc<AppState>>) -> Json<RecordingStatus> {
+    let steps = state.hid.recording_steps();
+    Json(RecordingStatus {
+        recording: steps.is_some(),
+        steps: steps.unwrap_or(0),
+    })
+}
+
+/// Start capturing the HID events sent by every client.
+pub async fn start_recording(State(state): State<Arc<AppState>>) -> Result<Json<RecordingStatus>> {
+    state.hid.start_recording()?;
+    Ok(Json(RecordingStatus {
+        recording: true,
+        steps: 0,
+    }))
+}
+
+/// Stop capturing and save the recording as a new macro.
+pub async fn stop_recording(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<StopRecordingRequest>,
+) -> Result<Json<Option<HidMacroSummary>>> {
+    let name = req.name.as_deref().map(str::trim).unwrap_or_default();
+    if !req.discard && name.is_empty() {
+        return Err(AppError::BadRequest("Macro name is required".to_string()));
+    }
+
+    let steps = state.hid.stop_recording()?;
+    if req.discard {
+        info!("Discarded macro recording with {} steps", steps.len());
+        return Ok(Json(None));
+    }
+
+    let result = state.macros.create(name, steps).await;
+    let details = result
+        .as_ref()
+        .ok()
+        .map(|m| serde_json::json!({ "id": m.id, "name": m.name, "steps": m.steps.len() }));
+    audit
+        .record(&state, "hid.macro_record", details, &result)
+        .await;
+
+    Ok(Json(Some(HidMacroSummary::from(&result?))))
+}
+
+pub async fn play_macro(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    audit: AuditContext,
+    Path(id): Path<String>,
+    req: Option<Json<PlayMacroRequest>>,
+) -> Result<Json<PlayMacroResponse>> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let owner = state.hid_control_owner(&session).await;
+    let result = state.hid.play_macro_by_id(&owner.id, &id, req.speed).await;
+    audit
+        .record(
+            &state,
+            "hid.macro_play",
+            Some(serde_json::json!({ "id": id, "speed": req.speed })),
+            &result,
+        )
+        .await;
+
+    Ok(Json(PlayMacroResponse { run_id: result? }))
+}
+
+pub async fn abort_macro(State(state): State<Arc<AppState>>) -> Json<LoginResponse> {
+    let aborted = state.hid.abort_macro();
+    Json(LoginResponse {
+        success: aborted,
+        message: Some(
+            if aborted {
+                "Macro aborted"
+            } else {
+                "No macro is playing"
+            }
+            .to_string(),
+        ),
+    })
+}
@@ -1,18 +1,38 @@
+pub mod api_tokens;
+pub mod audit;
+pub mod ch9329;
 pub mod config;
 pub mod devices;
 pub mod extensions;
+pub mod hid_control;
+pub mod hid_mock;
+pub mod macros;
+pub mod presets;
+pub mod sessions;
+pub mod shares;
 pub mod terminal;
+pub mod tls;
+pub mod totp;
+pub mod users;
 
-use axum::{extract::State, Json};
+use axum::{
+    extract::{ConnectInfo, State},
+    Json,
+};
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use tracing::{info, warn};
 
+use self::audit::AuditContext;
 use self::config::apply::ConfigApplyOptions;
-use crate::auth::{Session, SESSION_COOKIE};
-use crate::config::StreamMode;
+use crate::audit::AuditOutcome;
+use crate::auth::share::share_link_id;
+use crate::auth::{session_key, Session, ShareLink, UserRole, SESSION_COOKIE};
+use crate::config::{AuthConfig, StreamMode};
 use crate::error::{AppError, Result};
+use crate::events::SystemEvent;
 use crate::state::AppState;
 use crate::update::{UpdateChannel, UpdateOverviewResponse, UpdateStatusResponse, UpgradeRequest};
 use crate::utils::{hostname_uname, list_dir_names, read_trimmed};
@@ -446,6 +466,9 @@ mod tests {
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
+    /// TOTP or recovery code, required when two-factor authentication is enabled
+    #[serde(default)]
+    pub totp_code: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -454,8 +477,91 @@ pub struct LoginResponse {
     pub message: Option<String>,
 }
 
+/// Count a failed login. Returns `RateLimited` when this failure triggers a lockout.
+fn login_failure(
+    state: &AppState,
+    auth: &AuthConfig,
+    client_ip: Option<IpAddr>,
+    username: &str,
+    message: &str,
+) -> AppError {
+    match state
+        .login_limiter
+        .record_failure(auth.into(), client_ip, username)
+    {
+        Some(lockout) => {
+            warn!(
+                "Login locked for {}s after repeated failures (user '{}', ip {:?})",
+                lockout.as_secs(),
+                username,
+                client_ip
+            );
+            state.events.publish(SystemEvent::AuthLoginLocked {
+                username: username.to_string(),
+                ip: client_ip.map(|ip| ip.to_string()),
+                locked_secs: lockout.as_secs(),
+            });
+            AppError::RateLimited {
+                retry_after_secs: lockout.as_secs(),
+            }
+        }
+        None => AppError::AuthError(message.to_string()),
+    }
+}
+
+/// Rate-limit check, password and second factor. Failures are counted by the login limiter.
+async fn authenticate_login(
+    state: &AppState,
+    auth: &AuthConfig,
+    client_ip: Option<IpAddr>,
+    req: &LoginRequest,
+) -> Result<crate::auth::User> {
+    if let Some(wait) = state.login_limiter.check(client_ip, &req.username) {
+        return Err(AppError::RateLimited {
+            retry_after_secs: wait.as_secs() + u64::from(wait.subsec_nanos() > 0),
+        });
+    }
+
+    // Verify user credentials
+    let user = state
+        .users
+        .verify(&req.username, &req.password)
+        .await?
+        .ok_or_else(|| {
+            login_failure(
+                state,
+                auth,
+                client_ip,
+                &req.username,
+                "Invalid username or password",
+            )
+        })?;
+
+    if state.users.totp(&user.id).await?.enabled {
+        let code = req
+            .totp_code
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| AppError::AuthError("Two-factor code required".to_string()))?;
+        if !totp::verify_second_factor(state, &user.id, code).await? {
+            return Err(login_failure(
+                state,
+                auth,
+                client_ip,
+                &req.username,
+                "Invalid two-factor code",
+            ));
+        }
+    }
+
+    Ok(user)
+}
+
 pub async fn login(
     State(state): State<Arc<AppState>>,
+    connect_info: Option<axum::Extension<ConnectInfo<SocketAddr>>>,
+    headers: axum::http::HeaderMap,
     cookies: CookieJar,
     Json(req): Json<LoginRequest>,
 ) -> Result<(CookieJar, Json<LoginResponse>)> {
@@ -466,22 +572,37 @@ pub async fn login(
         return Err(AppError::BadRequest("System not initialized".to_string()));
     }
 
-    // Verify user credentials
-    let user = state
-        .users
-        .verify(&req.username, &req.password)
-        .await?
-        .ok_or_else(|| AppError::AuthError("Invalid username or password".to_string()))?;
+    let client_ip = connect_info.map(|info| info.0 .0.ip());
+    let result = authenticate_login(&state, &config.auth, client_ip, &req).await;
+    let audit = AuditContext {
+        actor: Some(req.username.clone()),
+        ip: client_ip.map(|ip| ip.to_string()),
+    };
+    audit.record(&state, "auth.login", None, &result).await;
+    let user = result?;
+
+    state.login_limiter.record_success(client_ip, &req.username);
 
     if !config.auth.single_user_allow_multiple_sessions {
-        // Kick existing sessions before creating a new one.
-        let revoked_ids = state.sessions.list_ids().await?;
-        state.sessions.delete_all().await?;
+        // Kick this user's existing sessions before creating a new one.
+        let revoked_ids = state.sessions.delete_for_user(&user.id).await?;
         state.remember_revoked_sessions(revoked_ids).await;
     }
 
     // Create session
-    let session = state.sessions.create(&user.id).await?;
+    let user_agent = headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(256).collect());
+    let session = state
+        .sessions
+        .create(
+            &user.id,
+            user.role,
+            client_ip.map(|ip| ip.to_string()),
+            user_agent,
+        )
+        .await?;
 
     // Set session cookie
     let cookie = Cookie::build((SESSION_COOKIE, session.id))
@@ -530,6 +651,7 @@ pub async fn logout(
 pub struct AuthCheckResponse {
     pub authenticated: bool,
     pub user: Option<String>,
+    pub role: UserRole,
 }
 
 pub async fn auth_check(
@@ -537,14 +659,15 @@ pub async fn auth_check(
     axum::Extension(session): axum::Extension<Session>,
 ) -> Json<AuthCheckResponse> {
     // Get user info from user_id
-    let username = match state.users.single_user().await {
-        Ok(Some(user)) if user.id == session.user_id => Some(user.username),
+    let username = match state.users.get(&session.user_id).await {
+        Ok(Some(user)) => Some(user.username),
         _ => None,
     };
 
     Json(AuthCheckResponse {
         authenticated: true,
         user: username,
+        role: session.role,
     })
 }
 
@@ -579,6 +702,8 @@ pub struct SetupRequest {
     pub hid_backend: Option<String>,
     pub hid_ch9329_port: Option<String>,
     pub hid_ch9329_baudrate: Option<u32>,
+    pub hid_mcu_port: Option<String>,
+    pub hid_mcu_baudrate: Option<u32>,
     pub hid_otg_udc: Option<String>,
     pub hid_otg_profile: Option<String>,
     pub hid_otg_endpoint_budget: Option<crate::config::OtgEndpointBudget>,
@@ -612,7 +737,7 @@ pub async fn setup_init(
         ));
     }
 
-    // Create single system user
+    // Create the initial administrator
     state
         .users
         .create_first_user(&req.username, &req.password)
@@ -652,6 +777,8 @@ pub async fn setup_init(
                 config.hid.backend = match backend.as_str() {
                     "otg" => crate::config::HidBackend::Otg,
                     "ch9329" => crate::config::HidBackend::Ch9329,
+                    "mcu" => crate::config::HidBackend::Mcu,
+                    "mock" => crate::config::HidBackend::Mock,
                     _ => crate::config::HidBackend::None,
                 };
             }
@@ -661,6 +788,12 @@ pub async fn setup_init(
             if let Some(baudrate) = req.hid_ch9329_baudrate {
                 config.hid.ch9329_baudrate = baudrate;
             }
+            if let Some(port) = req.hid_mcu_port.clone() {
+                config.hid.mcu_port = port;
+            }
+            if let Some(baudrate) = req.hid_mcu_baudrate {
+                config.hid.mcu_baudrate = baudrate;
+            }
             if let Some(udc) = req.hid_otg_udc.clone() {
                 config.hid.otg_udc = Some(udc);
             }
@@ -713,6 +846,11 @@ pub async fn setup_init(
             port: new_config.hid.ch9329_port.clone(),
             baud_rate: new_config.hid.ch9329_baudrate,
         },
+        crate::config::HidBackend::Mcu => crate::hid::HidBackendType::Mcu {
+            port: new_config.hid.mcu_port.clone(),
+            baud_rate: new_config.hid.mcu_baudrate,
+        },
+        crate::config::HidBackend::Mock => crate::hid::HidBackendType::Mock,
         crate::config::HidBackend::None => crate::hid::HidBackendType::None,
     };
 
@@ -1448,6 +1586,7 @@ pub struct MjpegStreamQuery {
 /// MJPEG stream endpoint
 pub async fn mjpeg_stream(
     State(state): State<Arc<AppState>>,
+    session: Option<axum::Extension<Session>>,
     Query(query): Query<MjpegStreamQuery>,
 ) -> impl IntoResponse {
     // Check if MJPEG mode is active
@@ -1488,8 +1627,10 @@ pub async fn mjpeg_stream(
         .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
     // Create RAII guard - this will automatically register and unregister the client
-    let guard = Arc::new(crate::stream::mjpeg::ClientGuard::new(
+    let owner = session.map(|s| session_key(&s.id));
+    let guard = Arc::new(crate::stream::mjpeg::ClientGuard::with_owner(
         client_id.clone(),
+        owner,
         handler.clone(),
     ));
 
@@ -1664,8 +1805,33 @@ pub struct CreateSessionResponse {
     pub session_id: String,
 }
 
+/// Share link viewers get audio only if their link allows it
+fn allow_audio(link: Option<&ShareLink>) -> bool {
+    link.is_none_or(|link| link.audio)
+}
+
+/// Share link viewers may only signal the WebRTC sessions they created
+async fn ensure_webrtc_session_access(
+    state: &AppState,
+    session: &Session,
+    webrtc_session_id: &str,
+) -> Result<()> {
+    if share_link_id(session).is_none() {
+        return Ok(());
+    }
+    match state.webrtc.session_owner(webrtc_session_id).await {
+        Some(owner) if owner == session_key(&session.id) => Ok(()),
+        _ => Err(AppError::NotFound(format!(
+            "Session not found: {}",
+            webrtc_session_id
+        ))),
+    }
+}
+
 pub async fn webrtc_create_session(
     State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
+    link: Option<axum::Extension<ShareLink>>,
 ) -> Result<Json<CreateSessionResponse>> {
     // Check if WebRTC mode is active
     if !state.stream_manager.is_webrtc_enabled().await {
@@ -1674,13 +1840,24 @@ pub async fn webrtc_create_session(
         ));
     }
 
-    let session_id = state.webrtc.create_session().await?;
+    let allow_input = session.role.allows(UserRole::Operator);
+    let owner = state.hid_control_owner(&session).await;
+    let session_id = state
+        .webrtc
+        .create_session_with_input(
+            Some(owner),
+            allow_input,
+            allow_audio(link.as_ref().map(|l| &l.0)),
+        )
+        .await?;
     Ok(Json(CreateSessionResponse { session_id }))
 }
 
 /// Handle WebRTC offer
 pub async fn webrtc_offer(
     State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
+    link: Option<axum::Extension<ShareLink>>,
     Json(req): Json<OfferRequest>,
 ) -> Result<Json<AnswerResponse>> {
     // Check if WebRTC mode is active
@@ -1693,15 +1870,23 @@ pub async fn webrtc_offer(
     // Backward compatibility: `client_id` is treated as an existing session_id hint.
     // New clients should not pass it; each offer creates a fresh session.
     let webrtc = &state.webrtc;
+    let allow_input = session.role.allows(UserRole::Operator);
+    let allow_audio = allow_audio(link.as_ref().map(|l| &l.0));
+    let owner = state.hid_control_owner(&session).await;
     let session_id = if let Some(client_id) = &req.client_id {
         // Reuse only when it matches an active session ID.
         if webrtc.get_session(client_id).await.is_some() {
+            ensure_webrtc_session_access(&state, &session, client_id).await?;
             client_id.clone()
         } else {
-            webrtc.create_session().await?
+            webrtc
+                .create_session_with_input(Some(owner), allow_input, allow_audio)
+                .await?
         }
     } else {
-        webrtc.create_session().await?
+        webrtc
+            .create_session_with_input(Some(owner), allow_input, allow_audio)
+            .await?
     };
 
     // Handle offer
@@ -1718,8 +1903,10 @@ pub async fn webrtc_offer(
 /// Add ICE candidate
 pub async fn webrtc_ice_candidate(
     State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
     Json(req): Json<IceCandidateRequest>,
 ) -> Result<Json<LoginResponse>> {
+    ensure_webrtc_session_access(&state, &session, &req.session_id).await?;
     state
         .webrtc
         .add_ice_candidate(&req.session_id, req.candidate)
@@ -1766,8 +1953,10 @@ pub struct CloseSessionRequest {
 
 pub async fn webrtc_close_session(
     State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
     Json(req): Json<CloseSessionRequest>,
 ) -> Result<Json<LoginResponse>> {
+    ensure_webrtc_session_access(&state, &session, &req.session_id).await?;
     state.webrtc.close_session(&req.session_id).await?;
 
     Ok(Json(LoginResponse {
@@ -2632,8 +2821,15 @@ pub async fn hid_status(State(state): State<Arc<AppState>>) -> Json<HidStatus> {
 }
 
 /// Reset HID state
-pub async fn hid_reset(State(state): State<Arc<AppState>>) -> Result<Json<LoginResponse>> {
-    state.hid.reset().await?;
+pub async fn hid_reset(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
+    audit: AuditContext,
+) -> Result<Json<LoginResponse>> {
+    let owner = state.hid_control_owner(&session).await;
+    let result = state.hid.reset_as(&owner.id).await;
+    audit.record(&state, "hid.reset", None, &result).await;
+    result?;
 
     Ok(Json(LoginResponse {
         success: true,
@@ -2641,6 +2837,124 @@ pub async fn hid_reset(State(state): State<Arc<AppState>>) -> Result<Json<LoginR
     }))
 }
 
+#[derive(Deserialize)]
+pub struct SystemControlRequest {
+    pub action: crate::hid::SystemControlAction,
+}
+
+/// Send a USB System Control usage (power down, sleep, wake up) to the target
+pub async fn hid_system_control(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
+    audit: AuditContext,
+    Json(req): Json<SystemControlRequest>,
+) -> Result<Json<LoginResponse>> {
+    let owner = state.hid_control_owner(&session).await;
+    let result = state
+        .hid
+        .send_input(
+            &owner.id,
+            crate::hid::HidChannelEvent::SystemControl(req.action),
+        )
+        .await;
+    audit
+        .record(
+            &state,
+            "hid.system_control",
+            Some(serde_json::json!({ "action": req.action })),
+            &result,
+        )
+        .await;
+    result?;
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some(format!("System control {:?} sent", req.action)),
+    }))
+}
+
+#[derive(Serialize)]
+pub struct TypeTextResponse {
+    pub job_id: String,
+}
+
+/// Type text into the target through HID; returns once the job has started
+pub async fn hid_type_text(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
+    audit: AuditContext,
+    Json(req): Json<crate::hid::TypeTextRequest>,
+) -> Result<Json<TypeTextResponse>> {
+    let owner = state.hid_control_owner(&session).await;
+    // The text may be a password, so only its length is audited.
+    let details = serde_json::json!({
+        "layout": req.layout,
+        "chars": req.text.chars().count(),
+    });
+    let result = state.hid.type_text(&owner.id, req);
+    audit
+        .record(&state, "hid.type_text", Some(details), &result)
+        .await;
+
+    Ok(Json(TypeTextResponse { job_id: result? }))
+}
+
+#[derive(Deserialize)]
+pub struct HidEventsQuery {
+    /// Release every key and button if an event fails
+    #[serde(default)]
+    pub release_on_error: bool,
+}
+
+#[derive(Serialize)]
+pub struct HidEventsResponse {
+    pub events: usize,
+}
+
+/// Send a batch of HID events in order; returns once all were sent or one failed
+pub async fn hid_events(
+    State(state): State<Arc<AppState>>,
+    axum::Extension(session): axum::Extension<Session>,
+    Query(query): Query<HidEventsQuery>,
+    audit: AuditContext,
+    Json(steps): Json<Vec<crate::hid::BatchStep>>,
+) -> Result<Json<HidEventsResponse>> {
+    let owner = state.hid_control_owner(&session).await;
+    let result = state
+        .hid
+        .run_batch(&owner.id, &steps, query.release_on_error)
+        .await;
+    audit
+        .record(
+            &state,
+            "hid.events",
+            Some(serde_json::json!({
+                "steps": steps.len(),
+                "release_on_error": query.release_on_error,
+            })),
+            &result,
+        )
+        .await;
+
+    Ok(Json(HidEventsResponse { events: result? }))
+}
+
+/// Cancel the running type job
+pub async fn hid_type_cancel(State(state): State<Arc<AppState>>) -> Json<LoginResponse> {
+    let cancelled = state.hid.cancel_typing();
+    Json(LoginResponse {
+        success: cancelled,
+        message: Some(
+            if cancelled {
+                "Typing cancelled"
+            } else {
+                "No text is being typed"
+            }
+            .to_string(),
+        ),
+    })
+}
+
 use crate::msd::{
     DownloadProgress, DriveFile, DriveInfo, DriveInitRequest, ImageDownloadRequest, ImageInfo,
     ImageManager, MsdConnectRequest, MsdMode, MsdState, VentoyDrive,
@@ -2686,8 +3000,21 @@ pub async fn msd_images_list(State(state): State<Arc<AppState>>) -> Result<Json<
 /// Upload new image (streaming - memory efficient for large files)
 pub async fn msd_image_upload(
     State(state): State<Arc<AppState>>,
-    mut multipart: Multipart,
+    audit: AuditContext,
+    multipart: Multipart,
 ) -> Result<Json<ImageInfo>> {
+    let result = receive_image_upload(&state, multipart).await;
+    let details = result
+        .as_ref()
+        .ok()
+        .map(|image| serde_json::json!({ "id": image.id, "name": image.name, "size": image.size }));
+    audit
+        .record(&state, "msd.image_upload", details, &result)
+        .await;
+    result.map(Json)
+}
+
+async fn receive_image_upload(state: &AppState, mut multipart: Multipart) -> Result<ImageInfo> {
     let config = state.config.get();
     let images_path = config.msd.images_dir();
     let manager = ImageManager::new(images_path);
@@ -2709,7 +3036,7 @@ pub async fn msd_image_upload(
             let image = manager
                 .create_from_multipart_field(&filename, field)
                 .await?;
-            return Ok(Json(image));
+            return Ok(image);
         }
     }
 
@@ -2732,13 +3059,23 @@ pub async fn msd_image_get(
 /// Delete image by ID
 pub async fn msd_image_delete(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     AxumPath(id): AxumPath<String>,
 ) -> Result<Json<LoginResponse>> {
     let config = state.config.get();
     let images_path = config.msd.images_dir();
     let manager = ImageManager::new(images_path);
 
-    manager.delete(&id)?;
+    let result = manager.delete(&id);
+    audit
+        .record(
+            &state,
+            "msd.image_delete",
+            Some(serde_json::json!({ "id": id })),
+            &result,
+        )
+        .await;
+    result?;
     Ok(Json(LoginResponse {
         success: true,
         message: Some("Image deleted".to_string()),
@@ -2786,8 +3123,28 @@ pub async fn msd_image_download_cancel(
 /// Connect MSD (image or drive)
 pub async fn msd_connect(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<MsdConnectRequest>,
 ) -> Result<Json<LoginResponse>> {
+    let details = serde_json::json!({
+        "mode": req.mode,
+        "image_id": req.image_id,
+        "cdrom": req.cdrom,
+        "read_only": req.read_only,
+    });
+    let result = connect_msd(&state, req).await;
+    audit
+        .record(&state, "msd.connect", Some(details), &result)
+        .await;
+    result?;
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("MSD connected".to_string()),
+    }))
+}
+
+async fn connect_msd(state: &AppState, req: MsdConnectRequest) -> Result<()> {
     let config = state.config.get();
     let mut msd_guard = state.msd.write().await;
     let controller = msd_guard
@@ -2819,25 +3176,31 @@ pub async fn msd_connect(
         }
     }
 
+    Ok(())
+}
+
+/// Disconnect MSD
+pub async fn msd_disconnect(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+) -> Result<Json<LoginResponse>> {
+    let result = disconnect_msd(&state).await;
+    audit.record(&state, "msd.disconnect", None, &result).await;
+    result?;
+
     Ok(Json(LoginResponse {
         success: true,
-        message: Some("MSD connected".to_string()),
+        message: Some("MSD disconnected".to_string()),
     }))
 }
 
-/// Disconnect MSD
-pub async fn msd_disconnect(State(state): State<Arc<AppState>>) -> Result<Json<LoginResponse>> {
+async fn disconnect_msd(state: &AppState) -> Result<()> {
     let mut msd_guard = state.msd.write().await;
     let controller = msd_guard
         .as_mut()
         .ok_or_else(|| AppError::Internal("MSD not initialized".to_string()))?;
 
-    controller.disconnect().await?;
-
-    Ok(Json(LoginResponse {
-        success: true,
-        message: Some("MSD disconnected".to_string()),
-    }))
+    controller.disconnect().await
 }
 
 /// Get drive info
@@ -2913,15 +3276,36 @@ pub async fn msd_drive_files(
 /// Upload file to drive (streaming - memory efficient for large files)
 pub async fn msd_drive_upload(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Query(params): Query<HashMap<String, String>>,
-    mut multipart: Multipart,
+    multipart: Multipart,
 ) -> Result<Json<LoginResponse>> {
+    let target_dir = params.get("path").map(|s| s.as_str()).unwrap_or("/");
+    let result = receive_drive_upload(&state, target_dir, multipart).await;
+    let details = match result {
+        Ok(ref file_path) => serde_json::json!({ "path": file_path }),
+        Err(_) => serde_json::json!({ "dir": target_dir }),
+    };
+    audit
+        .record(&state, "msd.drive_upload", Some(details), &result)
+        .await;
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some(format!("File uploaded: {}", result?)),
+    }))
+}
+
+/// Stream the multipart `file` field into the drive; returns the stored path.
+async fn receive_drive_upload(
+    state: &AppState,
+    target_dir: &str,
+    mut multipart: Multipart,
+) -> Result<String> {
     let config = state.config.get();
     let drive_path = config.msd.drive_path();
     let drive = VentoyDrive::new(drive_path);
 
-    let target_dir = params.get("path").map(|s| s.as_str()).unwrap_or("/");
-
     while let Some(field) = multipart
         .next_field()
         .await
@@ -2946,10 +3330,7 @@ pub async fn msd_drive_upload(
                 .write_file_from_multipart_field(&file_path, field)
                 .await?;
 
-            return Ok(Json(LoginResponse {
-                success: true,
-                message: Some(format!("File uploaded: {}", file_path)),
-            }));
+            return Ok(file_path);
         }
     }
 
@@ -2993,13 +3374,23 @@ pub async fn msd_drive_download(
 /// Delete file from drive
 pub async fn msd_drive_file_delete(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     AxumPath(file_path): AxumPath<String>,
 ) -> Result<Json<LoginResponse>> {
     let config = state.config.get();
     let drive_path = config.msd.drive_path();
     let drive = VentoyDrive::new(drive_path);
 
-    drive.delete(&file_path).await?;
+    let result = drive.delete(&file_path).await;
+    audit
+        .record(
+            &state,
+            "msd.drive_delete",
+            Some(serde_json::json!({ "path": file_path })),
+            &result,
+        )
+        .await;
+    result?;
 
     Ok(Json(LoginResponse {
         success: true,
@@ -3092,38 +3483,47 @@ pub struct AtxPowerControlRequest {
 /// Control ATX power
 pub async fn atx_power(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<AtxPowerControlRequest>,
 ) -> Result<Json<LoginResponse>> {
+    let result = press_atx_button(&state, &req.action).await;
+    audit
+        .record(
+            &state,
+            "atx.power",
+            Some(serde_json::json!({ "action": req.action })),
+            &result,
+        )
+        .await;
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some(result?.to_string()),
+    }))
+}
+
+async fn press_atx_button(state: &AppState, action: &str) -> Result<&'static str> {
     let atx_guard = state.atx.read().await;
     let atx = atx_guard
         .as_ref()
         .ok_or_else(|| AppError::Internal("ATX controller not initialized".to_string()))?;
 
-    match req.action.as_str() {
+    match action {
         "short" => {
             atx.power_short().await?;
-            Ok(Json(LoginResponse {
-                success: true,
-                message: Some("Power short press executed".to_string()),
-            }))
+            Ok("Power short press executed")
         }
         "long" => {
             atx.power_long().await?;
-            Ok(Json(LoginResponse {
-                success: true,
-                message: Some("Power long press (force off) executed".to_string()),
-            }))
+            Ok("Power long press (force off) executed")
         }
         "reset" => {
             atx.reset().await?;
-            Ok(Json(LoginResponse {
-                success: true,
-                message: Some("Reset button pressed".to_string()),
-            }))
+            Ok("Reset button pressed")
         }
         _ => Err(AppError::BadRequest(format!(
             "Unknown ATX action: {}. Valid actions: short, long, reset",
-            req.action
+            action
         ))),
     }
 }
@@ -3203,6 +3603,7 @@ async fn record_wol_history(state: &Arc<AppState>, mac_address: &str) -> Result<
 /// Send Wake-on-LAN magic packet
 pub async fn atx_wol(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<WolRequest>,
 ) -> Result<Json<LoginResponse>> {
     let mac_address = normalize_wol_mac_address(&req.mac_address);
@@ -3216,7 +3617,16 @@ pub async fn atx_wol(
     };
 
     // Send WOL packet
-    crate::atx::send_wol(&mac_address, interface)?;
+    let result = crate::atx::send_wol(&mac_address, interface);
+    audit
+        .record(
+            &state,
+            "atx.wol",
+            Some(serde_json::json!({ "mac_address": mac_address })),
+            &result,
+        )
+        .await;
+    result?;
 
     if let Err(error) = record_wol_history(&state, &mac_address).await {
         warn!("Failed to persist WOL history: {}", error);
@@ -3358,14 +3768,10 @@ pub async fn change_password(
 ) -> Result<Json<LoginResponse>> {
     let current_user = state
         .users
-        .single_user()
+        .get(&session.user_id)
         .await?
         .ok_or_else(|| AppError::AuthError("User not found".to_string()))?;
 
-    if current_user.id != session.user_id {
-        return Err(AppError::AuthError("Invalid session".to_string()));
-    }
-
     if req.new_password.len() < 4 {
         return Err(AppError::BadRequest(
             "Password must be at least 4 characters".to_string(),
@@ -3409,14 +3815,10 @@ pub async fn change_username(
 ) -> Result<Json<LoginResponse>> {
     let current_user = state
         .users
-        .single_user()
+        .get(&session.user_id)
         .await?
         .ok_or_else(|| AppError::AuthError("User not found".to_string()))?;
 
-    if current_user.id != session.user_id {
-        return Err(AppError::AuthError("Invalid session".to_string()));
-    }
-
     if req.username.len() < 2 {
         return Err(AppError::BadRequest(
             "Username must be at least 2 characters".to_string(),
@@ -3448,8 +3850,14 @@ pub async fn change_username(
 }
 
 /// Restart the application
-pub async fn system_restart(State(state): State<Arc<AppState>>) -> Json<LoginResponse> {
+pub async fn system_restart(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+) -> Json<LoginResponse> {
     info!("System restart requested via API");
+    audit
+        .record_outcome(&state, "system.restart", None, AuditOutcome::Success, None)
+        .await;
 
     // Send shutdown signal
     let _ = state.shutdown_tx.send(());
@@ -3509,9 +3917,15 @@ pub async fn update_overview(
 
 pub async fn update_upgrade(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<UpgradeRequest>,
 ) -> Result<Json<LoginResponse>> {
-    state.update.start_upgrade(req, state.shutdown_tx.clone())?;
+    let details = serde_json::to_value(&req).ok();
+    let result = state.update.start_upgrade(req, state.shutdown_tx.clone());
+    audit
+        .record(&state, "system.upgrade", details, &result)
+        .await;
+    result?;
 
     Ok(Json(LoginResponse {
         success: true,
@@ -0,0 +1,43 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+use super::audit::AuditContext;
+use crate::auth::Session;
+use crate::error::Result;
+use crate::hid::KeyPresetInfo;
+use crate::state::AppState;
+
+#[derive(Serialize)]
+pub struct SendPresetResponse {
+    pub events: usize,
+}
+
+/// Built-in and user-defined key presets
+pub async fn list_presets(State(state): State<Arc<AppState>>) -> Json<Vec<KeyPresetInfo>> {
+    Json(state.hid.key_presets())
+}
+
+/// Send a preset; returns once all of its events were sent
+pub async fn send_preset(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    audit: AuditContext,
+    Path(id): Path<String>,
+) -> Result<Json<SendPresetResponse>> {
+    let owner = state.hid_control_owner(&session).await;
+    let result = state.hid.run_preset(&owner.id, &id).await;
+    audit
+        .record(
+            &state,
+            "hid.preset",
+            Some(serde_json::json!({ "id": id })),
+            &result,
+        )
+        .await;
+
+    Ok(Json(SendPresetResponse { events: result? }))
+}
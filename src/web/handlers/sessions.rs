@@ -0,0 +1,133 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use super::audit::AuditContext;
+use super::LoginResponse;
+use crate::auth::{session_key, Session, SessionInfo, UserRole};
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+
+#[derive(Serialize)]
+pub struct SessionView {
+    #[serde(flatten)]
+    pub session: SessionInfo,
+    pub username: Option<String>,
+    /// Whether this is the session making the request
+    pub current: bool,
+}
+
+/// Sessions the caller may manage: every session for admins, otherwise their own.
+async fn visible_sessions(state: &AppState, caller: &Session) -> Result<Vec<SessionInfo>> {
+    let sessions = state.sessions.list().await?;
+    if caller.role == UserRole::Admin {
+        return Ok(sessions);
+    }
+    Ok(sessions
+        .into_iter()
+        .filter(|s| s.user_id == caller.user_id)
+        .collect())
+}
+
+async fn revoke(state: &AppState, keys: Vec<String>) -> Result<usize> {
+    let mut revoked = Vec::with_capacity(keys.len());
+    for key in keys {
+        if state.sessions.delete_by_key(&key).await? {
+            revoked.push(key);
+        }
+    }
+    let count = revoked.len();
+    state.remember_revoked_sessions(revoked).await;
+    Ok(count)
+}
+
+pub async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Session>,
+) -> Result<Json<Vec<SessionView>>> {
+    let current = session_key(&caller.id);
+    let usernames: HashMap<String, String> = state
+        .users
+        .list()
+        .await?
+        .into_iter()
+        .map(|user| (user.id, user.username))
+        .collect();
+
+    let sessions = visible_sessions(&state, &caller)
+        .await?
+        .into_iter()
+        .map(|session| SessionView {
+            username: usernames.get(&session.user_id).cloned(),
+            current: session.key == current,
+            session,
+        })
+        .collect();
+
+    Ok(Json(sessions))
+}
+
+/// Revoke one session by key. Revoking the current session is equivalent to logging out.
+pub async fn revoke_session(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Session>,
+    audit: AuditContext,
+    Path(key): Path<String>,
+) -> Result<Json<LoginResponse>> {
+    let visible = visible_sessions(&state, &caller).await?;
+    let result = if visible.iter().any(|s| s.key == key) {
+        revoke(&state, vec![key.clone()]).await
+    } else {
+        Err(AppError::NotFound("Session not found".to_string()))
+    };
+    audit
+        .record(
+            &state,
+            "auth.session_revoke",
+            Some(serde_json::json!({ "key": key })),
+            &result,
+        )
+        .await;
+    result?;
+    info!("Session {} revoked", key);
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("Session revoked".to_string()),
+    }))
+}
+
+/// Revoke every session the caller can see except the one making the request.
+pub async fn revoke_other_sessions(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Session>,
+    audit: AuditContext,
+) -> Result<Json<LoginResponse>> {
+    let current = session_key(&caller.id);
+    let keys = visible_sessions(&state, &caller)
+        .await?
+        .into_iter()
+        .map(|s| s.key)
+        .filter(|key| *key != current)
+        .collect();
+    let result = revoke(&state, keys).await;
+    let details = result
+        .as_ref()
+        .ok()
+        .map(|count| serde_json::json!({ "revoked": count }));
+    audit
+        .record(&state, "auth.session_revoke_others", details, &result)
+        .await;
+    let count = result?;
+    info!("Revoked {} other sessions", count);
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some(format!("Revoked {} sessions", count)),
+    }))
+}
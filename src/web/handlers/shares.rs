@@ -0,0 +1,138 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use tracing::info;
+
+use super::audit::AuditContext;
+use super::LoginResponse;
+use crate::auth::ShareLink;
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+
+/// Longest lifetime a share link may be given (30 days)
+const MAX_SHARE_LIFETIME_SECS: u64 = 30 * 24 * 3600;
+
+#[derive(Deserialize)]
+pub struct CreateShareLinkRequest {
+    pub name: String,
+    /// Lifetime in seconds
+    pub expires_in_secs: u64,
+    #[serde(default = "default_max_viewers")]
+    pub max_viewers: u32,
+    #[serde(default)]
+    pub audio: bool,
+    /// Optional PIN viewers must enter (`pin` query parameter or `X-Share-Pin` header)
+    pub pin: Option<String>,
+}
+
+fn default_max_viewers() -> u32 {
+    1
+}
+
+#[derive(Serialize)]
+pub struct CreateShareLinkResponse {
+    #[serde(flatten)]
+    pub link: ShareLink,
+    /// Plain-text secret; only returned at creation time
+    pub secret: String,
+    /// Relative URL that opens the MJPEG stream and starts a viewer session
+    pub url: String,
+}
+
+pub async fn list_share_links(State(state): State<Arc<AppState>>) -> Result<Json<Vec<ShareLink>>> {
+    Ok(Json(state.share_links.list().await?))
+}
+
+pub async fn create_share_link(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<CreateShareLinkRequest>,
+) -> Result<Json<CreateShareLinkResponse>> {
+    let name = req.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest(
+            "Share link name is required".to_string(),
+        ));
+    }
+    if !(1..=MAX_SHARE_LIFETIME_SECS).contains(&req.expires_in_secs) {
+        return Err(AppError::BadRequest(format!(
+            "Invalid expires_in_secs: must be 1-{}",
+            MAX_SHARE_LIFETIME_SECS
+        )));
+    }
+    if !(1..=16).contains(&req.max_viewers) {
+        return Err(AppError::BadRequest(
+            "Invalid max_viewers: must be 1-16".to_string(),
+        ));
+    }
+    let pin = req.pin.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    if pin.is_some_and(|p| p.len() < 4) {
+        return Err(AppError::BadRequest(
+            "PIN must be at least 4 characters".to_string(),
+        ));
+    }
+
+    let expires_at = OffsetDateTime::now_utc() + Duration::seconds(req.expires_in_secs as i64);
+    let result = state
+        .share_links
+        .create(
+            name,
+            req.audio,
+            req.max_viewers,
+            pin,
+            expires_at,
+            audit.actor.clone(),
+        )
+        .await;
+    let details = result
+        .as_ref()
+        .ok()
+        .map(|(link, _)| serde_json::to_value(link).unwrap_or_default());
+    audit.record(&state, "share.create", details, &result).await;
+    let (link, secret) = result?;
+    info!(
+        "Share link '{}' created, expires {}",
+        link.name, link.expires_at
+    );
+
+    Ok(Json(CreateShareLinkResponse {
+        url: format!("/api/stream/mjpeg?share={}", secret),
+        link,
+        secret,
+    }))
+}
+
+/// Delete a link and end every viewer session opened through it.
+pub async fn delete_share_link(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<String>,
+) -> Result<Json<LoginResponse>> {
+    let result = state.share_links.delete(&id).await;
+    audit
+        .record(
+            &state,
+            "share.revoke",
+            Some(serde_json::json!({ "id": id })),
+            &result,
+        )
+        .await;
+    result?;
+
+    let viewer_id = format!("{}{}", crate::auth::share::SHARE_USER_PREFIX, id);
+    let revoked = state.sessions.delete_for_user(&viewer_id).await?;
+    info!(
+        "Share link {} revoked, {} viewer sessions closed",
+        id,
+        revoked.len()
+    );
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("Share link revoked".to_string()),
+    }))
+}
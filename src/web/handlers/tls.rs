@@ -0,0 +1,125 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+use super::audit::AuditContext;
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+use crate::tls::CertificateInfo;
+
+#[derive(Deserialize)]
+pub struct CreateCsrRequest {
+    pub common_name: String,
+    /// DNS names and IP addresses the certificate should cover
+    #[serde(default)]
+    pub sans: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct CreateCsrResponse {
+    pub csr_pem: String,
+}
+
+#[derive(Deserialize)]
+pub struct InstallCertificateRequest {
+    /// Signed certificate, followed by any intermediates
+    pub certificate_pem: String,
+    /// Private key; defaults to the key generated with the last CSR
+    pub key_pem: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct SelfSignedRequest {
+    /// Defaults to localhost, 127.0.0.1 and ::1
+    #[serde(default)]
+    pub sans: Vec<String>,
+}
+
+fn trimmed_sans(sans: &[String]) -> Vec<String> {
+    sans.iter()
+        .map(|san| san.trim().to_string())
+        .filter(|san| !san.is_empty())
+        .collect()
+}
+
+pub async fn get_certificate(State(state): State<Arc<AppState>>) -> Result<Json<CertificateInfo>> {
+    Ok(Json(state.tls.certificate_info().await?))
+}
+
+/// Generate a new private key and return a CSR for it. The key stays on the
+/// device until the signed certificate is installed.
+pub async fn create_csr(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<CreateCsrRequest>,
+) -> Result<Json<CreateCsrResponse>> {
+    let common_name = req.common_name.trim();
+    if common_name.is_empty() {
+        return Err(AppError::BadRequest("common_name is required".to_string()));
+    }
+    let mut sans = trimmed_sans(&req.sans);
+    if sans.is_empty() {
+        sans.push(common_name.to_string());
+    }
+
+    let result = state.tls.create_csr(common_name, &sans).await;
+    audit
+        .record(
+            &state,
+            "tls.csr_create",
+            Some(serde_json::json!({ "common_name": common_name, "sans": sans })),
+            &result,
+        )
+        .await;
+
+    Ok(Json(CreateCsrResponse { csr_pem: result? }))
+}
+
+/// Install a signed certificate chain and hot-reload the HTTPS listener.
+pub async fn install_certificate(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Json(req): Json<InstallCertificateRequest>,
+) -> Result<Json<CertificateInfo>> {
+    let result = state
+        .tls
+        .install_certificate(&req.certificate_pem, req.key_pem.as_deref())
+        .await;
+    let details = result.as_ref().ok().map(
+        |info| serde_json::json!({ "subject": info.subject, "fingerprint": info.fingerprint }),
+    );
+    audit
+        .record(&state, "tls.certificate_install", details, &result)
+        .await;
+    let info = result?;
+    info!(
+        "Installed TLS certificate '{}', expires {}",
+        info.subject, info.not_after
+    );
+
+    Ok(Json(info))
+}
+
+/// Regenerate the self-signed certificate, dropping any custom certificate.
+pub async fn regenerate_self_signed(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    req: Option<Json<SelfSignedRequest>>,
+) -> Result<Json<CertificateInfo>> {
+    let req = req.map(|Json(req)| req).unwrap_or_default();
+    let sans = trimmed_sans(&req.sans);
+
+    let result = state.tls.regenerate_self_signed(&sans).await;
+    let details = result
+        .as_ref()
+        .ok()
+        .map(|info| serde_json::json!({ "sans": info.sans, "fingerprint": info.fingerprint }));
+    audit
+        .record(&state, "tls.self_signed_regenerate", details, &result)
+        .await;
+    let info = result?;
+    info!("Regenerated self-signed TLS certificate");
+
+    Ok(Json(info))
+}
@@ -0,0 +1,189 @@
+use axum::{extract::State, Extension, Json};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::auth::{totp, Session};
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+
+const TOTP_ISSUER: &str = "One-KVM";
+
+/// Decrypted TOTP secret of a user, once enrollment has at least started.
+async fn user_secret(state: &AppState, user_id: &str) -> Result<Option<String>> {
+    state
+        .users
+        .totp(user_id)
+        .await?
+        .secret
+        .map(|secret| state.config.open_secret(&secret))
+        .transpose()
+}
+
+/// Check a TOTP code and burn its time step, so each code works only once.
+async fn verify_totp_code(
+    state: &AppState,
+    user_id: &str,
+    secret: &str,
+    code: &str,
+) -> Result<bool> {
+    match totp::verify(secret, code)? {
+        Some(step) => state.users.use_totp_step(user_id, step as i64).await,
+        None => Ok(false),
+    }
+}
+
+/// Check a user's second-factor code: a fresh TOTP code, or an unused recovery code (consumed).
+pub async fn verify_second_factor(state: &AppState, user_id: &str, code: &str) -> Result<bool> {
+    let secret = user_secret(state, user_id)
+        .await?
+        .ok_or_else(|| AppError::Config("TOTP secret is not configured".to_string()))?;
+
+    if verify_totp_code(state, user_id, &secret, code).await? {
+        return Ok(true);
+    }
+    state.recovery_codes.consume(user_id, code).await
+}
+
+#[derive(Serialize)]
+pub struct TotpStatusResponse {
+    pub enabled: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Deserialize)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+#[derive(Serialize)]
+pub struct TotpRecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+fn already_enabled() -> AppError {
+    AppError::BadRequest("Two-factor authentication is already enabled".to_string())
+}
+
+fn not_enabled() -> AppError {
+    AppError::BadRequest("Two-factor authentication is not enabled".to_string())
+}
+
+/// Two-factor status of the caller's account
+pub async fn get_totp_status(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<TotpStatusResponse>> {
+    let enabled = state.users.totp(&session.user_id).await?.enabled;
+    let recovery_codes_remaining = if enabled {
+        state.recovery_codes.remaining(&session.user_id).await?
+    } else {
+        0
+    };
+
+    Ok(Json(TotpStatusResponse {
+        enabled,
+        recovery_codes_remaining,
+    }))
+}
+
+/// Start enrollment: generate a pending secret. TOTP stays off until confirmed.
+pub async fn setup_totp(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+) -> Result<Json<TotpSetupResponse>> {
+    let user = state
+        .users
+        .get(&session.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    if state.users.totp(&user.id).await?.enabled {
+        return Err(already_enabled());
+    }
+
+    let secret = totp::generate_secret();
+    state
+        .users
+        .set_totp_secret(&user.id, Some(&state.config.seal_secret(&secret)?))
+        .await?;
+
+    Ok(Json(TotpSetupResponse {
+        otpauth_uri: totp::otpauth_uri(&secret, &user.username, TOTP_ISSUER),
+        secret,
+    }))
+}
+
+/// Finish enrollment with a first valid code and hand out recovery codes.
+pub async fn enable_totp(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    Json(req): Json<TotpCodeRequest>,
+) -> Result<Json<TotpRecoveryCodesResponse>> {
+    let user_id = &session.user_id;
+    if state.users.totp(user_id).await?.enabled {
+        return Err(already_enabled());
+    }
+    let secret = user_secret(&state, user_id)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Run TOTP setup first".to_string()))?;
+
+    if !verify_totp_code(&state, user_id, &secret, &req.code).await? {
+        return Err(AppError::AuthError("Invalid two-factor code".to_string()));
+    }
+
+    let recovery_codes = state.recovery_codes.regenerate(user_id).await?;
+    state.users.enable_totp(user_id).await?;
+    info!("Two-factor authentication enabled for user {}", user_id);
+
+    Ok(Json(TotpRecoveryCodesResponse { recovery_codes }))
+}
+
+pub async fn disable_totp(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    Json(req): Json<TotpCodeRequest>,
+) -> Result<Json<TotpStatusResponse>> {
+    let user_id = &session.user_id;
+    if !state.users.totp(user_id).await?.enabled {
+        return Err(not_enabled());
+    }
+    if !verify_second_factor(&state, user_id, &req.code).await? {
+        return Err(AppError::AuthError("Invalid two-factor code".to_string()));
+    }
+
+    state.users.set_totp_secret(user_id, None).await?;
+    state.recovery_codes.clear(user_id).await?;
+    info!("Two-factor authentication disabled for user {}", user_id);
+
+    Ok(Json(TotpStatusResponse {
+        enabled: false,
+        recovery_codes_remaining: 0,
+    }))
+}
+
+pub async fn regenerate_recovery_codes(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    Json(req): Json<TotpCodeRequest>,
+) -> Result<Json<TotpRecoveryCodesResponse>> {
+    let user_id = &session.user_id;
+    if !state.users.totp(user_id).await?.enabled {
+        return Err(not_enabled());
+    }
+    let secret = user_secret(&state, user_id)
+        .await?
+        .ok_or_else(not_enabled)?;
+    // Only a live TOTP code may mint new recovery codes
+    if !verify_totp_code(&state, user_id, &secret, &req.code).await? {
+        return Err(AppError::AuthError("Invalid two-factor code".to_string()));
+    }
+
+    let recovery_codes = state.recovery_codes.regenerate(user_id).await?;
+    Ok(Json(TotpRecoveryCodesResponse { recovery_codes }))
+}
@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+use super::LoginResponse;
+use crate::auth::{Session, User, UserRole};
+use crate::error::{AppError, Result};
+use crate::state::AppState;
+
+#[derive(Serialize)]
+pub struct UserInfo {
+    pub id: String,
+    pub username: String,
+    pub role: UserRole,
+}
+
+impl From<User> for UserInfo {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            username: user.username,
+            role: user.role,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub password: String,
+    pub role: UserRole,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateUserRequest {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub role: Option<UserRole>,
+}
+
+fn validate_username(username: &str) -> Result<()> {
+    if username.len() < 2 {
+        return Err(AppError::BadRequest(
+            "Username must be at least 2 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_password(password: &str) -> Result<()> {
+    if password.len() < 4 {
+        return Err(AppError::BadRequest(
+            "Password must be at least 4 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Drop all sessions of a user so role or credential changes take effect immediately.
+async fn revoke_user_sessions(state: &AppState, user_id: &str) -> Result<()> {
+    let revoked = state.sessions.delete_for_user(user_id).await?;
+    state.remember_revoked_sessions(revoked).await;
+    Ok(())
+}
+
+pub async fn list_users(State(state): State<Arc<AppState>>) -> Result<Json<Vec<UserInfo>>> {
+    let users = state.users.list().await?;
+    Ok(Json(users.into_iter().map(UserInfo::from).collect()))
+}
+
+pub async fn create_user(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateUserRequest>,
+) -> Result<Json<UserInfo>> {
+    validate_username(&req.username)?;
+    validate_password(&req.password)?;
+
+    let user = state
+        .users
+        .create(&req.username, &req.password, req.role)
+        .await?;
+    info!("User '{}' created with role {}", user.username, user.role);
+
+    Ok(Json(user.into()))
+}
+
+pub async fn update_user(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateUserRequest>,
+) -> Result<Json<UserInfo>> {
+    if state.users.get(&id).await?.is_none() {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+
+    if let Some(ref username) = req.username {
+        validate_username(username)?;
+    }
+    if let Some(ref password) = req.password {
+        validate_password(password)?;
+    }
+    if req.role.is_some() && id == session.user_id {
+        return Err(AppError::BadRequest(
+            "Cannot change your own role".to_string(),
+        ));
+    }
+
+    if let Some(ref username) = req.username {
+        state.users.update_username(&id, username).await?;
+    }
+    if let Some(role) = req.role {
+        state.users.update_role(&id, role).await?;
+    }
+    if let Some(ref password) = req.password {
+        state.users.update_password(&id, password).await?;
+    }
+
+    if req.role.is_some() || req.password.is_some() {
+        revoke_user_sessions(&state, &id).await?;
+    }
+
+    let user = state
+        .users
+        .get(&id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    info!("User '{}' updated", user.username);
+
+    Ok(Json(user.into()))
+}
+
+pub async fn delete_user(
+    State(state): State<Arc<AppState>>,
+    Extension(session): Extension<Session>,
+    Path(id): Path<String>,
+) -> Result<Json<LoginResponse>> {
+    if id == session.user_id {
+        return Err(AppError::BadRequest(
+            "Cannot delete your own account".to_string(),
+        ));
+    }
+
+    state.users.delete(&id).await?;
+    revoke_user_sessions(&state, &id).await?;
+    info!("User ID {} deleted", id);
+
+    Ok(Json(LoginResponse {
+        success: true,
+        message: Some("User deleted".to_string()),
+    }))
+}
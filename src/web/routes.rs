@@ -1,7 +1,7 @@
 use axum::{
     extract::DefaultBodyLimit,
     middleware,
-    routing::{any, delete, get, patch, post},
+    routing::{any, delete, get, patch, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -13,7 +13,7 @@ use tower_http::{
 use super::audio_ws::audio_ws_handler;
 use super::handlers;
 use super::ws::ws_handler;
-use crate::auth::auth_middleware;
+use crate::auth::{access_middleware, auth_middleware, require_role, UserRole};
 use crate::hid::websocket::ws_hid_handler;
 use crate::state::AppState;
 
@@ -31,51 +31,215 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/setup", get(handlers::setup_status))
         .route("/setup/init", post(handlers::setup_init));
 
-    // Authenticated routes (all logged-in users)
-    let user_routes = Router::new()
+    // Viewer routes (all logged-in users): video, status and own account
+    let viewer_routes = Router::new()
         .route("/info", get(handlers::system_info))
         .route("/auth/logout", post(handlers::logout))
         .route("/auth/check", get(handlers::auth_check))
         .route("/auth/password", post(handlers::change_password))
         .route("/auth/username", post(handlers::change_username))
+        .route(
+            "/auth/sessions",
+            get(handlers::sessions::list_sessions)
+                .delete(handlers::sessions::revoke_other_sessions),
+        )
+        .route(
+            "/auth/sessions/{key}",
+            delete(handlers::sessions::revoke_session),
+        )
+        // Two-factor authentication of the caller's own account
+        .route("/auth/totp", get(handlers::totp::get_totp_status))
+        .route("/auth/totp/setup", post(handlers::totp::setup_totp))
+        .route("/auth/totp/enable", post(handlers::totp::enable_totp))
+        .route("/auth/totp/disable", post(handlers::totp::disable_totp))
+        .route(
+            "/auth/totp/recovery-codes",
+            post(handlers::totp::regenerate_recovery_codes),
+        )
         .route("/devices", get(handlers::list_devices))
         // WebSocket endpoint for real-time events
         .route("/ws", any(ws_handler))
-        // Stream control endpoints
+        // Stream status endpoints
         .route("/stream/status", get(handlers::stream_state))
-        .route("/stream/start", post(handlers::stream_start))
-        .route("/stream/stop", post(handlers::stream_stop))
         .route("/stream/mode", get(handlers::stream_mode_get))
-        .route("/stream/mode", post(handlers::stream_mode_set))
-        .route("/stream/bitrate", post(handlers::stream_set_bitrate))
         .route("/stream/codecs", get(handlers::stream_codecs_list))
         .route("/stream/constraints", get(handlers::stream_constraints_get))
-        .route(
-            "/video/encoder/self-check",
-            get(handlers::video_encoder_self_check),
-        )
-        // WebRTC endpoints
+        // WebRTC endpoints (HID data channel is only wired for operators)
         .route("/webrtc/session", post(handlers::webrtc_create_session))
         .route("/webrtc/offer", post(handlers::webrtc_offer))
         .route("/webrtc/ice", post(handlers::webrtc_ice_candidate))
         .route("/webrtc/ice-servers", get(handlers::webrtc_ice_servers))
         .route("/webrtc/status", get(handlers::webrtc_status))
         .route("/webrtc/close", post(handlers::webrtc_close_session))
-        // HID endpoints
+        // Device status endpoints
         .route("/hid/status", get(handlers::hid_status))
+        .route("/audio/status", get(handlers::audio_status))
+        .route("/audio/devices", get(handlers::list_audio_devices))
+        .route("/msd/status", get(handlers::msd_status))
+        .route("/atx/status", get(handlers::atx_status))
+        .route("/update/status", get(handlers::update_status))
+        // Audio WebSocket endpoint
+        .route("/ws/audio", any(audio_ws_handler));
+
+    // Stream endpoints (accessible with auth, but typically embedded in pages)
+    let stream_routes = Router::new()
+        .route("/stream", get(handlers::mjpeg_stream))
+        .route("/stream/mjpeg", get(handlers::mjpeg_stream))
+        .route("/snapshot", get(handlers::snapshot));
+
+    // Operator routes: stream control, HID, MSD, ATX and audio control
+    let operator_routes = Router::new()
+        .route("/stream/start", post(handlers::stream_start))
+        .route("/stream/stop", post(handlers::stream_stop))
+        .route("/stream/mode", post(handlers::stream_mode_set))
+        .route("/stream/bitrate", post(handlers::stream_set_bitrate))
+        .route(
+            "/video/encoder/self-check",
+            get(handlers::video_encoder_self_check),
+        )
+        // HID endpoints
         .route("/hid/otg/self-check", get(handlers::hid_otg_self_check))
         .route("/hid/reset", post(handlers::hid_reset))
+        .route("/hid/system", post(handlers::hid_system_control))
+        .route("/hid/events", post(handlers::hid_events))
+        .route("/hid/presets", get(handlers::presets::list_presets))
+        .route(
+            "/hid/presets/{id}/send",
+            post(handlers::presets::send_preset),
+        )
+        .route(
+            "/hid/type",
+            post(handlers::hid_type_text).delete(handlers::hid_type_cancel),
+        )
+        .route("/hid/jiggler", get(handlers::config::get_jiggler))
+        // Recorded macros
+        .route(
+            "/hid/macros",
+            get(handlers::macros::list_macros).post(handlers::macros::import_macro),
+        )
+        .route("/hid/macros/abort", post(handlers::macros::abort_macro))
+        .route(
+            "/hid/macros/record",
+            get(handlers::macros::recording_status),
+        )
+        .route(
+            "/hid/macros/record/start",
+            post(handlers::macros::start_recording),
+        )
+        .route(
+            "/hid/macros/record/stop",
+            post(handlers::macros::stop_recording),
+        )
+        .route(
+            "/hid/macros/{id}",
+            get(handlers::macros::export_macro).delete(handlers::macros::delete_macro),
+        )
+        .route("/hid/macros/{id}/play", post(handlers::macros::play_macro))
+        // Exclusive control lock and presence list
+        .route(
+            "/hid/control",
+            get(handlers::hid_control::get_control)
+                .post(handlers::hid_control::request_control)
+                .delete(handlers::hid_control::release_control),
+        )
         // WebSocket HID endpoint (for MJPEG mode)
         .route("/ws/hid", any(ws_hid_handler))
         // Audio endpoints
-        .route("/audio/status", get(handlers::audio_status))
         .route("/audio/start", post(handlers::start_audio_streaming))
         .route("/audio/stop", post(handlers::stop_audio_streaming))
         .route("/audio/quality", post(handlers::set_audio_quality))
         .route("/audio/device", post(handlers::select_audio_device))
-        .route("/audio/devices", get(handlers::list_audio_devices))
-        // Audio WebSocket endpoint
-        .route("/ws/audio", any(audio_ws_handler))
+        // MSD (Mass Storage Device) endpoints
+        .route("/msd/images", get(handlers::msd_images_list))
+        .route("/msd/images/download", post(handlers::msd_image_download))
+        .route(
+            "/msd/images/download/cancel",
+            post(handlers::msd_image_download_cancel),
+        )
+        .route("/msd/images/{id}", get(handlers::msd_image_get))
+        .route("/msd/images/{id}", delete(handlers::msd_image_delete))
+        .route("/msd/connect", post(handlers::msd_connect))
+        .route("/msd/disconnect", post(handlers::msd_disconnect))
+        // MSD Virtual Drive endpoints
+        .route("/msd/drive", get(handlers::msd_drive_info))
+        .route("/msd/drive", delete(handlers::msd_drive_delete))
+        .route("/msd/drive/init", post(handlers::msd_drive_init))
+        .route("/msd/drive/files", get(handlers::msd_drive_files))
+        .route(
+            "/msd/drive/files/{*path}",
+            get(handlers::msd_drive_download),
+        )
+        .route(
+            "/msd/drive/files/{*path}",
+            delete(handlers::msd_drive_file_delete),
+        )
+        .route("/msd/drive/mkdir/{*path}", post(handlers::msd_drive_mkdir))
+        // ATX (Power Control) endpoints
+        .route("/atx/power", post(handlers::atx_power))
+        .route("/atx/wol", post(handlers::atx_wol))
+        .route("/atx/wol/history", get(handlers::atx_wol_history))
+        // Device discovery endpoints
+        .route("/devices/atx", get(handlers::devices::list_atx_devices))
+        .route("/devices/usb", get(handlers::devices::list_usb_devices));
+
+    // Large file upload routes (MSD images and drive files)
+    // Use streaming upload to support files larger than available RAM
+    // Disable body limit for streaming uploads - files are written directly to disk
+    let upload_routes = Router::new()
+        .route("/msd/images", post(handlers::msd_image_upload))
+        .route("/msd/drive/files", post(handlers::msd_drive_upload))
+        .layer(DefaultBodyLimit::disable());
+
+    // Admin routes: configuration and system management
+    let admin_routes = Router::new()
+        // User management
+        .route("/users", get(handlers::users::list_users))
+        .route("/users", post(handlers::users::create_user))
+        .route("/users/{id}", patch(handlers::users::update_user))
+        .route("/users/{id}", delete(handlers::users::delete_user))
+        // Audit log
+        .route("/audit", get(handlers::audit::list_audit_entries))
+        // Mock HID backend inspection for end-to-end tests
+        .route(
+            "/debug/hid/reports",
+            get(handlers::hid_mock::list_reports).delete(handlers::hid_mock::clear_reports),
+        )
+        .route("/debug/hid/mock", patch(handlers::hid_mock::update_mock))
+        // CH9329 chip parameters (USB IDs and strings, work mode, serial rate)
+        .route(
+            "/hid/ch9329/settings",
+            get(handlers::ch9329::get_settings).patch(handlers::ch9329::update_settings),
+        )
+        .route(
+            "/hid/ch9329/baud-rate",
+            post(handlers::ch9329::switch_baud_rate),
+        )
+        // API tokens
+        .route("/tokens", get(handlers::api_tokens::list_api_tokens))
+        .route("/tokens", post(handlers::api_tokens::create_api_token))
+        .route(
+            "/tokens/{id}",
+            delete(handlers::api_tokens::delete_api_token),
+        )
+        // View-only share links (viewers use the stream routes with ?share=<secret>)
+        .route("/shares", get(handlers::shares::list_share_links))
+        .route("/shares", post(handlers::shares::create_share_link))
+        .route("/shares/{id}", delete(handlers::shares::delete_share_link))
+        // Take HID control away from another user
+        .route(
+            "/hid/control/force",
+            post(handlers::hid_control::force_take_control),
+        )
+        // HTTPS certificate management (applied without restart)
+        .route(
+            "/tls/certificate",
+            get(handlers::tls::get_certificate).put(handlers::tls::install_certificate),
+        )
+        .route("/tls/csr", post(handlers::tls::create_csr))
+        .route(
+            "/tls/self-signed",
+            post(handlers::tls::regenerate_self_signed),
+        )
         // Configuration management (domain-separated endpoints)
         .route("/config", get(handlers::config::get_all_config))
         .route("/config/video", get(handlers::config::get_video_config))
@@ -90,8 +254,14 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         )
         .route("/config/hid", get(handlers::config::get_hid_config))
         .route("/config/hid", patch(handlers::config::update_hid_config))
+        .route("/hid/jiggler", put(handlers::config::update_jiggler))
         .route("/config/msd", get(handlers::config::get_msd_config))
         .route("/config/msd", patch(handlers::config::update_msd_config))
+        .route("/config/access", get(handlers::config::get_access_config))
+        .route(
+            "/config/access",
+            patch(handlers::config::update_access_config),
+        )
         .route("/config/atx", get(handlers::config::get_atx_config))
         .route("/config/atx", patch(handlers::config::update_atx_config))
         .route("/config/audio", get(handlers::config::get_audio_config))
@@ -141,41 +311,6 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/system/restart", post(handlers::system_restart))
         .route("/update/overview", get(handlers::update_overview))
         .route("/update/upgrade", post(handlers::update_upgrade))
-        .route("/update/status", get(handlers::update_status))
-        // MSD (Mass Storage Device) endpoints
-        .route("/msd/status", get(handlers::msd_status))
-        .route("/msd/images", get(handlers::msd_images_list))
-        .route("/msd/images/download", post(handlers::msd_image_download))
-        .route(
-            "/msd/images/download/cancel",
-            post(handlers::msd_image_download_cancel),
-        )
-        .route("/msd/images/{id}", get(handlers::msd_image_get))
-        .route("/msd/images/{id}", delete(handlers::msd_image_delete))
-        .route("/msd/connect", post(handlers::msd_connect))
-        .route("/msd/disconnect", post(handlers::msd_disconnect))
-        // MSD Virtual Drive endpoints
-        .route("/msd/drive", get(handlers::msd_drive_info))
-        .route("/msd/drive", delete(handlers::msd_drive_delete))
-        .route("/msd/drive/init", post(handlers::msd_drive_init))
-        .route("/msd/drive/files", get(handlers::msd_drive_files))
-        .route(
-            "/msd/drive/files/{*path}",
-            get(handlers::msd_drive_download),
-        )
-        .route(
-            "/msd/drive/files/{*path}",
-            delete(handlers::msd_drive_file_delete),
-        )
-        .route("/msd/drive/mkdir/{*path}", post(handlers::msd_drive_mkdir))
-        // ATX (Power Control) endpoints
-        .route("/atx/status", get(handlers::atx_status))
-        .route("/atx/power", post(handlers::atx_power))
-        .route("/atx/wol", post(handlers::atx_wol))
-        .route("/atx/wol/history", get(handlers::atx_wol_history))
-        // Device discovery endpoints
-        .route("/devices/atx", get(handlers::devices::list_atx_devices))
-        .route("/devices/usb", get(handlers::devices::list_usb_devices))
         .route(
             "/devices/usb/reset",
             post(handlers::devices::reset_usb_device),
@@ -213,29 +348,28 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/terminal/ws", get(handlers::terminal::terminal_ws))
         .route("/terminal/{*path}", get(handlers::terminal::terminal_proxy));
 
-    // Protected routes (all authenticated users)
-    let protected_routes = user_routes;
-
-    // Stream endpoints (accessible with auth, but typically embedded in pages)
-    let stream_routes = Router::new()
-        .route("/stream", get(handlers::mjpeg_stream))
-        .route("/stream/mjpeg", get(handlers::mjpeg_stream))
-        .route("/snapshot", get(handlers::snapshot));
-
-    // Large file upload routes (MSD images and drive files)
-    // Use streaming upload to support files larger than available RAM
-    // Disable body limit for streaming uploads - files are written directly to disk
-    let upload_routes = Router::new()
-        .route("/msd/images", post(handlers::msd_image_upload))
-        .route("/msd/drive/files", post(handlers::msd_drive_upload))
-        .layer(DefaultBodyLimit::disable());
+    // Role guards run inside auth_middleware, which attaches the Session
+    let protected_routes = Router::new()
+        .merge(viewer_routes)
+        .merge(stream_routes)
+        .merge(
+            Router::new()
+                .merge(operator_routes)
+                .merge(upload_routes)
+                .route_layer(middleware::from_fn_with_state(
+                    UserRole::Operator,
+                    require_role,
+                )),
+        )
+        .merge(admin_routes.route_layer(middleware::from_fn_with_state(
+            UserRole::Admin,
+            require_role,
+        )));
 
     // Combine API routes
     let api_routes = Router::new()
         .merge(public_routes)
         .merge(protected_routes)
-        .merge(stream_routes)
-        .merge(upload_routes)
         .layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -250,5 +384,9 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .merge(static_routes)
         .layer(TraceLayer::new_for_http())
         .layer(cors)
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            access_middleware,
+        ))
         .with_state(state)
 }
@@ -31,8 +31,8 @@ use super::signaling::{ConnectionState, IceCandidate, SdpAnswer, SdpOffer};
 use super::video_track::{UniversalVideoTrack, UniversalVideoTrackConfig, VideoCodec};
 use crate::audio::OpusFrame;
 use crate::error::{AppError, Result};
-use crate::hid::datachannel::{parse_hid_message, HidChannelEvent};
-use crate::hid::HidController;
+use crate::hid::datachannel::parse_hid_message;
+use crate::hid::{HidController, PresenceGuard};
 use crate::video::types::{
     BitratePreset, EncodedVideoFrame, PixelFormat, Resolution, VideoEncoderType,
 };
@@ -139,6 +139,9 @@ pub struct UniversalSession {
     state_rx: watch::Receiver<ConnectionState>,
     ice_candidates: Arc<Mutex<Vec<IceCandidate>>>,
     hid_controller: Option<Arc<HidController>>,
+    presence: parking_lot::Mutex<Option<PresenceGuard>>,
+    /// Control owner of the client that created the session
+    owner_id: String,
     video_receiver_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
     audio_receiver_handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
     fps: u32,
@@ -336,6 +339,8 @@ impl UniversalSession {
             state_rx,
             ice_candidates: Arc::new(Mutex::new(vec![])),
             hid_controller: None,
+            presence: parking_lot::Mutex::new(None),
+            owner_id: String::new(),
             video_receiver_handle: Mutex::new(None),
             audio_receiver_handle: Mutex::new(None),
             fps: config.fps,
@@ -453,14 +458,32 @@ impl UniversalSession {
             }));
     }
 
-    pub fn set_hid_controller(&mut self, hid: Arc<HidController>) {
+    /// Record the control owner of the client that created the session.
+    pub fn set_owner_id(&mut self, owner_id: String) {
+        self.owner_id = owner_id;
+    }
+
+    pub fn owner_id(&self) -> &str {
+        &self.owner_id
+    }
+
+    /// List this session as a connected client until it is closed.
+    pub fn set_presence(&self, presence: PresenceGuard) {
+        *self.presence.lock() = Some(presence);
+    }
+
+    /// Wire the HID data channel; input is attributed to `owner_id` for the
+    /// control lock.
+    pub fn set_hid_controller(&mut self, hid: Arc<HidController>, owner_id: String) {
         let hid_clone = hid.clone();
         let data_channel = self.data_channel.clone();
+        let owner_id = Arc::new(owner_id);
 
         self.pc
             .on_data_channel(Box::new(move |dc: Arc<RTCDataChannel>| {
                 let data_channel = data_channel.clone();
                 let hid = hid_clone.clone();
+                let owner_id = owner_id.clone();
 
                 Box::pin(async move {
                     info!("Data channel with HID support: {}", dc.label());
@@ -468,26 +491,13 @@ impl UniversalSession {
 
                     dc.on_message(Box::new(move |msg: DataChannelMessage| {
                         let hid = hid.clone();
+                        let owner_id = owner_id.clone();
 
                         // webrtc-rs won't poll this future; spawn HID work for latency.
                         tokio::spawn(async move {
                             if let Some(event) = parse_hid_message(&msg.data) {
-                                match event {
-                                    HidChannelEvent::Keyboard(kb_event) => {
-                                        if let Err(e) = hid.send_keyboard(kb_event).await {
-                                            debug!("Failed to send keyboard event: {}", e);
-                                        }
-                                    }
-                                    HidChannelEvent::Mouse(ms_event) => {
-                                        if let Err(e) = hid.send_mouse(ms_event).await {
-                                            debug!("Failed to send mouse event: {}", e);
-                                        }
-                                    }
-                                    HidChannelEvent::Consumer(consumer_event) => {
-                                        if let Err(e) = hid.send_consumer(consumer_event).await {
-                                            debug!("Failed to send consumer event: {}", e);
-                                        }
-                                    }
+                                if let Err(e) = hid.send_input(&owner_id, event).await {
+                                    debug!("Failed to send HID event: {}", e);
                                 }
                             }
                         });
@@ -872,6 +882,8 @@ impl UniversalSession {
     }
 
     pub async fn close(&self) -> Result<()> {
+        self.presence.lock().take();
+
         if let Some(handle) = self.video_receiver_handle.lock().await.take() {
             handle.abort();
         }
@@ -1,6 +1,6 @@
 //! [`WebRtcStreamer`]: shared [`SharedVideoPipeline`], multiplex [`UniversalSession`] (video/audio/HID DC).
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock as StdRwLock};
@@ -10,7 +10,7 @@ use tracing::{debug, info, trace, warn};
 use crate::audio::{AudioController, OpusFrame};
 use crate::error::{AppError, Result};
 use crate::events::{EventBus, StreamDeviceLostKind, SystemEvent};
-use crate::hid::HidController;
+use crate::hid::{ControlOwner, HidController, InputSource};
 use crate::video::device::{
     enumerate_devices, select_recovery_device, VideoDevice, VideoDeviceRecoveryHint,
 };
@@ -944,7 +944,24 @@ impl WebRtcStreamer {
 
     /// Create a new WebRTC session
     pub async fn create_session(&self) -> Result<String> {
+        self.create_session_with_input(None, true, true).await
+    }
+
+    /// Create a new WebRTC session listed in the HID presence list as `owner`
+    /// (an anonymous per-session owner if `None`); `allow_input = false` leaves
+    /// the HID data channel unwired and `allow_audio = false` keeps audio off
+    /// even while it is enabled globally.
+    pub async fn create_session_with_input(
+        &self,
+        owner: Option<ControlOwner>,
+        allow_input: bool,
+        allow_audio: bool,
+    ) -> Result<String> {
         let session_id = uuid::Uuid::new_v4().to_string();
+        let owner = owner.unwrap_or_else(|| ControlOwner {
+            id: format!("webrtc:{}", session_id),
+            name: "WebRTC client".to_string(),
+        });
         let codec = *self.video_codec.read().await;
 
         // Ensure video pipeline is running
@@ -959,7 +976,7 @@ impl WebRtcStreamer {
             input_format: config.input_format,
             bitrate_preset: config.bitrate_preset,
             fps: config.fps,
-            audio_enabled: *self.audio_enabled.read().await,
+            audio_enabled: allow_audio && *self.audio_enabled.read().await,
         };
         drop(config);
 
@@ -973,8 +990,12 @@ impl WebRtcStreamer {
         // The server only receives it via on_data_channel callback set in set_hid_controller().
         // If server also created a channel, frontend's ondatachannel would overwrite its
         // own channel with server's, but server's channel has no message handler!
+        session.set_owner_id(owner.id.clone());
         if let Some(ref hid) = *self.hid_controller.read().await {
-            session.set_hid_controller(hid.clone());
+            session.set_presence(hid.control().connect(InputSource::WebRtc, owner.clone()));
+            if allow_input {
+                session.set_hid_controller(hid.clone(), owner.id);
+            }
         }
 
         let session = Arc::new(session);
@@ -1097,6 +1118,30 @@ impl WebRtcStreamer {
         })
     }
 
+    /// Control owner that created the session
+    pub async fn session_owner(&self, session_id: &str) -> Option<String> {
+        let sessions = self.sessions.read().await;
+        sessions.get(session_id).map(|s| s.owner_id().to_string())
+    }
+
+    /// Control owners with a session that is still connecting or connected
+    pub async fn active_owners(&self) -> HashSet<String> {
+        self.sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| {
+                !matches!(
+                    s.state(),
+                    ConnectionState::Closed
+                        | ConnectionState::Failed
+                        | ConnectionState::Disconnected
+                )
+            })
+            .map(|s| s.owner_id().to_string())
+            .collect()
+    }
+
     /// List all sessions
     pub async fn list_sessions(&self) -> Vec<SessionInfo> {
         self.sessions
@@ -389,6 +389,7 @@ export const hidApi = {
     y?: number | null
     button?: 'left' | 'right' | 'middle' | null
     scroll?: number | null
+    scroll_x?: number | null
   }) => {
     await ensureHidConnection()
     const event: HidMouseEvent = {
@@ -397,6 +398,7 @@ export const hidApi = {
       y: data.y ?? undefined,
       button: mapButton(data.button ?? undefined),
       scroll: data.scroll ?? undefined,
+      scrollX: data.scroll_x ?? undefined,
     }
     await hidWs.sendMouse(event)
     return { success: true }
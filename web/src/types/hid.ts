@@ -19,6 +19,8 @@ export interface HidMouseEvent {
   y?: number
   button?: number // 0=left, 1=middle, 2=right
   scroll?: number
+  /** Horizontal wheel delta, positive scrolls right */
+  scrollX?: number
 }
 
 /** Consumer control event for HID input (multimedia keys) */
@@ -61,9 +63,9 @@ export function encodeKeyboardEvent(event: HidKeyboardEvent): ArrayBuffer {
   return buffer
 }
 
-/** Encode mouse event to binary format (7 bytes) */
+/** Encode mouse event to binary format (8 bytes) */
 export function encodeMouseEvent(event: HidMouseEvent): ArrayBuffer {
-  const buffer = new ArrayBuffer(7)
+  const buffer = new ArrayBuffer(8)
   const view = new DataView(buffer)
 
   view.setUint8(0, MSG_MOUSE)
@@ -104,6 +106,9 @@ export function encodeMouseEvent(event: HidMouseEvent): ArrayBuffer {
     view.setUint8(6, 0)
   }
 
+  // Horizontal scroll delta
+  view.setInt8(7, event.type === 'scroll' ? (event.scrollX ?? 0) : 0)
+
   return buffer
 }
 